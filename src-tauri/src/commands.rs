@@ -1,4 +1,8 @@
-use crate::database::{Database, Note, Tag, Folder, Project, DatabaseBackup};
+use crate::graph::{Graph, GraphFilters, GraphScope};
+use crate::periodic::Period;
+use crate::readability::ProseStats;
+use crate::project_types::{ProjectType, DEFAULT_PROJECT_TYPE};
+use crate::database::{Database, Note, Task, TaskQuery, WritingDay, WritingStreak, WritingGoalProgress, FocusSession, FocusWeekSummary, ProjectStats, ProjectScaffold, ProjectBundle, DailyRollup, DailyRollupConfig, PeriodicConfig, PeriodicNote, CalendarEntry, Template, TemplateUpdate, NoteFromTemplate, NoteStateFilter, NoteSummaryFilter, NoteSort, NoteSummaryPage, BulkNoteOperation, BulkNoteResult, Tag, TagUpdate, TagTreeNode, TagRewriteResult, Folder, FolderUpdate, Project, DatabaseBackup, UnresolvedLink, RenameNoteResult, Link, LinkPreview, AmbiguousLink, VaultHealthReport, BacklinkContext};
 use serde::{Deserialize, Serialize};
 use std::sync::Mutex;
 use tauri::{State, Manager};
@@ -24,6 +28,7 @@ pub struct UpdateNoteInput {
     title: Option<String>,
     content: Option<String>,
     properties: Option<String>,  // JSON string for note properties
+    folder: Option<String>,      // Moves the note (folder is created if missing)
 }
 
 // Note commands
@@ -51,9 +56,29 @@ pub fn get_note(
 pub fn list_notes(
     state: State<AppState>,
     folder: Option<String>,
+    include_archived: Option<bool>,
 ) -> Result<Vec<Note>, String> {
     let db = state.db.lock().unwrap();
-    db.list_notes(folder.as_deref()).map_err(|e| e.to_string())
+    db.list_notes(folder.as_deref(), include_archived.unwrap_or(false)).map_err(|e| e.to_string())
+}
+
+/// Lightweight note listing with filters, sorting and cursor pagination
+#[tauri::command]
+pub fn list_note_summaries(
+    state: State<AppState>,
+    filter: Option<NoteSummaryFilter>,
+    sort: Option<NoteSort>,
+    cursor: Option<String>,
+    limit: Option<usize>,
+) -> Result<NoteSummaryPage, String> {
+    let db = state.db.lock().unwrap();
+    db.list_note_summaries(
+        &filter.unwrap_or_default(),
+        &sort.unwrap_or_default(),
+        cursor.as_deref(),
+        limit,
+    )
+    .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
@@ -63,8 +88,55 @@ pub fn update_note(
     updates: UpdateNoteInput,
 ) -> Result<Option<Note>, String> {
     let db = state.db.lock().unwrap();
-    db.update_note(&id, updates.title.as_deref(), updates.content.as_deref(), updates.properties.as_deref())
-        .map_err(|e| e.to_string())
+    db.update_and_move_note(
+        &id,
+        updates.title.as_deref(),
+        updates.content.as_deref(),
+        updates.properties.as_deref(),
+        updates.folder.as_deref(),
+    )
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn move_note(
+    state: State<AppState>,
+    id: String,
+    folder: String,
+) -> Result<Option<Note>, String> {
+    let db = state.db.lock().unwrap();
+    db.move_note(&id, &folder).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn restore_note(
+    state: State<AppState>,
+    id: String,
+) -> Result<bool, String> {
+    let db = state.db.lock().unwrap();
+    db.restore_note(&id).map_err(|e| e.to_string())
+}
+
+/// Apply the same operations to many notes in one transaction, with a result per note
+#[tauri::command]
+pub fn bulk_update_notes(
+    state: State<AppState>,
+    ids: Vec<String>,
+    operations: Vec<BulkNoteOperation>,
+) -> Result<Vec<BulkNoteResult>, String> {
+    let db = state.db.lock().unwrap();
+    db.bulk_update_notes(&ids, &operations).map_err(|e| e.to_string())
+}
+
+/// Move several notes into one folder; returns how many moved
+#[tauri::command]
+pub fn move_notes(
+    state: State<AppState>,
+    ids: Vec<String>,
+    folder: String,
+) -> Result<usize, String> {
+    let db = state.db.lock().unwrap();
+    db.move_notes(&ids, &folder).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
@@ -80,9 +152,41 @@ pub fn delete_note(
 pub fn search_notes(
     state: State<AppState>,
     query: String,
+    filter: Option<NoteStateFilter>,
 ) -> Result<Vec<Note>, String> {
     let db = state.db.lock().unwrap();
-    db.search_notes(&query).map_err(|e| e.to_string())
+    db.search_notes(&query, &filter.unwrap_or_default()).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn set_note_pinned(
+    state: State<AppState>,
+    id: String,
+    pinned: bool,
+) -> Result<Option<Note>, String> {
+    let db = state.db.lock().unwrap();
+    db.set_note_pinned(&id, pinned).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn set_note_favorite(
+    state: State<AppState>,
+    id: String,
+    favorite: bool,
+) -> Result<Option<Note>, String> {
+    let db = state.db.lock().unwrap();
+    db.set_note_favorite(&id, favorite).map_err(|e| e.to_string())
+}
+
+/// Archived notes drop out of default listings but stay searchable
+#[tauri::command]
+pub fn set_note_archived(
+    state: State<AppState>,
+    id: String,
+    archived: bool,
+) -> Result<Option<Note>, String> {
+    let db = state.db.lock().unwrap();
+    db.set_note_archived(&id, archived).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
@@ -93,6 +197,64 @@ pub fn get_folders(
     db.get_folders().map_err(|e| e.to_string())
 }
 
+/// Create a folder; nested paths like `research/papers` create missing parents
+#[tauri::command]
+pub fn create_folder(
+    state: State<AppState>,
+    path: String,
+    color: Option<String>,
+    icon: Option<String>,
+) -> Result<Folder, String> {
+    let db = state.db.lock().unwrap();
+    db.create_folder(&path, color.as_deref(), icon.as_deref())
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn update_folder(
+    state: State<AppState>,
+    path: String,
+    updates: FolderUpdate,
+) -> Result<Option<Folder>, String> {
+    let db = state.db.lock().unwrap();
+    db.update_folder(&path, &updates).map_err(|e| e.to_string())
+}
+
+/// Rename a folder to a new full path; subfolders and notes follow
+#[tauri::command]
+pub fn rename_folder(
+    state: State<AppState>,
+    path: String,
+    new_path: String,
+) -> Result<Option<Folder>, String> {
+    let db = state.db.lock().unwrap();
+    db.rename_folder(&path, &new_path).map_err(|e| e.to_string())
+}
+
+/// Move a folder under another folder, or to the top level when `new_parent` is empty
+#[tauri::command]
+pub fn move_folder(
+    state: State<AppState>,
+    path: String,
+    new_parent: Option<String>,
+) -> Result<Option<Folder>, String> {
+    let db = state.db.lock().unwrap();
+    db.move_folder(&path, new_parent.as_deref()).map_err(|e| e.to_string())
+}
+
+/// Delete a folder; its notes move to the inbox, or the delete is refused unless
+/// `move_notes_to_inbox` is set
+#[tauri::command]
+pub fn delete_folder(
+    state: State<AppState>,
+    path: String,
+    move_notes_to_inbox: Option<bool>,
+) -> Result<bool, String> {
+    let db = state.db.lock().unwrap();
+    db.delete_folder(&path, move_notes_to_inbox.unwrap_or(false))
+        .map_err(|e| e.to_string())
+}
+
 // Tag commands
 
 #[tauri::command]
@@ -108,9 +270,20 @@ pub fn filter_notes_by_tags(
     state: State<AppState>,
     tag_ids: Vec<String>,
     match_all: bool,
+    include_descendants: Option<bool>,
 ) -> Result<Vec<Note>, String> {
     let db = state.db.lock().unwrap();
-    db.filter_notes_by_tags(tag_ids, match_all).map_err(|e| e.to_string())
+    db.filter_notes_by_tags(tag_ids, match_all, include_descendants.unwrap_or(false))
+        .map_err(|e| e.to_string())
+}
+
+/// Tags nested by `/` with direct and descendant note counts
+#[tauri::command]
+pub fn get_tag_tree(
+    state: State<AppState>,
+) -> Result<Vec<TagTreeNode>, String> {
+    let db = state.db.lock().unwrap();
+    db.get_tag_tree().map_err(|e| e.to_string())
 }
 
 
@@ -135,6 +308,43 @@ pub fn rename_tag(
     db.rename_tag(&id, &new_name).map_err(|e| e.to_string())
 }
 
+/// Update a tag's name, colour, description, icon, pinned state, sort order or parent override
+#[tauri::command]
+pub fn update_tag(
+    state: State<AppState>,
+    id: String,
+    updates: TagUpdate,
+) -> Result<Option<Tag>, String> {
+    let db = state.db.lock().unwrap();
+    db.update_tag(&id, &updates).map_err(|e| e.to_string())
+}
+
+/// Rename a tag (and its children) everywhere it's written; `dry_run` previews the affected notes
+#[tauri::command]
+pub fn rename_tag_in_notes(
+    state: State<AppState>,
+    id: String,
+    new_name: String,
+    dry_run: Option<bool>,
+) -> Result<Option<TagRewriteResult>, String> {
+    let db = state.db.lock().unwrap();
+    db.rename_tag_in_notes(&id, &new_name, dry_run.unwrap_or(false))
+        .map_err(|e| e.to_string())
+}
+
+/// Merge tags into `target_id`, rewriting note bodies; `dry_run` previews the affected notes
+#[tauri::command]
+pub fn merge_tags(
+    state: State<AppState>,
+    source_ids: Vec<String>,
+    target_id: String,
+    dry_run: Option<bool>,
+) -> Result<Option<TagRewriteResult>, String> {
+    let db = state.db.lock().unwrap();
+    db.merge_tags(&source_ids, &target_id, dry_run.unwrap_or(false))
+        .map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 pub fn delete_tag(
     state: State<AppState>,
@@ -179,9 +389,11 @@ pub fn get_note_tags(
 pub fn get_notes_by_tag(
     state: State<AppState>,
     tag_id: String,
+    include_descendants: Option<bool>,
 ) -> Result<Vec<Note>, String> {
     let db = state.db.lock().unwrap();
-    db.get_notes_by_tag(&tag_id).map_err(|e| e.to_string())
+    db.get_notes_by_tag(&tag_id, include_descendants.unwrap_or(false))
+        .map_err(|e| e.to_string())
 }
 
 // Link commands
@@ -247,6 +459,105 @@ pub fn get_outgoing_links(
     db.get_outgoing_links(&noteId).map_err(|e| e.to_string())
 }
 
+/// Outgoing links of a note with their kind (link/embed), anchor and alias
+#[tauri::command]
+pub fn get_note_links(
+    state: State<AppState>,
+    note_id: String,
+) -> Result<Vec<Link>, String> {
+    let db = state.db.lock().unwrap();
+    db.get_note_links(&note_id).map_err(|e| e.to_string())
+}
+
+/// Text referenced by a link: a `#Heading` section, a `^block`, or the whole note
+#[tauri::command]
+pub fn get_link_preview(
+    state: State<AppState>,
+    note_id: String,
+    anchor: Option<String>,
+) -> Result<Option<LinkPreview>, String> {
+    let db = state.db.lock().unwrap();
+    db.get_link_preview(&note_id, anchor.as_deref()).map_err(|e| e.to_string())
+}
+
+/// Backlinks with the paragraph around each reference, for the backlinks pane
+#[tauri::command]
+pub fn get_backlinks_with_context(
+    state: State<AppState>,
+    note_id: String,
+) -> Result<Vec<BacklinkContext>, String> {
+    let db = state.db.lock().unwrap();
+    db.get_backlinks_with_context(&note_id).map_err(|e| e.to_string())
+}
+
+/// Notes that mention this note's title without linking to it
+#[tauri::command]
+pub fn get_unlinked_mentions(
+    state: State<AppState>,
+    note_id: String,
+) -> Result<Vec<BacklinkContext>, String> {
+    let db = state.db.lock().unwrap();
+    db.get_unlinked_mentions(&note_id).map_err(|e| e.to_string())
+}
+
+/// Nodes and edges for the knowledge graph view, with degree, centrality and clusters
+#[tauri::command]
+pub fn get_graph(
+    state: State<AppState>,
+    scope: GraphScope,
+    depth: Option<u32>,
+    filters: Option<GraphFilters>,
+) -> Result<Graph, String> {
+    let db = state.db.lock().unwrap();
+    db.get_graph(&scope, depth, &filters.unwrap_or_default())
+        .map_err(|e| e.to_string())
+}
+
+/// Report links whose target text matches more than one note
+#[tauri::command]
+pub fn get_ambiguous_links(
+    state: State<AppState>,
+) -> Result<Vec<AmbiguousLink>, String> {
+    let db = state.db.lock().unwrap();
+    db.get_ambiguous_links().map_err(|e| e.to_string())
+}
+
+/// Rename a note and rewrite `[[links]]` to it in every referencing note.
+/// Pass `dry_run: true` to preview which notes would change.
+#[tauri::command]
+pub fn rename_note(
+    state: State<AppState>,
+    id: String,
+    new_title: String,
+    dry_run: Option<bool>,
+) -> Result<Option<RenameNoteResult>, String> {
+    let db = state.db.lock().unwrap();
+    db.rename_note(&id, &new_title, dry_run.unwrap_or(false))
+        .map_err(|e| e.to_string())
+}
+
+/// List `[[links]]` whose target note doesn't exist yet, optionally for one source note
+#[tauri::command]
+pub fn get_unresolved_links(
+    state: State<AppState>,
+    note_id: Option<String>,
+) -> Result<Vec<UnresolvedLink>, String> {
+    let db = state.db.lock().unwrap();
+    db.get_unresolved_links(note_id.as_deref()).map_err(|e| e.to_string())
+}
+
+/// Vault maintenance report: orphans, dead ends, hubs, unfiled, stale inbox,
+/// empty notes and duplicate titles
+#[tauri::command]
+pub fn get_vault_health(
+    state: State<AppState>,
+    stale_days: Option<u32>,
+    hub_min_links: Option<u32>,
+) -> Result<VaultHealthReport, String> {
+    let db = state.db.lock().unwrap();
+    db.get_vault_health(stale_days, hub_min_links).map_err(|e| e.to_string())
+}
+
 // Project commands
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -258,6 +569,8 @@ pub struct CreateProjectInput {
     pub color: Option<String>,
     pub icon: Option<String>,
     pub settings: Option<String>,
+    /// Create the type's folders and starter notes (default: true)
+    pub scaffold: Option<bool>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -277,15 +590,28 @@ pub fn create_project(
     project: CreateProjectInput,
 ) -> Result<Project, String> {
     let db = state.db.lock().unwrap();
-    db.create_project(
-        &project.name,
-        project.description.as_deref(),
-        project.project_type.as_deref().unwrap_or("generic"),
-        project.color.as_deref(),
-        project.icon.as_deref(),
-        project.settings.as_deref(),
-    )
-    .map_err(|e| e.to_string())
+    let name = &project.name;
+    let description = project.description.as_deref();
+    let project_type = project.project_type.as_deref().unwrap_or(DEFAULT_PROJECT_TYPE);
+    let (color, icon, settings) = (project.color.as_deref(), project.icon.as_deref(), project.settings.as_deref());
+
+    // Scaffolding runs in the same transaction, so a failure doesn't leave the project behind
+    let created = if project.scaffold.unwrap_or(true) {
+        db.create_scaffolded_project(name, description, project_type, color, icon, settings)
+    } else {
+        db.create_project(name, description, project_type, color, icon, settings)
+    };
+    created.map_err(|e| e.to_string())
+}
+
+/// Create any missing folders and starter notes for the project's type
+#[tauri::command]
+pub fn scaffold_project(
+    state: State<AppState>,
+    project_id: String,
+) -> Result<Option<ProjectScaffold>, String> {
+    let db = state.db.lock().unwrap();
+    db.scaffold_project(&project_id, chrono::Local::now().date_naive()).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
@@ -301,9 +627,34 @@ pub fn get_project(
 pub fn list_projects(
     state: State<AppState>,
     project_type: Option<String>,
+    include_archived: Option<bool>,
 ) -> Result<Vec<Project>, String> {
     let db = state.db.lock().unwrap();
-    db.list_projects(project_type.as_deref()).map_err(|e| e.to_string())
+    db.list_projects(project_type.as_deref(), include_archived.unwrap_or(false)).map_err(|e| e.to_string())
+}
+
+/// Archived projects drop out of the project list but keep their notes
+#[tauri::command]
+pub fn set_project_archived(
+    state: State<AppState>,
+    id: String,
+    archived: bool,
+) -> Result<Option<Project>, String> {
+    let db = state.db.lock().unwrap();
+    db.set_project_archived(&id, archived).map_err(|e| e.to_string())
+}
+
+/// Copy a project; without notes the copy is an empty template with the same
+/// settings and folders
+#[tauri::command]
+pub fn duplicate_project(
+    state: State<AppState>,
+    id: String,
+    name: Option<String>,
+    include_notes: Option<bool>,
+) -> Result<Option<Project>, String> {
+    let db = state.db.lock().unwrap();
+    db.duplicate_project(&id, name.as_deref(), include_notes.unwrap_or(true)).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
@@ -363,6 +714,62 @@ pub fn get_project_note_count(
     db.get_project_note_count(&project_id).map_err(|e| e.to_string())
 }
 
+/// Overview statistics for a project's dashboard
+#[tauri::command]
+pub fn get_project_stats(
+    state: State<AppState>,
+    project_id: String,
+) -> Result<Option<ProjectStats>, String> {
+    let db = state.db.lock().unwrap();
+    db.get_project_stats(&project_id, chrono::Local::now().date_naive()).map_err(|e| e.to_string())
+}
+
+// Project type commands
+
+#[tauri::command]
+pub fn list_project_types(state: State<AppState>) -> Result<Vec<ProjectType>, String> {
+    let db = state.db.lock().unwrap();
+    db.list_project_types().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn get_project_type(
+    state: State<AppState>,
+    id: String,
+) -> Result<Option<ProjectType>, String> {
+    let db = state.db.lock().unwrap();
+    db.get_project_type(&id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn create_project_type(
+    state: State<AppState>,
+    project_type: ProjectType,
+) -> Result<ProjectType, String> {
+    let db = state.db.lock().unwrap();
+    db.create_project_type(&project_type).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn update_project_type(
+    state: State<AppState>,
+    id: String,
+    project_type: ProjectType,
+) -> Result<Option<ProjectType>, String> {
+    let db = state.db.lock().unwrap();
+    db.update_project_type(&id, &project_type).map_err(|e| e.to_string())
+}
+
+/// Built-in types and types still used by a project can't be deleted
+#[tauri::command]
+pub fn delete_project_type(
+    state: State<AppState>,
+    id: String,
+) -> Result<bool, String> {
+    let db = state.db.lock().unwrap();
+    db.delete_project_type(&id).map_err(|e| e.to_string())
+}
+
 // AI commands
 
 /// Check if a CLI tool is available in PATH
@@ -462,23 +869,13 @@ pub fn run_gemini(prompt: String, context: Option<String>) -> Result<String, Str
 #[tauri::command]
 pub fn get_or_create_daily_note(state: State<AppState>, date: String) -> Result<Note, String> {
     let db = state.db.lock().unwrap();
-    
-    // Check if it exists
-    if let Some(note) = db.get_note_by_title_and_folder(&date, "daily").map_err(|e| e.to_string())? {
-        return Ok(note);
-    }
-    
-    // Create it with Markdown format
-    let content = format!("## {}\n\n", date);
-    
-    let note = db.create_note(&date, &content, "daily", None, None).map_err(|e| e.to_string())?;
-    Ok(note)
+    db.get_or_create_daily_note(&date).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
 pub fn export_to_obsidian(state: State<AppState>, target_path: String) -> Result<String, String> {
     let db = state.db.lock().unwrap();
-    let notes = db.list_notes(None).map_err(|e| e.to_string())?;
+    let notes = db.list_notes(None, true).map_err(|e| e.to_string())?;
     
     let path = std::path::Path::new(&target_path);
     if !path.exists() {
@@ -492,8 +889,18 @@ pub fn export_to_obsidian(state: State<AppState>, target_path: String) -> Result
         let file_path = path.join(filename);
         
         // Simple frontmatter and content
-        let content = format!("---\ntitle: \"{}\"\nfolder: \"{}\"\ncreated: {}\n---\n\n{}", 
-            note.title.replace("\"", "\\\""), note.folder, note.created_at, note.content);
+        let mut states = String::new();
+        if note.pinned {
+            states.push_str("pinned: true\n");
+        }
+        if note.favorite {
+            states.push_str("favorite: true\n");
+        }
+        if note.archived_at.is_some() {
+            states.push_str("archived: true\n");
+        }
+        let content = format!("---\ntitle: \"{}\"\nfolder: \"{}\"\ncreated: {}\n{}---\n\n{}", 
+            note.title.replace("\"", "\\\""), note.folder, note.created_at, states, note.content);
             
         std::fs::write(file_path, content).map_err(|e| e.to_string())?;
     }
@@ -665,6 +1072,344 @@ pub fn export_document(options: ExportOptions) -> Result<ExportResult, String> {
     crate::academic::export_document(&options, &output_dir)
 }
 
+// Template commands
+
+#[tauri::command]
+pub fn list_templates(state: State<AppState>) -> Result<Vec<Template>, String> {
+    let db = state.db.lock().unwrap();
+    db.list_templates().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn get_template(
+    state: State<AppState>,
+    id: String,
+) -> Result<Option<Template>, String> {
+    let db = state.db.lock().unwrap();
+    db.get_template(&id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn create_template(
+    state: State<AppState>,
+    name: String,
+    content: String,
+    description: Option<String>,
+    properties: Option<String>,
+    tags: Option<Vec<String>>,
+) -> Result<Template, String> {
+    let db = state.db.lock().unwrap();
+    db.create_template(
+        &name,
+        &content,
+        description.as_deref(),
+        properties.as_deref(),
+        &tags.unwrap_or_default(),
+    )
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn update_template(
+    state: State<AppState>,
+    id: String,
+    update: TemplateUpdate,
+) -> Result<Option<Template>, String> {
+    let db = state.db.lock().unwrap();
+    db.update_template(&id, &update).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn delete_template(
+    state: State<AppState>,
+    id: String,
+) -> Result<bool, String> {
+    let db = state.db.lock().unwrap();
+    db.delete_template(&id).map_err(|e| e.to_string())
+}
+
+/// Create a note from a template (or the project's default template);
+/// `date` is `YYYY-MM-DD` and defaults to today
+#[tauri::command]
+pub fn create_note_from_template(
+    state: State<AppState>,
+    template_id: Option<String>,
+    title: String,
+    folder: Option<String>,
+    project_id: Option<String>,
+    date: Option<String>,
+) -> Result<NoteFromTemplate, String> {
+    let date = date.as_deref().map(parse_date).transpose()?;
+    let db = state.db.lock().unwrap();
+    db.create_note_from_template(
+        template_id.as_deref(),
+        &title,
+        folder.as_deref().unwrap_or("inbox"),
+        project_id.as_deref(),
+        date,
+    )
+    .map_err(|e| e.to_string())
+}
+
+/// Parse a `YYYY-MM-DD` date argument
+fn parse_date(date: &str) -> Result<chrono::NaiveDate, String> {
+    chrono::NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d").map_err(|_| format!("Invalid date: {}", date))
+}
+
+#[tauri::command]
+pub fn get_daily_note_template(state: State<AppState>) -> Result<Option<Template>, String> {
+    let db = state.db.lock().unwrap();
+    db.get_daily_note_template().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn set_daily_note_template(
+    state: State<AppState>,
+    template_id: Option<String>,
+) -> Result<(), String> {
+    let db = state.db.lock().unwrap();
+    db.set_daily_note_template(template_id.as_deref()).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn set_project_default_template(
+    state: State<AppState>,
+    project_id: String,
+    template_id: Option<String>,
+) -> Result<Option<Project>, String> {
+    let db = state.db.lock().unwrap();
+    db.set_project_default_template(&project_id, template_id.as_deref()).map_err(|e| e.to_string())
+}
+
+// Periodic note commands
+
+/// Get or create the note for the period containing `date` (default: today)
+#[tauri::command]
+pub fn get_or_create_periodic_note(
+    state: State<AppState>,
+    period: Period,
+    date: Option<String>,
+) -> Result<Note, String> {
+    let date = match date {
+        Some(d) => parse_date(&d)?,
+        None => chrono::Local::now().date_naive(),
+    };
+    let db = state.db.lock().unwrap();
+    db.get_or_create_periodic_note(period, date).map_err(|e| e.to_string())
+}
+
+/// The period `offset` steps from the one containing `date` (-1 = previous, 1 = next)
+#[tauri::command]
+pub fn get_periodic_note(
+    state: State<AppState>,
+    period: Period,
+    date: String,
+    offset: Option<i32>,
+) -> Result<PeriodicNote, String> {
+    let date = parse_date(&date)?;
+    let db = state.db.lock().unwrap();
+    db.get_periodic_note(period, date, offset.unwrap_or(0)).map_err(|e| e.to_string())
+}
+
+/// Periods with a note between `from` and `to`, for calendar views
+#[tauri::command]
+pub fn get_periodic_calendar(
+    state: State<AppState>,
+    period: Period,
+    from: String,
+    to: String,
+) -> Result<Vec<CalendarEntry>, String> {
+    let (from, to) = (parse_date(&from)?, parse_date(&to)?);
+    let db = state.db.lock().unwrap();
+    db.get_periodic_calendar(period, from, to).map_err(|e| e.to_string())
+}
+
+/// Carry unfinished tasks and yesterday's notes into the daily note for `date` (default: today)
+#[tauri::command]
+pub fn rollup_daily_note(
+    state: State<AppState>,
+    date: Option<String>,
+) -> Result<DailyRollup, String> {
+    let date = match date {
+        Some(d) => parse_date(&d)?,
+        None => chrono::Local::now().date_naive(),
+    };
+    let db = state.db.lock().unwrap();
+    db.rollup_daily_note(date).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn get_daily_rollup_config(state: State<AppState>) -> Result<DailyRollupConfig, String> {
+    let db = state.db.lock().unwrap();
+    db.get_daily_rollup_config().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn set_daily_rollup_config(
+    state: State<AppState>,
+    config: DailyRollupConfig,
+) -> Result<(), String> {
+    let db = state.db.lock().unwrap();
+    db.set_daily_rollup_config(&config).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn get_periodic_configs(state: State<AppState>) -> Result<Vec<PeriodicConfig>, String> {
+    let db = state.db.lock().unwrap();
+    db.get_periodic_configs().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn set_periodic_config(
+    state: State<AppState>,
+    config: PeriodicConfig,
+) -> Result<PeriodicConfig, String> {
+    let db = state.db.lock().unwrap();
+    db.set_periodic_config(&config).map_err(|e| e.to_string())
+}
+
+// Task operations
+
+/// Indexed checklist items; open tasks only unless `query.include_done` is set
+#[tauri::command]
+pub fn query_tasks(
+    state: State<AppState>,
+    query: Option<TaskQuery>,
+) -> Result<Vec<Task>, String> {
+    let db = state.db.lock().unwrap();
+    db.query_tasks(&query.unwrap_or_default()).map_err(|e| e.to_string())
+}
+
+/// Check or uncheck a task by rewriting its line in the note
+#[tauri::command]
+pub fn toggle_task(
+    state: State<AppState>,
+    note_id: String,
+    line: i64,
+) -> Result<Option<Task>, String> {
+    let db = state.db.lock().unwrap();
+    db.toggle_task(&note_id, line).map_err(|e| e.to_string())
+}
+
+// Writing progress
+
+/// Words written per day between `from` and `to` (`YYYY-MM-DD`), optionally for one project
+#[tauri::command]
+pub fn get_writing_heatmap(
+    state: State<AppState>,
+    from: String,
+    to: String,
+    project_id: Option<String>,
+) -> Result<Vec<WritingDay>, String> {
+    let (from, to) = (parse_date(&from)?, parse_date(&to)?);
+    let db = state.db.lock().unwrap();
+    db.get_writing_heatmap(from, to, project_id.as_deref()).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn get_writing_streak(state: State<AppState>) -> Result<WritingStreak, String> {
+    let db = state.db.lock().unwrap();
+    db.get_writing_streak(chrono::Local::now().date_naive()).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn get_note_goal_progress(
+    state: State<AppState>,
+    note_id: String,
+) -> Result<Option<WritingGoalProgress>, String> {
+    let db = state.db.lock().unwrap();
+    db.get_note_goal_progress(&note_id, chrono::Local::now().date_naive()).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn get_project_goal_progress(
+    state: State<AppState>,
+    project_id: String,
+) -> Result<Option<WritingGoalProgress>, String> {
+    let db = state.db.lock().unwrap();
+    db.get_project_goal_progress(&project_id, chrono::Local::now().date_naive()).map_err(|e| e.to_string())
+}
+
+// Prose statistics
+
+/// Word, sentence and readability statistics for a note or, failing that, a project
+#[tauri::command]
+pub fn get_prose_stats(
+    state: State<AppState>,
+    note_id: Option<String>,
+    project_id: Option<String>,
+) -> Result<Option<ProseStats>, String> {
+    let db = state.db.lock().unwrap();
+    match (note_id, project_id) {
+        (Some(id), _) => db.get_note_prose_stats(&id).map_err(|e| e.to_string()),
+        (None, Some(id)) => db.get_project_prose_stats(&id).map_err(|e| e.to_string()),
+        (None, None) => Err("Either note_id or project_id is required".to_string()),
+    }
+}
+
+// Focus sessions
+
+/// Start a timed session against a note or project
+#[tauri::command]
+pub fn start_focus_session(
+    state: State<AppState>,
+    note_id: Option<String>,
+    project_id: Option<String>,
+    planned_minutes: Option<i64>,
+) -> Result<FocusSession, String> {
+    let db = state.db.lock().unwrap();
+    db.start_focus_session(note_id.as_deref(), project_id.as_deref(), planned_minutes)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn pause_focus_session(state: State<AppState>, id: String) -> Result<Option<FocusSession>, String> {
+    let db = state.db.lock().unwrap();
+    db.pause_focus_session(&id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn resume_focus_session(state: State<AppState>, id: String) -> Result<Option<FocusSession>, String> {
+    let db = state.db.lock().unwrap();
+    db.resume_focus_session(&id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn record_focus_interruption(state: State<AppState>, id: String) -> Result<Option<FocusSession>, String> {
+    let db = state.db.lock().unwrap();
+    db.record_focus_interruption(&id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn stop_focus_session(
+    state: State<AppState>,
+    id: String,
+    reflection: Option<String>,
+) -> Result<Option<FocusSession>, String> {
+    let db = state.db.lock().unwrap();
+    db.stop_focus_session(&id, reflection.as_deref()).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn get_active_focus_session(state: State<AppState>) -> Result<Option<FocusSession>, String> {
+    let db = state.db.lock().unwrap();
+    db.get_active_focus_session().map_err(|e| e.to_string())
+}
+
+/// Focus time by project for the week containing `date` (default: this week)
+#[tauri::command]
+pub fn get_focus_week_summary(
+    state: State<AppState>,
+    date: Option<String>,
+) -> Result<FocusWeekSummary, String> {
+    let date = match date {
+        Some(d) => parse_date(&d)?,
+        None => chrono::Local::now().date_naive(),
+    };
+    let db = state.db.lock().unwrap();
+    db.get_focus_week_summary(date).map_err(|e| e.to_string())
+}
+
 // Project Settings commands
 
 /// Get project settings
@@ -690,17 +1435,21 @@ pub fn update_project_settings(
 
 // Backup and restore commands
 
-fn get_backup_dir(app_handle: tauri::AppHandle) -> Result<PathBuf, String> {
+fn get_app_data_subdir(app_handle: &tauri::AppHandle, name: &str) -> Result<PathBuf, String> {
     let app_data_dir = app_handle
         .path()
         .app_data_dir()
         .map_err(|e| format!("Failed to get app data directory: {}", e))?;
 
-    let backup_dir = app_data_dir.join("backups");
-    fs::create_dir_all(&backup_dir)
-        .map_err(|e| format!("Failed to create backup directory: {}", e))?;
+    let dir = app_data_dir.join(name);
+    fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create {} directory: {}", name, e))?;
+
+    Ok(dir)
+}
 
-    Ok(backup_dir)
+fn get_backup_dir(app_handle: tauri::AppHandle) -> Result<PathBuf, String> {
+    get_app_data_subdir(&app_handle, "backups")
 }
 
 #[tauri::command]
@@ -806,6 +1555,130 @@ pub fn list_backups(app_handle: tauri::AppHandle) -> Result<Vec<BackupInfo>, Str
     Ok(backups)
 }
 
+// Project bundle commands
+
+const PROJECT_BUNDLE_FILE: &str = "bundle.json";
+const PROJECT_BUNDLE_ATTACHMENTS: &str = "attachments";
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProjectBundleExport {
+    /// The bundle directory
+    pub path: String,
+    /// Attachment paths that weren't copied: relative, missing, or unreadable
+    pub skipped_attachments: Vec<String>,
+}
+
+/// Write a project to a `<name>_<timestamp>.scribe-project` directory holding
+/// `bundle.json` and copies of the files its notes refer to. Saved under
+/// `destination` if given, otherwise the app's `exports` directory.
+#[tauri::command]
+pub fn export_project_bundle(
+    app_handle: tauri::AppHandle,
+    state: State<AppState>,
+    project_id: String,
+    destination: Option<String>,
+) -> Result<Option<ProjectBundleExport>, String> {
+    let db = state.db.lock().unwrap();
+    let Some(mut bundle) = db.export_project_bundle(&project_id).map_err(|e| e.to_string())? else {
+        return Ok(None);
+    };
+
+    let parent = match destination {
+        Some(dir) => PathBuf::from(dir),
+        None => get_app_data_subdir(&app_handle, "exports")?,
+    };
+    let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S");
+    let dir_name: String = bundle.project.name
+        .chars()
+        .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+    let bundle_dir = parent.join(format!("{}_{}.scribe-project", dir_name, timestamp));
+    let attachments_dir = bundle_dir.join(PROJECT_BUNDLE_ATTACHMENTS);
+    fs::create_dir_all(&attachments_dir)
+        .map_err(|e| format!("Failed to create bundle directory: {}", e))?;
+
+    // Only absolute paths are copied; there's no base to resolve relative ones against.
+    // Skipped references stay in the notes as written and are reported back.
+    let mut skipped_attachments = Vec::new();
+    bundle.attachments.retain(|attachment| {
+        let source = PathBuf::from(&attachment.path);
+        let copied = source.is_absolute()
+            && source.is_file()
+            && fs::copy(&source, attachments_dir.join(&attachment.file)).is_ok();
+        if !copied {
+            skipped_attachments.push(attachment.path.clone());
+        }
+        copied
+    });
+
+    let json = serde_json::to_string_pretty(&bundle)
+        .map_err(|e| format!("Failed to serialize bundle: {}", e))?;
+    fs::write(bundle_dir.join(PROJECT_BUNDLE_FILE), json)
+        .map_err(|e| format!("Failed to write bundle file: {}", e))?;
+
+    Ok(Some(ProjectBundleExport {
+        path: bundle_dir.to_string_lossy().to_string(),
+        skipped_attachments,
+    }))
+}
+
+/// Import a bundle directory (or its `bundle.json`) as a new project.
+/// Attachments are copied into the app's `attachments` directory and the
+/// notes are pointed at the copies.
+#[tauri::command]
+pub fn import_project_bundle(
+    app_handle: tauri::AppHandle,
+    state: State<AppState>,
+    bundle_path: String,
+    name: Option<String>,
+) -> Result<Project, String> {
+    let mut bundle_dir = PathBuf::from(&bundle_path);
+    if bundle_dir.is_file() {
+        bundle_dir.pop();
+    }
+
+    let json = fs::read_to_string(bundle_dir.join(PROJECT_BUNDLE_FILE))
+        .map_err(|e| format!("Failed to read bundle file: {}", e))?;
+    let bundle: ProjectBundle = serde_json::from_str(&json)
+        .map_err(|e| format!("Failed to parse bundle file: {}", e))?;
+
+    let mut attachments = std::collections::HashMap::new();
+    let mut target_dir = None;
+    if !bundle.attachments.is_empty() {
+        let dir = get_app_data_subdir(&app_handle, "attachments")?.join(uuid::Uuid::new_v4().to_string());
+        fs::create_dir_all(&dir)
+            .map_err(|e| format!("Failed to create attachments directory: {}", e))?;
+        target_dir = Some(dir);
+    }
+
+    let copied = target_dir.as_ref().map_or(Ok(()), |dir| {
+        for attachment in &bundle.attachments {
+            // Bundle file names are ours, but don't follow one out of the bundle
+            let file_name = PathBuf::from(&attachment.file);
+            let Some(file_name) = file_name.file_name() else {
+                continue;
+            };
+            let target = dir.join(file_name);
+            fs::copy(bundle_dir.join(PROJECT_BUNDLE_ATTACHMENTS).join(file_name), &target)
+                .map_err(|e| format!("Failed to copy attachment {}: {}", attachment.file, e))?;
+            attachments.insert(attachment.path.clone(), target.to_string_lossy().to_string());
+        }
+        Ok(())
+    });
+
+    let imported = copied.and_then(|()| {
+        let db = state.db.lock().unwrap();
+        db.import_project_bundle(&bundle, name.as_deref(), &attachments).map_err(|e| e.to_string())
+    });
+
+    // Don't leave copies behind for a project that wasn't imported
+    if let (Err(_), Some(dir)) = (&imported, &target_dir) {
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    imported
+}
+
 // Chat history operations
 
 #[tauri::command]
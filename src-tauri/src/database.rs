@@ -2,7 +2,13 @@ use rusqlite::{Connection, Result as SqlResult, params_from_iter, params};
 use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue;
 use tauri::{AppHandle, Manager};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use crate::markdown;
+use crate::templates::{self, TemplateContext};
+use crate::periodic::Period;
+use crate::readability::{self, ProseStats};
+use crate::project_types::{self, BibliographyMode, ProjectType};
+use crate::graph::{self, Graph, GraphEdge, GraphFilters, GraphNode, GraphScope};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Note {
@@ -15,6 +21,14 @@ pub struct Note {
     pub created_at: i64,
     pub updated_at: i64,
     pub deleted_at: Option<i64>,
+    /// Kept at the top of listings
+    #[serde(default)]
+    pub pinned: bool,
+    #[serde(default)]
+    pub favorite: bool,
+    /// Archived notes are hidden from default listings but still show up in search
+    #[serde(default)]
+    pub archived_at: Option<i64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -36,6 +50,9 @@ pub struct Project {
     pub settings: Option<String>,
     pub created_at: i64,
     pub updated_at: i64,
+    /// Archived projects are left out of `list_projects` but keep their notes
+    #[serde(default)]
+    pub archived_at: Option<i64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -44,6 +61,65 @@ pub struct Tag {
     pub name: String,
     pub color: Option<String>,
     pub created_at: i64,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub icon: Option<String>,
+    #[serde(default)]
+    pub pinned: bool,
+    #[serde(default)]
+    pub sort_order: i64,
+    /// Show this tag under another tag in the tree instead of its `/` parent
+    #[serde(default)]
+    pub parent_id: Option<String>,
+}
+
+/// Changes for `update_tag`; `None` leaves a field alone and an empty string clears it
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TagUpdate {
+    pub name: Option<String>,
+    pub color: Option<String>,
+    pub description: Option<String>,
+    pub icon: Option<String>,
+    pub pinned: Option<bool>,
+    pub sort_order: Option<i64>,
+    pub parent_id: Option<String>,
+}
+
+const NOTE_COLUMNS: &str =
+    "notes.id, notes.title, notes.content, notes.folder, notes.project_id, notes.properties, notes.created_at, notes.updated_at, notes.deleted_at, notes.pinned, notes.favorite, notes.archived_at";
+
+const PROJECT_COLUMNS: &str =
+    "projects.id, projects.name, projects.description, projects.type, projects.color, projects.icon, projects.settings, projects.created_at, projects.updated_at, projects.archived_at";
+
+const TAG_COLUMNS: &str =
+    "tags.id, tags.name, tags.color, tags.created_at, tags.description, tags.icon, tags.pinned, tags.sort_order, tags.parent_id";
+
+/// Default tag colours: mid-tone hues that stay readable on both light and dark themes
+const TAG_PALETTE: [&str; 12] = [
+    "#E5484D", "#F76B15", "#D9A100", "#46A758", "#12A594", "#00A2C7",
+    "#0090FF", "#3E63DD", "#8E4EC6", "#D6409F", "#AD7F58", "#6E7F96",
+];
+
+/// A node in the hierarchical tag tree (`#research/statistics` nests under `#research`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TagTreeNode {
+    /// Last path segment, e.g. "statistics"
+    pub name: String,
+    /// Full tag path, e.g. "research/statistics"
+    pub path: String,
+    /// None for intermediate paths that were never used as a tag themselves
+    pub id: Option<String>,
+    pub color: Option<String>,
+    pub description: Option<String>,
+    pub icon: Option<String>,
+    pub pinned: bool,
+    pub sort_order: i64,
+    /// Live notes tagged with exactly this tag
+    pub direct_count: usize,
+    /// Live notes tagged with this tag or any descendant, each counted once
+    pub total_count: usize,
+    pub children: Vec<TagTreeNode>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -54,6 +130,197 @@ pub struct Folder {
     pub sort_order: i32,
 }
 
+/// Changes for `update_folder`; `None` leaves a field alone and an empty string clears it
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FolderUpdate {
+    pub color: Option<String>,
+    pub icon: Option<String>,
+    pub sort_order: Option<i32>,
+}
+
+/// Pinned/favourite/archived conditions; `None` matches either state
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NoteStateFilter {
+    #[serde(default)]
+    pub pinned: Option<bool>,
+    #[serde(default)]
+    pub favorite: Option<bool>,
+    #[serde(default)]
+    pub archived: Option<bool>,
+}
+
+impl NoteStateFilter {
+    /// `AND ...` conditions on the notes table (or alias) `table`
+    fn sql(&self, table: &str) -> String {
+        let mut sql = String::new();
+        if let Some(pinned) = self.pinned {
+            sql.push_str(&format!(" AND {}.pinned = {}", table, pinned as i32));
+        }
+        if let Some(favorite) = self.favorite {
+            sql.push_str(&format!(" AND {}.favorite = {}", table, favorite as i32));
+        }
+        if let Some(archived) = self.archived {
+            let not = if archived { "NOT " } else { "" };
+            sql.push_str(&format!(" AND {}.archived_at IS {}NULL", table, not));
+        }
+        sql
+    }
+}
+
+/// Which notes `list_note_summaries` returns
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NoteSummaryFilter {
+    #[serde(default)]
+    pub folder: Option<String>,
+    /// Also match notes in subfolders of `folder`
+    #[serde(default)]
+    pub include_subfolders: bool,
+    #[serde(default)]
+    pub project_id: Option<String>,
+    /// Notes must carry every one of these tags
+    #[serde(default)]
+    pub tag_ids: Vec<String>,
+    /// Archived notes are hidden unless `archived` is `Some(true)`, which lists only archived notes
+    #[serde(default, flatten)]
+    pub state: NoteStateFilter,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "field", rename_all = "snake_case")]
+pub enum NoteSortField {
+    Title,
+    Created,
+    #[default]
+    Updated,
+    WordCount,
+    /// Value of a note property; notes without it come last
+    Property { key: String },
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NoteSort {
+    #[serde(flatten)]
+    pub field: NoteSortField,
+    /// Defaults to newest/largest first for dates and word count, A→Z otherwise
+    #[serde(default)]
+    pub descending: Option<bool>,
+}
+
+/// A note without its body, for sidebars and lists
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NoteSummary {
+    pub id: String,
+    pub title: String,
+    pub folder: String,
+    pub project_id: Option<String>,
+    pub project_name: Option<String>,
+    pub tags: Vec<String>,
+    pub word_count: i64,
+    pub preview: String,
+    pub created_at: i64,
+    pub updated_at: i64,
+    pub pinned: bool,
+    pub favorite: bool,
+    pub archived_at: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NoteSummaryPage {
+    pub notes: Vec<NoteSummary>,
+    /// Pass back as `cursor` to get the next page; `None` on the last page
+    pub next_cursor: Option<String>,
+}
+
+/// Position after the last note of a page, handed out as an opaque string
+#[derive(Debug, Serialize, Deserialize)]
+struct NoteCursor {
+    #[serde(default)]
+    pinned: bool,
+    missing: bool,
+    value: JsonValue,
+    id: String,
+}
+
+const TEMPLATE_COLUMNS: &str = "id, name, description, content, properties, tags, created_at, updated_at";
+
+/// `app_settings` key holding the id of the template used for new daily notes
+const DAILY_TEMPLATE_SETTING: &str = "daily_note_template_id";
+/// Key in a project's settings JSON naming its default template
+const PROJECT_TEMPLATE_SETTING: &str = "defaultTemplateId";
+
+/// `app_settings` key prefix for periodic note configs (`periodic.weekly`, ...)
+const PERIODIC_SETTING_PREFIX: &str = "periodic.";
+
+const DAILY_ROLLUP_SETTING: &str = "daily_rollup";
+/// Who wrote a note edit. Only the user's own words go into the writing log;
+/// rollups, task toggles, templates and imports don't.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum NoteEdit {
+    User,
+    Generated,
+}
+
+const ROLLUP_TASKS_HEADING: &str = "## Carried over";
+const ROLLUP_LINKS_HEADING: &str = "## Yesterday";
+
+/// Note properties and project settings keys holding writing goals
+const NOTE_WORD_GOAL_PROPERTY: &str = "word_goal";
+const NOTE_DEADLINE_PROPERTY: &str = "due";
+const PROJECT_WORD_GOAL_SETTING: &str = "wordGoal";
+const PROJECT_DEADLINE_SETTING: &str = "deadline";
+
+const FOCUS_RUNNING: &str = "running";
+const FOCUS_PAUSED: &str = "paused";
+const FOCUS_COMPLETED: &str = "completed";
+
+/// Live sessions report their elapsed time so far
+const FOCUS_SESSION_COLUMNS: &str = "id, note_id, project_id, planned_minutes, status, started_at, ended_at,
+    CASE WHEN status = 'completed' THEN duration_seconds
+         ELSE COALESCE(paused_at, CAST(strftime('%s', 'now') AS INTEGER)) - started_at - paused_seconds END,
+    words_written, interruptions, reflection";
+
+const PROJECT_TYPE_COLUMNS: &str = "id, name, description, icon, color, folders, starter_notes, properties, export_preset, bibliography, builtin, sort_order";
+/// Key in a project's settings holding its citation style
+const PROJECT_CITATION_STYLE_SETTING: &str = "citationStyle";
+
+const PROJECT_BUNDLE_VERSION: &str = "1.0";
+
+const PROJECT_TIMELINE_DAYS: u64 = 30;
+const PROJECT_RECENT_NOTES: usize = 5;
+
+const DEFAULT_PAGE_SIZE: usize = 50;
+const MAX_PAGE_SIZE: usize = 500;
+const PREVIEW_CHARS: usize = 160;
+/// How much of a note body (after frontmatter) the preview is built from
+const PREVIEW_SOURCE_CHARS: usize = 2000;
+
+/// One change applied by `bulk_update_notes`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BulkNoteOperation {
+    MoveFolder { folder: String },
+    /// `None` removes the note from its project
+    AssignProject { project_id: Option<String> },
+    /// Tags are written into the note body, so they survive the next edit
+    AddTag { tag: String },
+    RemoveTag { tag: String },
+    /// A `null` value removes the property
+    SetProperty { key: String, property_type: PropertyType, value: JsonValue },
+    Trash,
+    Restore,
+}
+
+/// Outcome of a bulk update for one note; a failed note leaves no partial changes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BulkNoteResult {
+    pub note_id: String,
+    pub success: bool,
+    pub error: Option<String>,
+}
+
+/// Folders the app relies on; they can't be renamed or deleted
+const SYSTEM_FOLDERS: [&str; 4] = ["inbox", "notes", "archive", "daily"];
+
 // Property validation types
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -95,1607 +362,5942 @@ pub struct DatabaseBackup {
     pub folders: Vec<Folder>,
     pub note_tags: Vec<NoteTag>,
     pub links: Vec<Link>,
+    #[serde(default)]
+    pub templates: Vec<Template>,
+    #[serde(default)]
+    pub project_types: Vec<ProjectType>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct NoteTag {
-    pub note_id: String,
-    pub tag_id: String,
+pub struct Template {
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+    /// Markdown body with `{{placeholders}}` (see `templates.rs`)
+    pub content: String,
+    /// Default properties, same JSON shape as `Note::properties`; text values may use placeholders
+    pub properties: Option<String>,
+    /// Tags added to every note made from the template
+    pub tags: Vec<String>,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+/// Fields to change on a template; `None` leaves a field as is, an empty string clears it
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct TemplateUpdate {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub content: Option<String>,
+    pub properties: Option<String>,
+    pub tags: Option<Vec<String>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Link {
-    pub source_note_id: String,
-    pub target_note_id: String,
+pub struct NoteFromTemplate {
+    pub note: Note,
+    /// Char offset in `note.content` where the template put `{{cursor}}`
+    pub cursor: Option<usize>,
 }
 
-impl Database {
-    pub fn new(app_handle: &AppHandle) -> SqlResult<Self> {
-        let app_data_dir = app_handle
-            .path()
-            .app_data_dir()
-            .expect("Failed to get app data directory");
+/// Where and how notes for one period are created
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PeriodicConfig {
+    pub period: Period,
+    pub folder: String,
+    /// Moment-style title format, e.g. `GGGG-[W]ww`
+    pub title_format: String,
+    pub template_id: Option<String>,
+}
 
-        std::fs::create_dir_all(&app_data_dir).expect("Failed to create app data directory");
+/// One period and its note, if it has been created
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PeriodicNote {
+    pub period: Period,
+    /// First and last day of the period (`YYYY-MM-DD`)
+    pub start_date: String,
+    pub end_date: String,
+    pub title: String,
+    pub note: Option<Note>,
+}
 
-        let db_path = app_data_dir.join("scribe.db");
-        let conn = Connection::open(db_path)?;
+/// What to carry into a new daily note
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DailyRollupConfig {
+    /// Roll up automatically when today's daily note is created
+    pub enabled: bool,
+    /// How many days back to look for unfinished `- [ ]` items
+    pub lookback_days: u32,
+    /// Link the notes edited the day before
+    pub link_recent_notes: bool,
+}
 
-        // Enable WAL mode for better performance
-        conn.pragma_update(None, "journal_mode", "WAL")?;
+impl Default for DailyRollupConfig {
+    fn default() -> Self {
+        Self { enabled: true, lookback_days: 7, link_recent_notes: true }
+    }
+}
 
-        let mut db = Database { conn };
-        db.initialize()?;
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DailyRollup {
+    pub note_id: String,
+    pub carried_tasks: usize,
+    /// Daily notes the tasks came from; their items are now marked `- [>]`
+    pub source_note_ids: Vec<String>,
+    pub linked_notes: usize,
+}
 
-        Ok(db)
-    }
+/// A checklist item indexed from a note. Tasks are identified by their note
+/// and zero-based line, which is also what `toggle_task` takes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Task {
+    pub note_id: String,
+    pub note_title: String,
+    pub project_id: Option<String>,
+    pub line: i64,
+    /// Text with the due date and priority markers removed
+    pub text: String,
+    /// The character between the brackets: ' ', 'x', '>', ...
+    pub status: String,
+    pub checked: bool,
+    /// `YYYY-MM-DD`, from `📅 2026-10-20` or `due:2026-10-20`
+    pub due_date: Option<String>,
+    /// highest, high, medium, low or lowest
+    pub priority: Option<String>,
+    pub tags: Vec<String>,
+}
 
-    #[cfg(test)]
-    pub fn new_with_path<P: AsRef<std::path::Path>>(path: P) -> SqlResult<Self> {
-        let conn = Connection::open(path)?;
-        let mut db = Database { conn };
-        db.initialize()?;
-        Ok(db)
-    }
-    
-    fn initialize(&mut self) -> SqlResult<()> {
-        // Create schema_version table
-        self.conn.execute(
-            "CREATE TABLE IF NOT EXISTS schema_version (
-                version INTEGER PRIMARY KEY,
-                applied_at INTEGER DEFAULT (strftime('%s', 'now'))
-            )",
-            [],
-        )?;
-        
-        let current_version = self.get_current_schema_version()?;
-        
-        if current_version < 1 {
-            self.run_migration_001()?;
-            self.conn.execute("INSERT INTO schema_version (version) VALUES (?)", [1])?;
-        }
-        
-        if current_version < 2 {
-            self.run_migration_002()?;
-            self.conn.execute("INSERT INTO schema_version (version) VALUES (?)", [2])?;
-        }
-        
-        if current_version < 3 {
-            self.run_migration_003()?;
-            self.conn.execute("INSERT INTO schema_version (version) VALUES (?)", [3])?;
-        }
+/// Filter for `query_tasks`. By default only open tasks are returned.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TaskQuery {
+    /// Also return checked and migrated tasks
+    pub include_done: bool,
+    /// Due on or after / on or before (`YYYY-MM-DD`); setting either skips undated tasks
+    pub due_from: Option<String>,
+    pub due_to: Option<String>,
+    pub project_id: Option<String>,
+    /// Tag name written in the task itself; child tags match too
+    pub tag: Option<String>,
+    pub note_id: Option<String>,
+}
 
-        if current_version < 4 {
-            self.run_migration_004()?;
-            self.conn.execute("INSERT INTO schema_version (version) VALUES (?)", [4])?;
-        }
+/// Words written on one day, for the heatmap
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WritingDay {
+    pub date: String,
+    pub words_added: i64,
+    pub words_removed: i64,
+}
 
-        if current_version < 5 {
-            self.run_migration_005()?;
-            self.conn.execute("INSERT INTO schema_version (version) VALUES (?)", [5])?;
-        }
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WritingStreak {
+    /// Consecutive days with words written, ending today (or yesterday if nothing yet today)
+    pub current: u32,
+    pub longest: u32,
+    pub words_today: i64,
+    pub last_written: Option<String>,
+}
 
-        if current_version < 6 {
-            self.run_migration_006()?;
-            self.conn.execute("INSERT INTO schema_version (version) VALUES (?)", [6])?;
-        }
+/// Progress toward a note's `word_goal`/`due` properties or a project's `wordGoal`/`deadline` settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WritingGoalProgress {
+    pub word_count: i64,
+    pub word_goal: Option<i64>,
+    pub words_remaining: Option<i64>,
+    /// 0-100, capped
+    pub percent: Option<f64>,
+    pub deadline: Option<String>,
+    /// Days left including today; negative once the deadline has passed
+    pub days_left: Option<i64>,
+    /// Words per remaining day needed to meet the goal by the deadline
+    pub daily_target: Option<i64>,
+    pub words_today: i64,
+}
 
-        if current_version < 7 {
-            self.run_migration_007_seed_demo_data()?;
-            self.conn.execute("INSERT INTO schema_version (version) VALUES (?)", [7])?;
-        }
+/// A timed focus session against a note or project
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FocusSession {
+    pub id: String,
+    pub note_id: Option<String>,
+    pub project_id: Option<String>,
+    pub planned_minutes: Option<i64>,
+    /// running, paused or completed
+    pub status: String,
+    pub started_at: i64,
+    pub ended_at: Option<i64>,
+    /// Time spent focused so far, pauses excluded
+    pub duration_seconds: i64,
+    /// Net words added to the note (or project) during the session
+    pub words_written: i64,
+    pub interruptions: i64,
+    pub reflection: Option<String>,
+}
 
-        if current_version < 8 {
-            self.run_migration_008_add_properties_to_fts()?;
-            self.conn.execute("INSERT INTO schema_version (version) VALUES (?)", [8])?;
-        }
+/// Focus time for one project (or none) within a week
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FocusProjectTime {
+    pub project_id: Option<String>,
+    pub project_name: Option<String>,
+    pub total_seconds: i64,
+    pub sessions: i64,
+    pub words_written: i64,
+}
 
-        if current_version < 9 {
-            self.run_migration_009_chat_history()?;
-            self.conn.execute("INSERT INTO schema_version (version) VALUES (?)", [9])?;
-        }
+/// Completed focus sessions in one week, largest projects first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FocusWeekSummary {
+    /// Monday and Sunday of the week (`YYYY-MM-DD`)
+    pub start_date: String,
+    pub end_date: String,
+    pub total_seconds: i64,
+    pub sessions: i64,
+    pub words_written: i64,
+    pub interruptions: i64,
+    pub projects: Vec<FocusProjectTime>,
+}
 
-        if current_version < 10 {
-            self.run_migration_010_add_project_icons()?;
-            self.conn.execute("INSERT INTO schema_version (version) VALUES (?)", [10])?;
-        }
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectTagCount {
+    pub tag_id: String,
+    pub name: String,
+    pub color: Option<String>,
+    pub note_count: i64,
+}
 
-        Ok(())
-    }
-    
-    #[cfg(not(test))]
-    fn get_current_schema_version(&self) -> SqlResult<i32> {
-        let version: Result<i32, _> = self.conn.query_row(
-            "SELECT MAX(version) FROM schema_version",
-            [],
-            |row| row.get(0),
-        );
-        Ok(version.unwrap_or(0))
-    }
+/// Notes created and words written in a project on one day
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectActivityDay {
+    pub date: String,
+    pub notes_created: i64,
+    pub words_added: i64,
+}
 
-    #[cfg(test)]
-    pub(crate) fn get_current_schema_version(&self) -> SqlResult<i32> {
-        let version: Result<i32, _> = self.conn.query_row(
-            "SELECT MAX(version) FROM schema_version",
-            [],
-            |row| row.get(0),
-        );
-        Ok(version.unwrap_or(0))
-    }
-    
-    fn run_migration_001(&self) -> SqlResult<()> {
-        println!("✅ Database initialized (schema version 1)");
-        
-        self.conn.execute_batch(
-            "CREATE TABLE notes (
-                id TEXT PRIMARY KEY DEFAULT (lower(hex(randomblob(16)))),
-                title TEXT NOT NULL,
-                content TEXT DEFAULT '',
-                folder TEXT DEFAULT 'inbox',
-                created_at INTEGER DEFAULT (strftime('%s', 'now')),
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectRecentNote {
+    pub id: String,
+    pub title: String,
+    pub word_count: i64,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+/// Overview of a project for Mission Control
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectStats {
+    pub project_id: String,
+    pub note_count: i64,
+    pub total_words: i64,
+    pub words_added_7_days: i64,
+    pub words_added_30_days: i64,
+    /// Tags on the project's notes, most used first
+    pub tags: Vec<ProjectTagCount>,
+    /// Resolved links from the project's notes, and how many stay inside the project
+    pub link_count: i64,
+    pub internal_link_count: i64,
+    /// Links per note
+    pub link_density: f64,
+    pub open_tasks: i64,
+    pub completed_tasks: i64,
+    /// Latest `updated_at` of the project's notes
+    pub last_activity: Option<i64>,
+    /// Days with activity in the last 30 days, oldest first
+    pub timeline: Vec<ProjectActivityDay>,
+    pub recent_notes: Vec<ProjectRecentNote>,
+}
+
+/// What `scaffold_project` added
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProjectScaffold {
+    pub folders: Vec<String>,
+    pub notes: Vec<Note>,
+}
+
+/// A chat session in a project bundle, with its messages
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleChatSession {
+    pub id: String,
+    pub note_id: String,
+    pub created_at: i64,
+    pub updated_at: i64,
+    pub messages: Vec<BundleChatMessage>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleChatMessage {
+    pub role: String,
+    pub content: String,
+    pub timestamp: i64,
+}
+
+/// A file a note refers to. `path` is the reference as written in the notes;
+/// `file` is the copy's name in the bundle's `attachments` directory.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleAttachment {
+    pub path: String,
+    pub file: String,
+}
+
+/// One project with everything needed to recreate it in another vault.
+/// Ids are the exporting vault's; `import_project_bundle` gives everything new ones.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectBundle {
+    pub version: String,
+    pub timestamp: i64,
+    pub project: Project,
+    #[serde(default)]
+    pub settings: Option<String>,
+    /// The project's type when it isn't built in
+    #[serde(default)]
+    pub project_type: Option<ProjectType>,
+    pub notes: Vec<Note>,
+    #[serde(default)]
+    pub folders: Vec<Folder>,
+    #[serde(default)]
+    pub tags: Vec<Tag>,
+    #[serde(default)]
+    pub note_tags: Vec<NoteTag>,
+    #[serde(default)]
+    pub links: Vec<Link>,
+    #[serde(default)]
+    pub chat_sessions: Vec<BundleChatSession>,
+    #[serde(default)]
+    pub attachments: Vec<BundleAttachment>,
+}
+
+/// A period that has a note, for calendar dots
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CalendarEntry {
+    pub date: String,
+    pub note_id: String,
+    pub title: String,
+    pub word_count: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NoteTag {
+    pub note_id: String,
+    pub tag_id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Link {
+    pub source_note_id: String,
+    pub target_note_id: Option<String>,  // None while the target note doesn't exist
+    #[serde(default)]
+    pub target_title: String,  // Note title as written in the source note
+    #[serde(default = "default_link_kind")]
+    pub kind: String,  // "link" or "embed"
+    #[serde(default)]
+    pub anchor: Option<String>,  // "#Heading" or "^block-id"
+    #[serde(default)]
+    pub alias: Option<String>,
+}
+
+fn default_link_kind() -> String {
+    markdown::LINK_KIND_LINK.to_string()
+}
+
+/// Text a link points at, for previews and transclusions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LinkPreview {
+    pub note_id: String,
+    pub title: String,
+    pub anchor: Option<String>,
+    pub content: String,
+}
+
+/// One place a note is referenced from another note
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LinkContext {
+    /// The paragraph or list item holding the reference
+    pub context: String,
+    /// 1-based line of the reference
+    pub line: usize,
+    /// Character offsets of the reference in the source note
+    pub start: usize,
+    pub end: usize,
+    /// Character offsets of `context` in the source note
+    pub context_start: usize,
+    pub context_end: usize,
+    /// "link", "embed", or "mention" for unlinked mentions
+    pub kind: String,
+    pub anchor: Option<String>,
+    pub alias: Option<String>,
+}
+
+/// A referencing note with every place it mentions the target
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BacklinkContext {
+    pub note_id: String,
+    pub title: String,
+    pub folder: String,
+    pub updated_at: i64,
+    pub mentions: Vec<LinkContext>,
+}
+
+/// A note whose `[[links]]` were (or would be) rewritten by a rename
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RenamedLinkChange {
+    pub note_id: String,
+    pub title: String,
+    pub occurrences: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RenameNoteResult {
+    pub note_id: String,
+    pub old_title: String,
+    pub new_title: String,
+    pub dry_run: bool,
+    pub modified_notes: Vec<RenamedLinkChange>,
+}
+
+/// A tag renamed, or folded into an existing tag, by a tag rename or merge
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TagRename {
+    pub tag_id: String,
+    pub old_name: String,
+    pub new_name: String,
+    /// Set when a tag with the new name already existed and this one was merged into it
+    pub merged_into: Option<String>,
+}
+
+/// A note whose `#tags` were (or would be) rewritten
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TagNoteChange {
+    pub note_id: String,
+    pub title: String,
+    pub occurrences: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TagRewriteResult {
+    pub dry_run: bool,
+    pub tags: Vec<TagRename>,
+    pub modified_notes: Vec<TagNoteChange>,
+}
+
+/// A note that a link's target text could refer to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LinkCandidate {
+    pub id: String,
+    pub title: String,
+    pub folder: String,
+    pub project_id: Option<String>,
+    pub project_name: Option<String>,
+}
+
+/// A link whose target text matches more than one note
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AmbiguousLink {
+    pub source_note_id: String,
+    pub source_title: String,
+    pub target_title: String,
+    pub target_note_id: Option<String>,  // The candidate the link currently resolves to
+    pub candidates: Vec<LinkCandidate>,
+}
+
+/// A wiki link whose target note doesn't exist (yet)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnresolvedLink {
+    pub source_note_id: String,
+    pub source_title: String,
+    pub target_title: String,
+    pub created_at: i64,
+}
+
+/// A note listed in the vault health report
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthNote {
+    pub id: String,
+    pub title: String,
+    pub folder: String,
+    pub project_id: Option<String>,
+    pub inbound_links: usize,
+    pub outbound_links: usize,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+/// Live notes sharing a title (case-insensitive)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DuplicateTitle {
+    pub title: String,
+    pub notes: Vec<HealthNote>,
+}
+
+/// Weekly "tidy up" report for the vault
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VaultHealthReport {
+    /// No links in or out
+    pub orphans: Vec<HealthNote>,
+    /// Linked to, but no outgoing links
+    pub dead_ends: Vec<HealthNote>,
+    /// Most linked notes, by inbound + outbound links
+    pub hubs: Vec<HealthNote>,
+    /// Neither tagged nor assigned to a project
+    pub unfiled: Vec<HealthNote>,
+    /// Still in the inbox after `stale_days`
+    pub stale_inbox: Vec<HealthNote>,
+    /// Blank or whitespace-only content
+    pub empty: Vec<HealthNote>,
+    pub duplicate_titles: Vec<DuplicateTitle>,
+}
+
+/// Validation failure surfaced through `SqlResult`, like invalid properties JSON
+fn invalid_input(message: impl Into<String>) -> rusqlite::Error {
+    rusqlite::Error::ToSqlConversionFailure(Box::new(std::io::Error::new(
+        std::io::ErrorKind::InvalidInput,
+        message.into(),
+    )))
+}
+
+/// Unix timestamp of local midnight at the start of `date`
+fn local_day_start(date: chrono::NaiveDate) -> i64 {
+    use chrono::TimeZone;
+    let midnight = date.and_time(chrono::NaiveTime::MIN);
+    chrono::Local
+        .from_local_datetime(&midnight)
+        .earliest()
+        .map_or_else(|| midnight.and_utc().timestamp(), |t| t.timestamp())
+}
+
+fn goal_progress(
+    word_count: i64,
+    word_goal: Option<i64>,
+    deadline: Option<String>,
+    words_today: i64,
+    today: chrono::NaiveDate,
+) -> WritingGoalProgress {
+    let word_goal = word_goal.filter(|g| *g > 0);
+    let words_remaining = word_goal.map(|g| (g - word_count).max(0));
+    let days_left = deadline
+        .as_deref()
+        .and_then(|d| chrono::NaiveDate::parse_from_str(d, "%Y-%m-%d").ok())
+        .map(|d| (d - today).num_days() + 1);
+    let daily_target = match (words_remaining, days_left) {
+        (Some(remaining), Some(days)) if days > 0 => Some((remaining + days - 1) / days),
+        _ => None,
+    };
+
+    WritingGoalProgress {
+        word_count,
+        word_goal,
+        words_remaining,
+        percent: word_goal.map(|g| (word_count as f64 / g as f64 * 100.0).min(100.0)),
+        deadline,
+        days_left,
+        daily_target,
+        words_today,
+    }
+}
+
+/// Pinned tags first, then manual sort order, then name
+fn sort_tag_tree_nodes(nodes: &mut [TagTreeNode]) {
+    nodes.sort_by(|a, b| {
+        b.pinned
+            .cmp(&a.pinned)
+            .then(a.sort_order.cmp(&b.sort_order))
+            .then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase()))
+    });
+}
+
+impl Database {
+    pub fn new(app_handle: &AppHandle) -> SqlResult<Self> {
+        let app_data_dir = app_handle
+            .path()
+            .app_data_dir()
+            .expect("Failed to get app data directory");
+
+        std::fs::create_dir_all(&app_data_dir).expect("Failed to create app data directory");
+
+        let db_path = app_data_dir.join("scribe.db");
+        let conn = Connection::open(db_path)?;
+
+        // Enable WAL mode for better performance
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+
+        let mut db = Database { conn };
+        db.initialize()?;
+
+        Ok(db)
+    }
+
+    #[cfg(test)]
+    pub fn new_with_path<P: AsRef<std::path::Path>>(path: P) -> SqlResult<Self> {
+        let conn = Connection::open(path)?;
+        let mut db = Database { conn };
+        db.initialize()?;
+        Ok(db)
+    }
+    
+    fn initialize(&mut self) -> SqlResult<()> {
+        // Create schema_version table
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS schema_version (
+                version INTEGER PRIMARY KEY,
+                applied_at INTEGER DEFAULT (strftime('%s', 'now'))
+            )",
+            [],
+        )?;
+        
+        let current_version = self.get_current_schema_version()?;
+        
+        if current_version < 1 {
+            self.run_migration_001()?;
+            self.conn.execute("INSERT INTO schema_version (version) VALUES (?)", [1])?;
+        }
+        
+        if current_version < 2 {
+            self.run_migration_002()?;
+            self.conn.execute("INSERT INTO schema_version (version) VALUES (?)", [2])?;
+        }
+        
+        if current_version < 3 {
+            self.run_migration_003()?;
+            self.conn.execute("INSERT INTO schema_version (version) VALUES (?)", [3])?;
+        }
+
+        if current_version < 4 {
+            self.run_migration_004()?;
+            self.conn.execute("INSERT INTO schema_version (version) VALUES (?)", [4])?;
+        }
+
+        if current_version < 5 {
+            self.run_migration_005()?;
+            self.conn.execute("INSERT INTO schema_version (version) VALUES (?)", [5])?;
+        }
+
+        if current_version < 6 {
+            self.run_migration_006()?;
+            self.conn.execute("INSERT INTO schema_version (version) VALUES (?)", [6])?;
+        }
+
+        if current_version < 7 {
+            self.run_migration_007_seed_demo_data()?;
+            self.conn.execute("INSERT INTO schema_version (version) VALUES (?)", [7])?;
+        }
+
+        if current_version < 8 {
+            self.run_migration_008_add_properties_to_fts()?;
+            self.conn.execute("INSERT INTO schema_version (version) VALUES (?)", [8])?;
+        }
+
+        if current_version < 9 {
+            self.run_migration_009_chat_history()?;
+            self.conn.execute("INSERT INTO schema_version (version) VALUES (?)", [9])?;
+        }
+
+        if current_version < 10 {
+            self.run_migration_010_add_project_icons()?;
+            self.conn.execute("INSERT INTO schema_version (version) VALUES (?)", [10])?;
+        }
+
+        if current_version < 11 {
+            self.run_migration_011_unresolved_links()?;
+            self.conn.execute("INSERT INTO schema_version (version) VALUES (?)", [11])?;
+        }
+
+        if current_version < 12 {
+            self.run_migration_012_link_kinds()?;
+            self.conn.execute("INSERT INTO schema_version (version) VALUES (?)", [12])?;
+        }
+
+        if current_version < 13 {
+            self.run_migration_013_reextract_tags()?;
+            self.conn.execute("INSERT INTO schema_version (version) VALUES (?)", [13])?;
+        }
+
+        if current_version < 14 {
+            self.run_migration_014_tag_metadata()?;
+            self.conn.execute("INSERT INTO schema_version (version) VALUES (?)", [14])?;
+        }
+
+        if current_version < 15 {
+            self.run_migration_015_word_count()?;
+            self.conn.execute("INSERT INTO schema_version (version) VALUES (?)", [15])?;
+        }
+
+        if current_version < 16 {
+            self.run_migration_016_note_states()?;
+            self.conn.execute("INSERT INTO schema_version (version) VALUES (?)", [16])?;
+        }
+
+        if current_version < 17 {
+            self.run_migration_017_templates()?;
+            self.conn.execute("INSERT INTO schema_version (version) VALUES (?)", [17])?;
+        }
+
+        if current_version < 18 {
+            self.run_migration_018_periodic_notes()?;
+            self.conn.execute("INSERT INTO schema_version (version) VALUES (?)", [18])?;
+        }
+
+        if current_version < 19 {
+            self.run_migration_019_tasks()?;
+            self.conn.execute("INSERT INTO schema_version (version) VALUES (?)", [19])?;
+        }
+
+        if current_version < 20 {
+            self.run_migration_020_writing_log()?;
+            self.conn.execute("INSERT INTO schema_version (version) VALUES (?)", [20])?;
+        }
+
+        if current_version < 21 {
+            self.run_migration_021_focus_sessions()?;
+            self.conn.execute("INSERT INTO schema_version (version) VALUES (?)", [21])?;
+        }
+
+        if current_version < 22 {
+            self.run_migration_022_project_types()?;
+            self.conn.execute("INSERT INTO schema_version (version) VALUES (?)", [22])?;
+        }
+
+        if current_version < 23 {
+            self.run_migration_023_project_archive()?;
+            self.conn.execute("INSERT INTO schema_version (version) VALUES (?)", [23])?;
+        }
+
+        Ok(())
+    }
+    
+    #[cfg(not(test))]
+    fn get_current_schema_version(&self) -> SqlResult<i32> {
+        let version: Result<i32, _> = self.conn.query_row(
+            "SELECT MAX(version) FROM schema_version",
+            [],
+            |row| row.get(0),
+        );
+        Ok(version.unwrap_or(0))
+    }
+
+    #[cfg(test)]
+    pub(crate) fn get_current_schema_version(&self) -> SqlResult<i32> {
+        let version: Result<i32, _> = self.conn.query_row(
+            "SELECT MAX(version) FROM schema_version",
+            [],
+            |row| row.get(0),
+        );
+        Ok(version.unwrap_or(0))
+    }
+    
+    fn run_migration_001(&self) -> SqlResult<()> {
+        println!("✅ Database initialized (schema version 1)");
+        
+        self.conn.execute_batch(
+            "CREATE TABLE notes (
+                id TEXT PRIMARY KEY DEFAULT (lower(hex(randomblob(16)))),
+                title TEXT NOT NULL,
+                content TEXT DEFAULT '',
+                folder TEXT DEFAULT 'inbox',
+                created_at INTEGER DEFAULT (strftime('%s', 'now')),
                 updated_at INTEGER DEFAULT (strftime('%s', 'now')),
                 deleted_at INTEGER NULL
             );
 
-            CREATE VIRTUAL TABLE notes_fts USING fts5(
-                note_id UNINDEXED,
-                title,
-                content
-            );
+            CREATE VIRTUAL TABLE notes_fts USING fts5(
+                note_id UNINDEXED,
+                title,
+                content
+            );
+
+            CREATE TRIGGER notes_ai AFTER INSERT ON notes BEGIN
+                INSERT INTO notes_fts(note_id, title, content)
+                VALUES (new.id, new.title, new.content);
+            END;
+
+            CREATE TRIGGER notes_ad AFTER DELETE ON notes BEGIN
+                DELETE FROM notes_fts WHERE note_id = old.id;
+            END;
+
+            CREATE TRIGGER notes_au AFTER UPDATE ON notes BEGIN
+                DELETE FROM notes_fts WHERE note_id = old.id;
+                INSERT INTO notes_fts(note_id, title, content)
+                VALUES (new.id, new.title, new.content);
+            END;
+
+            CREATE INDEX idx_notes_folder ON notes(folder);
+            CREATE INDEX idx_notes_updated ON notes(updated_at DESC);
+            CREATE INDEX idx_notes_deleted ON notes(deleted_at);
+
+            CREATE TABLE tags (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                note_id TEXT NOT NULL,
+                tag TEXT NOT NULL,
+                FOREIGN KEY (note_id) REFERENCES notes(id) ON DELETE CASCADE,
+                UNIQUE(note_id, tag)
+            );
+
+            CREATE INDEX idx_tags_note ON tags(note_id);
+            CREATE INDEX idx_tags_tag ON tags(tag);
+
+            CREATE TABLE folders (
+                path TEXT PRIMARY KEY,
+                color TEXT,
+                icon TEXT,
+                sort_order INTEGER DEFAULT 0
+            );
+
+            INSERT INTO folders (path, sort_order) VALUES
+                ('inbox', 1),
+                ('projects', 2),
+                ('areas', 3),
+                ('resources', 4),
+                ('archive', 5);",
+        )?;
+        
+        Ok(())
+    }
+    
+    fn run_migration_002(&self) -> SqlResult<()> {
+        println!("✅ Database updated (schema version 2)");
+        
+        self.conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS links (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                source_note_id TEXT NOT NULL,
+                target_note_id TEXT NOT NULL,
+                created_at INTEGER DEFAULT (strftime('%s', 'now')),
+                FOREIGN KEY (source_note_id) REFERENCES notes(id) ON DELETE CASCADE,
+                FOREIGN KEY (target_note_id) REFERENCES notes(id) ON DELETE CASCADE,
+                UNIQUE(source_note_id, target_note_id)
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_links_source ON links(source_note_id);
+            CREATE INDEX IF NOT EXISTS idx_links_target ON links(target_note_id);",
+        )?;
+        
+        Ok(())
+    }
+    
+    fn run_migration_003(&self) -> SqlResult<()> {
+        println!("✅ Database updated (schema version 3)");
+        
+        self.conn.execute_batch(
+            "DROP TABLE IF EXISTS tags;
+
+            CREATE TABLE IF NOT EXISTS tags (
+                id TEXT PRIMARY KEY DEFAULT (lower(hex(randomblob(16)))),
+                name TEXT NOT NULL UNIQUE COLLATE NOCASE,
+                color TEXT,
+                created_at INTEGER DEFAULT (strftime('%s', 'now')),
+                UNIQUE(name COLLATE NOCASE)
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_tags_name ON tags(name COLLATE NOCASE);
+
+            CREATE TABLE IF NOT EXISTS note_tags (
+                note_id TEXT NOT NULL,
+                tag_id TEXT NOT NULL,
+                created_at INTEGER DEFAULT (strftime('%s', 'now')),
+                PRIMARY KEY (note_id, tag_id),
+                FOREIGN KEY (note_id) REFERENCES notes(id) ON DELETE CASCADE,
+                FOREIGN KEY (tag_id) REFERENCES tags(id) ON DELETE CASCADE
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_note_tags_note ON note_tags(note_id);
+            CREATE INDEX IF NOT EXISTS idx_note_tags_tag ON note_tags(tag_id);",
+        )?;
+
+        Ok(())
+    }
+
+    fn run_migration_004(&self) -> SqlResult<()> {
+        println!("Running database migration 004 (projects system)");
+
+        self.conn.execute_batch(
+            "-- Create projects table
+            CREATE TABLE IF NOT EXISTS projects (
+                id TEXT PRIMARY KEY DEFAULT (lower(hex(randomblob(16)))),
+                name TEXT NOT NULL,
+                description TEXT,
+                type TEXT CHECK(type IN ('research', 'teaching', 'r-package', 'r-dev', 'generic')) DEFAULT 'generic',
+                color TEXT,
+                icon TEXT,
+                settings TEXT,
+                created_at INTEGER DEFAULT (strftime('%s', 'now')),
+                updated_at INTEGER DEFAULT (strftime('%s', 'now'))
+            );
+
+            -- Add project_id column to notes table
+            ALTER TABLE notes ADD COLUMN project_id TEXT REFERENCES projects(id) ON DELETE SET NULL;
+
+            -- Create index for efficient project-based note lookups
+            CREATE INDEX IF NOT EXISTS idx_notes_project ON notes(project_id);
+
+            -- Create index for project name lookups
+            CREATE INDEX IF NOT EXISTS idx_projects_name ON projects(name);
+            CREATE INDEX IF NOT EXISTS idx_projects_type ON projects(type);",
+        )?;
+
+        println!("Database migration 004 completed (projects system)");
+        Ok(())
+    }
+
+    fn run_migration_005(&self) -> SqlResult<()> {
+        println!("Running database migration 005 (note properties)");
+
+        // Add properties column to notes table
+        self.conn.execute(
+            "ALTER TABLE notes ADD COLUMN properties TEXT",
+            [],
+        )?;
+
+        println!("Database migration 005 completed (note properties)");
+        Ok(())
+    }
+
+    fn run_migration_006(&self) -> SqlResult<()> {
+        println!("Running database migration 006 (project settings table)");
+
+        self.conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS project_settings (
+                project_id TEXT PRIMARY KEY REFERENCES projects(id) ON DELETE CASCADE,
+                settings TEXT NOT NULL DEFAULT '{}'
+            );",
+        )?;
+
+        println!("Database migration 006 completed (project settings table)");
+        Ok(())
+    }
+
+    /// Migration 007: Seed demo data for new users
+    ///
+    /// Creates a "Getting Started" project with example notes to help
+    /// users understand how Scribe works. Only runs if no projects exist.
+    fn run_migration_007_seed_demo_data(&self) -> SqlResult<()> {
+        println!("Running database migration 007 (demo data seeding)");
+
+        // Check if any projects already exist (don't seed if user has data)
+        let project_count: i64 = self.conn.query_row(
+            "SELECT COUNT(*) FROM projects",
+            [],
+            |row| row.get(0),
+        )?;
+
+        if project_count > 0 {
+            println!("  Skipping demo data - projects already exist");
+            return Ok(());
+        }
+
+        println!("  Seeding demo data for new user...");
+
+        // Generate UUIDs for our entities
+        let project_id: String = self.conn.query_row(
+            "SELECT lower(hex(randomblob(16)))",
+            [],
+            |row| row.get(0),
+        )?;
+
+        let note1_id: String = self.conn.query_row(
+            "SELECT lower(hex(randomblob(16)))",
+            [],
+            |row| row.get(0),
+        )?;
+
+        let note2_id: String = self.conn.query_row(
+            "SELECT lower(hex(randomblob(16)))",
+            [],
+            |row| row.get(0),
+        )?;
+
+        let note3_id: String = self.conn.query_row(
+            "SELECT lower(hex(randomblob(16)))",
+            [],
+            |row| row.get(0),
+        )?;
+
+        // Create demo project
+        self.conn.execute(
+            "INSERT INTO projects (id, name, description, type, color, icon) VALUES (?, ?, ?, ?, ?, ?)",
+            rusqlite::params![
+                &project_id,
+                "Getting Started",
+                "Learn how to use Scribe with these example notes",
+                "generic",
+                "#3B82F6",
+                "BookOpen"
+            ],
+        )?;
+
+        // Create demo notes
+        let welcome_content = r#"# Welcome to Scribe! 👋
+
+Scribe is an **ADHD-friendly distraction-free writer** designed to help you focus.
+
+## Quick Tips
+
+- Press **⌘N** to create a new note
+- Press **⌘D** to open today's daily note
+- Press **⌘K** to open the command palette
+- Press **Escape** to close panels
+
+## Tauri Desktop App
+
+You're running Scribe as a **native desktop app** with SQLite storage.
+Your notes are saved locally and sync instantly!
+
+## Getting Started
+
+1. Create a new note with ⌘N
+2. Start writing without distractions
+3. Use #tags to organize your notes
+4. Link notes with [[wiki links]]
+
+See the [[Features Overview]] note for more details."#;
+
+        let features_content = r#"# Features Overview
+
+Scribe includes these core features to help you write:
+
+## ✍️ Writing
+- Clean, minimal editor
+- Auto-save (never lose work)
+- Dark mode for less eye strain
+- Word count & reading time
+
+## 🏷️ Organization
+- **Tags**: Add #tags anywhere in your notes
+- **Folders**: inbox, notes, archive
+- **Projects**: Group related notes together
+
+## 🔗 Knowledge
+- **Wiki Links**: Connect notes with [[Note Title]]
+- **Backlinks**: See what links to the current note
+- **Daily Notes**: Quick journal entries
+
+## 📚 Academic Features
+- Citation management with Zotero
+- Export to PDF, Word, LaTeX
+- Equation support with KaTeX
+
+## 🤖 AI Integration
+- Claude CLI integration
+- Gemini CLI integration
+- No API keys needed - uses your installed CLI tools"#;
+
+        let daily_content = format!(r#"# Daily Note Example
+
+## Focus for Today
+- [ ] Review the [[Welcome to Scribe]] tutorial
+- [ ] Create your first note
+- [ ] Try adding some #tags
+
+## Notes
+Use this space for quick thoughts and ideas.
+
+Press ⌘D anytime to open today's daily note.
+
+## End of Day Review
+What did you accomplish today?"#);
+
+        // Insert notes
+        self.conn.execute(
+            "INSERT INTO notes (id, title, content, folder, project_id) VALUES (?, ?, ?, ?, ?)",
+            rusqlite::params![&note1_id, "Welcome to Scribe", welcome_content, "inbox", &project_id],
+        )?;
+
+        self.conn.execute(
+            "INSERT INTO notes (id, title, content, folder, project_id) VALUES (?, ?, ?, ?, ?)",
+            rusqlite::params![&note2_id, "Features Overview", features_content, "notes", &project_id],
+        )?;
+
+        self.conn.execute(
+            "INSERT INTO notes (id, title, content, folder, project_id) VALUES (?, ?, ?, ?, ?)",
+            rusqlite::params![&note3_id, "Daily Note Example", &daily_content, "notes", rusqlite::types::Null],
+        )?;
+
+        // Update FTS index (properties column will be added in migration 008, so don't use it here)
+        self.conn.execute(
+            "INSERT INTO notes_fts (note_id, title, content) VALUES (?, ?, ?)",
+            rusqlite::params![&note1_id, "Welcome to Scribe", welcome_content],
+        )?;
+        self.conn.execute(
+            "INSERT INTO notes_fts (note_id, title, content) VALUES (?, ?, ?)",
+            rusqlite::params![&note2_id, "Features Overview", features_content],
+        )?;
+        self.conn.execute(
+            "INSERT INTO notes_fts (note_id, title, content) VALUES (?, ?, ?)",
+            rusqlite::params![&note3_id, "Daily Note Example", &daily_content],
+        )?;
+
+        // Create demo tags
+        let tag_welcome_id: String = self.conn.query_row(
+            "SELECT lower(hex(randomblob(16)))",
+            [],
+            |row| row.get(0),
+        )?;
+        let tag_tutorial_id: String = self.conn.query_row(
+            "SELECT lower(hex(randomblob(16)))",
+            [],
+            |row| row.get(0),
+        )?;
+        let tag_tips_id: String = self.conn.query_row(
+            "SELECT lower(hex(randomblob(16)))",
+            [],
+            |row| row.get(0),
+        )?;
+
+        self.conn.execute(
+            "INSERT INTO tags (id, name, color) VALUES (?, ?, ?)",
+            rusqlite::params![&tag_welcome_id, "welcome", "#10B981"],
+        )?;
+        self.conn.execute(
+            "INSERT INTO tags (id, name, color) VALUES (?, ?, ?)",
+            rusqlite::params![&tag_tutorial_id, "tutorial", "#8B5CF6"],
+        )?;
+        self.conn.execute(
+            "INSERT INTO tags (id, name, color) VALUES (?, ?, ?)",
+            rusqlite::params![&tag_tips_id, "tips", "#F59E0B"],
+        )?;
+
+        // Associate tags with notes
+        self.conn.execute(
+            "INSERT INTO note_tags (note_id, tag_id) VALUES (?, ?)",
+            rusqlite::params![&note1_id, &tag_welcome_id],
+        )?;
+        self.conn.execute(
+            "INSERT INTO note_tags (note_id, tag_id) VALUES (?, ?)",
+            rusqlite::params![&note1_id, &tag_tutorial_id],
+        )?;
+        self.conn.execute(
+            "INSERT INTO note_tags (note_id, tag_id) VALUES (?, ?)",
+            rusqlite::params![&note2_id, &tag_tutorial_id],
+        )?;
+        self.conn.execute(
+            "INSERT INTO note_tags (note_id, tag_id) VALUES (?, ?)",
+            rusqlite::params![&note2_id, &tag_tips_id],
+        )?;
+
+        // Create wiki link from Welcome to Features Overview
+        self.conn.execute(
+            "INSERT INTO links (source_note_id, target_note_id) VALUES (?, ?)",
+            rusqlite::params![&note1_id, &note2_id],
+        )?;
+
+        println!("  ✅ Demo data seeded successfully:");
+        println!("     - 1 project: 'Getting Started'");
+        println!("     - 3 notes: Welcome, Features, Daily Example");
+        println!("     - 3 tags: #welcome, #tutorial, #tips");
+
+        Ok(())
+    }
+
+    #[cfg(not(test))]
+    fn run_migration_008_add_properties_to_fts(&self) -> SqlResult<()> {
+        println!("Running database migration 008 (add properties to FTS index)");
+
+        // Drop old FTS table and triggers
+        self.conn.execute_batch("
+            DROP TRIGGER IF EXISTS notes_au;
+            DROP TRIGGER IF EXISTS notes_ad;
+            DROP TRIGGER IF EXISTS notes_ai;
+            DROP TABLE IF EXISTS notes_fts;
+        ")?;
+
+        // Recreate FTS table with properties column
+        self.conn.execute_batch("
+            CREATE VIRTUAL TABLE notes_fts USING fts5(
+                note_id UNINDEXED,
+                title,
+                content,
+                properties
+            );
+
+            CREATE TRIGGER notes_ai AFTER INSERT ON notes BEGIN
+                INSERT INTO notes_fts(note_id, title, content, properties)
+                VALUES (new.id, new.title, new.content, COALESCE(new.properties, ''));
+            END;
+
+            CREATE TRIGGER notes_ad AFTER DELETE ON notes BEGIN
+                DELETE FROM notes_fts WHERE note_id = old.id;
+            END;
+
+            CREATE TRIGGER notes_au AFTER UPDATE ON notes BEGIN
+                DELETE FROM notes_fts WHERE note_id = old.id;
+                INSERT INTO notes_fts(note_id, title, content, properties)
+                VALUES (new.id, new.title, new.content, COALESCE(new.properties, ''));
+            END;
+        ")?;
+
+        // Populate FTS table with existing notes
+        self.conn.execute("
+            INSERT INTO notes_fts(note_id, title, content, properties)
+            SELECT id, title, content, COALESCE(properties, '')
+            FROM notes
+            WHERE deleted_at IS NULL
+        ", [])?;
+
+        println!("  ✅ FTS index updated with properties column");
+        Ok(())
+    }
+
+    #[cfg(test)]
+    pub(crate) fn run_migration_008_add_properties_to_fts(&self) -> SqlResult<()> {
+        println!("Running database migration 008 (add properties to FTS index)");
+
+        // Drop old FTS table and triggers
+        self.conn.execute_batch("
+            DROP TRIGGER IF EXISTS notes_au;
+            DROP TRIGGER IF EXISTS notes_ad;
+            DROP TRIGGER IF EXISTS notes_ai;
+            DROP TABLE IF EXISTS notes_fts;
+        ")?;
+
+        // Recreate FTS table with properties column
+        self.conn.execute_batch("
+            CREATE VIRTUAL TABLE notes_fts USING fts5(
+                note_id UNINDEXED,
+                title,
+                content,
+                properties
+            );
+
+            CREATE TRIGGER notes_ai AFTER INSERT ON notes BEGIN
+                INSERT INTO notes_fts(note_id, title, content, properties)
+                VALUES (new.id, new.title, new.content, COALESCE(new.properties, ''));
+            END;
+
+            CREATE TRIGGER notes_ad AFTER DELETE ON notes BEGIN
+                DELETE FROM notes_fts WHERE note_id = old.id;
+            END;
+
+            CREATE TRIGGER notes_au AFTER UPDATE ON notes BEGIN
+                DELETE FROM notes_fts WHERE note_id = old.id;
+                INSERT INTO notes_fts(note_id, title, content, properties)
+                VALUES (new.id, new.title, new.content, COALESCE(new.properties, ''));
+            END;
+        ")?;
+
+        // Populate FTS table with existing notes
+        self.conn.execute("
+            INSERT INTO notes_fts(note_id, title, content, properties)
+            SELECT id, title, content, COALESCE(properties, '')
+            FROM notes
+            WHERE deleted_at IS NULL
+        ", [])?;
+
+        println!("  ✅ FTS index updated with properties column");
+        Ok(())
+    }
+
+    #[cfg(not(test))]
+    fn run_migration_009_chat_history(&self) -> SqlResult<()> {
+        println!("Running database migration 009 (chat history tables)");
+
+        // Create chat_sessions table
+        self.conn.execute_batch("
+            CREATE TABLE IF NOT EXISTS chat_sessions (
+                id TEXT PRIMARY KEY,
+                note_id TEXT NOT NULL,
+                created_at INTEGER NOT NULL,
+                updated_at INTEGER NOT NULL,
+                FOREIGN KEY (note_id) REFERENCES notes(id) ON DELETE CASCADE
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_chat_sessions_note_id ON chat_sessions(note_id);
+            CREATE INDEX IF NOT EXISTS idx_chat_sessions_updated_at ON chat_sessions(updated_at DESC);
+        ")?;
+
+        // Create chat_messages table
+        self.conn.execute_batch("
+            CREATE TABLE IF NOT EXISTS chat_messages (
+                id TEXT PRIMARY KEY,
+                session_id TEXT NOT NULL,
+                role TEXT NOT NULL CHECK (role IN ('user', 'assistant')),
+                content TEXT NOT NULL,
+                timestamp INTEGER NOT NULL,
+                FOREIGN KEY (session_id) REFERENCES chat_sessions(id) ON DELETE CASCADE
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_chat_messages_session_id ON chat_messages(session_id);
+            CREATE INDEX IF NOT EXISTS idx_chat_messages_timestamp ON chat_messages(timestamp ASC);
+        ")?;
+
+        println!("  ✅ Chat history tables created");
+        Ok(())
+    }
+
+    #[cfg(test)]
+    pub(crate) fn run_migration_009_chat_history(&self) -> SqlResult<()> {
+        println!("Running database migration 009 (chat history tables)");
+
+        // Create chat_sessions table
+        self.conn.execute_batch("
+            CREATE TABLE IF NOT EXISTS chat_sessions (
+                id TEXT PRIMARY KEY,
+                note_id TEXT NOT NULL,
+                created_at INTEGER NOT NULL,
+                updated_at INTEGER NOT NULL,
+                FOREIGN KEY (note_id) REFERENCES notes(id) ON DELETE CASCADE
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_chat_sessions_note_id ON chat_sessions(note_id);
+            CREATE INDEX IF NOT EXISTS idx_chat_sessions_updated_at ON chat_sessions(updated_at DESC);
+        ")?;
+
+        // Create chat_messages table
+        self.conn.execute_batch("
+            CREATE TABLE IF NOT EXISTS chat_messages (
+                id TEXT PRIMARY KEY,
+                session_id TEXT NOT NULL,
+                role TEXT NOT NULL CHECK (role IN ('user', 'assistant')),
+                content TEXT NOT NULL,
+                timestamp INTEGER NOT NULL,
+                FOREIGN KEY (session_id) REFERENCES chat_sessions(id) ON DELETE CASCADE
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_chat_messages_session_id ON chat_messages(session_id);
+            CREATE INDEX IF NOT EXISTS idx_chat_messages_timestamp ON chat_messages(timestamp ASC);
+        ")?;
+
+        println!("  ✅ Chat history tables created");
+        Ok(())
+    }
+
+    fn run_migration_010_add_project_icons(&self) -> SqlResult<()> {
+        println!("Running database migration 010 (add project icons)");
+
+        // Check if icon column already exists (it's added in migration 004 for new databases)
+        let has_icon_column: bool = self.conn.query_row(
+            "SELECT COUNT(*) FROM pragma_table_info('projects') WHERE name = 'icon'",
+            [],
+            |row| row.get::<_, i32>(0).map(|count| count > 0),
+        )?;
+
+        if !has_icon_column {
+            // Add icon column to projects table (for databases created before migration 004 update)
+            self.conn.execute_batch("
+                ALTER TABLE projects ADD COLUMN icon TEXT;
+            ")?;
+            println!("  ✅ Icon column added to projects table");
+        } else {
+            println!("  ℹ️  Icon column already exists (skipped)");
+        }
+
+        Ok(())
+    }
+
+    /// Migration 011: Track unresolved wiki links
+    ///
+    /// Rebuilds the links table so `target_note_id` is nullable and the raw
+    /// `[[Title]]` text is kept in `target_title`. Links to notes that don't
+    /// exist yet are stored instead of dropped, and get resolved once a note
+    /// with that title appears.
+    fn run_migration_011_unresolved_links(&self) -> SqlResult<()> {
+        println!("Running database migration 011 (unresolved links)");
+
+        self.conn.execute_batch("
+            CREATE TABLE links_new (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                source_note_id TEXT NOT NULL,
+                target_note_id TEXT,
+                target_title TEXT NOT NULL,
+                created_at INTEGER DEFAULT (strftime('%s', 'now')),
+                FOREIGN KEY (source_note_id) REFERENCES notes(id) ON DELETE CASCADE,
+                FOREIGN KEY (target_note_id) REFERENCES notes(id) ON DELETE SET NULL
+            );
+
+            INSERT OR IGNORE INTO links_new (id, source_note_id, target_note_id, target_title, created_at)
+            SELECT links.id, links.source_note_id, links.target_note_id, notes.title, links.created_at
+            FROM links
+            JOIN notes ON notes.id = links.target_note_id;
+
+            DROP TABLE links;
+            ALTER TABLE links_new RENAME TO links;
+
+            CREATE INDEX IF NOT EXISTS idx_links_source ON links(source_note_id);
+            CREATE INDEX IF NOT EXISTS idx_links_target ON links(target_note_id);
+            CREATE UNIQUE INDEX IF NOT EXISTS idx_links_source_title ON links(source_note_id, target_title);
+            CREATE INDEX IF NOT EXISTS idx_links_unresolved ON links(target_title) WHERE target_note_id IS NULL;
+        ")?;
+
+        println!("  ✅ Links table now tracks unresolved targets");
+        Ok(())
+    }
+
+    /// Migration 012: Link kinds, heading/block anchors and aliases
+    ///
+    /// Until now `[[Note|alias]]` or `[[Note#Heading]]` were stored as literal
+    /// titles that never resolved. Existing rows are split into their parts
+    /// and re-resolved against the bare title.
+    fn run_migration_012_link_kinds(&self) -> SqlResult<()> {
+        println!("Running database migration 012 (link kinds, anchors and aliases)");
+
+        self.conn.execute_batch("
+            ALTER TABLE links ADD COLUMN kind TEXT NOT NULL DEFAULT 'link';
+            ALTER TABLE links ADD COLUMN anchor TEXT;
+            ALTER TABLE links ADD COLUMN alias TEXT;
+
+            DROP INDEX IF EXISTS idx_links_source_title;
+            CREATE UNIQUE INDEX IF NOT EXISTS idx_links_unique ON links(
+                source_note_id, target_title, kind, COALESCE(anchor, ''), COALESCE(alias, '')
+            );
+        ")?;
+
+        let rows: Vec<(i64, String)> = self.conn
+            .prepare("SELECT id, target_title FROM links WHERE target_title GLOB '*[|#^]*'")?
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        for (link_id, raw_title) in &rows {
+            let (title, anchor, alias) = markdown::parse_link_text(raw_title);
+            self.conn.execute(
+                "UPDATE OR REPLACE links
+                 SET target_title = ?1, anchor = ?2, alias = ?3,
+                     target_note_id = (SELECT id FROM notes WHERE title = ?1 AND deleted_at IS NULL LIMIT 1)
+                 WHERE id = ?4",
+                rusqlite::params![title, anchor, alias, link_id],
+            )?;
+        }
+
+        println!("  ✅ Links now record kind, anchor and alias ({} rows re-parsed)", rows.len());
+        Ok(())
+    }
+
+    fn run_migration_013_reextract_tags(&self) -> SqlResult<()> {
+        println!("Running database migration 013 (Markdown-aware tag and link extraction)");
+
+        let tagged_before: Vec<String> = self.conn
+            .prepare("SELECT DISTINCT tag_id FROM note_tags")?
+            .query_map([], |row| row.get(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let notes: Vec<(String, String)> = self.conn
+            .prepare("SELECT id, content FROM notes")?
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        for (note_id, content) in &notes {
+            self.update_note_tags(note_id, content)?;
+            self.update_note_links(note_id, content)?;
+        }
+
+        // Tags that only existed because of the old regex (e.g. `#include`, `#ff0000`)
+        let mut removed = 0;
+        for tag_id in &tagged_before {
+            removed += self.conn.execute(
+                "DELETE FROM tags WHERE id = ?1 AND NOT EXISTS (SELECT 1 FROM note_tags WHERE tag_id = ?1)",
+                [tag_id],
+            )?;
+        }
+
+        println!("  ✅ Re-extracted tags and links for {} notes ({} stray tags removed)", notes.len(), removed);
+        Ok(())
+    }
+
+    fn run_migration_014_tag_metadata(&self) -> SqlResult<()> {
+        println!("Running database migration 014 (tag metadata and palette colours)");
+
+        self.conn.execute_batch("
+            ALTER TABLE tags ADD COLUMN description TEXT;
+            ALTER TABLE tags ADD COLUMN icon TEXT;
+            ALTER TABLE tags ADD COLUMN pinned INTEGER NOT NULL DEFAULT 0;
+            ALTER TABLE tags ADD COLUMN sort_order INTEGER NOT NULL DEFAULT 0;
+            ALTER TABLE tags ADD COLUMN parent_id TEXT REFERENCES tags(id) ON DELETE SET NULL;
+        ")?;
+
+        // Move auto-assigned `hsl(h, 70%, 50%)` colours onto the palette; picked colours stay
+        let tags: Vec<(String, String, Option<String>)> = self.conn
+            .prepare("SELECT id, name, color FROM tags")?
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut recoloured = 0;
+        for (id, name, color) in &tags {
+            let generated = format!("hsl({}, 70%, 50%)", Self::tag_color_hash(name) % 360);
+            if color.as_deref() == Some(generated.as_str()) {
+                self.conn.execute(
+                    "UPDATE tags SET color = ? WHERE id = ?",
+                    [self.generate_tag_color(name), id.clone()],
+                )?;
+                recoloured += 1;
+            }
+        }
+
+        println!("  ✅ Tags now have description, icon, pinned, sort order and parent ({} colours moved to the palette)", recoloured);
+        Ok(())
+    }
+
+    fn run_migration_015_word_count(&self) -> SqlResult<()> {
+        println!("Running database migration 015 (note word counts)");
+
+        self.conn.execute_batch("
+            ALTER TABLE notes ADD COLUMN word_count INTEGER NOT NULL DEFAULT 0;
+            CREATE INDEX IF NOT EXISTS idx_notes_word_count ON notes(word_count);
+            CREATE INDEX IF NOT EXISTS idx_notes_created ON notes(created_at);
+        ")?;
+
+        let notes: Vec<(String, String)> = self.conn
+            .prepare("SELECT id, content FROM notes")?
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        for (id, content) in &notes {
+            self.conn.execute(
+                "UPDATE notes SET word_count = ? WHERE id = ?",
+                rusqlite::params![markdown::word_count(content) as i64, id],
+            )?;
+        }
+
+        println!("  ✅ Counted words for {} notes", notes.len());
+        Ok(())
+    }
+
+    fn run_migration_016_note_states(&self) -> SqlResult<()> {
+        println!("Running database migration 016 (pinned, favourite and archived notes)");
+
+        self.conn.execute_batch("
+            ALTER TABLE notes ADD COLUMN pinned INTEGER NOT NULL DEFAULT 0;
+            ALTER TABLE notes ADD COLUMN favorite INTEGER NOT NULL DEFAULT 0;
+            ALTER TABLE notes ADD COLUMN archived_at INTEGER;
+            CREATE INDEX IF NOT EXISTS idx_notes_archived ON notes(archived_at);
+        ")?;
+
+        println!("  ✅ Notes can now be pinned, favourited and archived");
+        Ok(())
+    }
+
+    fn run_migration_017_templates(&self) -> SqlResult<()> {
+        println!("Running database migration 017 (note templates and app settings)");
+
+        self.conn.execute_batch("
+            CREATE TABLE IF NOT EXISTS templates (
+                id TEXT PRIMARY KEY DEFAULT (lower(hex(randomblob(16)))),
+                name TEXT NOT NULL UNIQUE COLLATE NOCASE,
+                description TEXT,
+                content TEXT NOT NULL DEFAULT '',
+                properties TEXT,
+                tags TEXT NOT NULL DEFAULT '[]',
+                created_at INTEGER DEFAULT (strftime('%s', 'now')),
+                updated_at INTEGER DEFAULT (strftime('%s', 'now'))
+            );
+
+            CREATE TABLE IF NOT EXISTS app_settings (
+                key TEXT PRIMARY KEY,
+                value TEXT NOT NULL
+            );
+        ")?;
+
+        println!("  ✅ Templates and app settings tables created");
+        Ok(())
+    }
+
+    fn run_migration_018_periodic_notes(&self) -> SqlResult<()> {
+        println!("Running database migration 018 (periodic notes)");
+
+        self.conn.execute_batch("
+            CREATE TABLE IF NOT EXISTS periodic_notes (
+                period TEXT NOT NULL,
+                start_date TEXT NOT NULL,
+                note_id TEXT NOT NULL,
+                PRIMARY KEY (period, start_date),
+                FOREIGN KEY (note_id) REFERENCES notes(id) ON DELETE CASCADE
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_periodic_notes_note ON periodic_notes(note_id);
+        ")?;
+
+        // Existing daily notes were found by their `YYYY-MM-DD` title in `daily`
+        let daily: Vec<(String, String)> = self.conn
+            .prepare("SELECT id, title FROM notes WHERE folder = 'daily' AND deleted_at IS NULL ORDER BY created_at")?
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut linked = 0;
+        for (id, title) in &daily {
+            if let Ok(date) = chrono::NaiveDate::parse_from_str(title.trim(), "%Y-%m-%d") {
+                linked += self.conn.execute(
+                    "INSERT OR IGNORE INTO periodic_notes (period, start_date, note_id) VALUES ('daily', ?, ?)",
+                    [date.to_string(), id.clone()],
+                )?;
+            }
+        }
+
+        println!("  ✅ Periodic notes table created ({} daily notes linked)", linked);
+        Ok(())
+    }
+
+    fn run_migration_019_tasks(&self) -> SqlResult<()> {
+        println!("Running database migration 019 (task index)");
+
+        self.conn.execute_batch("
+            CREATE TABLE IF NOT EXISTS tasks (
+                note_id TEXT NOT NULL,
+                line INTEGER NOT NULL,
+                text TEXT NOT NULL,
+                status TEXT NOT NULL DEFAULT ' ',
+                checked INTEGER NOT NULL DEFAULT 0,
+                due_date TEXT,
+                priority TEXT,
+                tags TEXT NOT NULL DEFAULT '[]',
+                PRIMARY KEY (note_id, line),
+                FOREIGN KEY (note_id) REFERENCES notes(id) ON DELETE CASCADE
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_tasks_status_due ON tasks(status, due_date);
+        ")?;
+
+        let notes: Vec<(String, String)> = self.conn
+            .prepare("SELECT id, content FROM notes")?
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        for (id, content) in &notes {
+            self.update_note_tasks(id, content)?;
+        }
+
+        let indexed: i64 = self.conn.query_row("SELECT COUNT(*) FROM tasks", [], |row| row.get(0))?;
+        println!("  ✅ Tasks table created ({} tasks indexed)", indexed);
+        Ok(())
+    }
+
+    fn run_migration_020_writing_log(&self) -> SqlResult<()> {
+        println!("Running database migration 020 (writing log)");
+
+        self.conn.execute_batch("
+            CREATE TABLE IF NOT EXISTS writing_log (
+                date TEXT NOT NULL,
+                note_id TEXT NOT NULL,
+                words_added INTEGER NOT NULL DEFAULT 0,
+                words_removed INTEGER NOT NULL DEFAULT 0,
+                PRIMARY KEY (date, note_id)
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_writing_log_note ON writing_log(note_id);
+        ")?;
+
+        println!("  ✅ Writing log table created");
+        Ok(())
+    }
+
+    fn run_migration_021_focus_sessions(&self) -> SqlResult<()> {
+        println!("Running database migration 021 (focus sessions)");
+
+        self.conn.execute_batch("
+            CREATE TABLE IF NOT EXISTS focus_sessions (
+                id TEXT PRIMARY KEY DEFAULT (lower(hex(randomblob(16)))),
+                note_id TEXT REFERENCES notes(id) ON DELETE SET NULL,
+                project_id TEXT REFERENCES projects(id) ON DELETE SET NULL,
+                planned_minutes INTEGER,
+                status TEXT NOT NULL DEFAULT 'running',
+                started_at INTEGER NOT NULL DEFAULT (strftime('%s', 'now')),
+                ended_at INTEGER,
+                paused_at INTEGER,
+                paused_seconds INTEGER NOT NULL DEFAULT 0,
+                duration_seconds INTEGER NOT NULL DEFAULT 0,
+                words_at_start INTEGER NOT NULL DEFAULT 0,
+                words_written INTEGER NOT NULL DEFAULT 0,
+                interruptions INTEGER NOT NULL DEFAULT 0,
+                reflection TEXT
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_focus_sessions_started ON focus_sessions(started_at);
+            CREATE INDEX IF NOT EXISTS idx_focus_sessions_status ON focus_sessions(status);
+        ")?;
+
+        println!("  ✅ Focus sessions table created");
+        Ok(())
+    }
+
+    fn run_migration_022_project_types(&self) -> SqlResult<()> {
+        println!("Running database migration 022 (project types)");
+
+        self.conn.execute_batch("
+            CREATE TABLE IF NOT EXISTS project_types (
+                id TEXT PRIMARY KEY,
+                name TEXT NOT NULL,
+                description TEXT,
+                icon TEXT,
+                color TEXT,
+                folders TEXT NOT NULL DEFAULT '[]',
+                starter_notes TEXT NOT NULL DEFAULT '[]',
+                properties TEXT NOT NULL DEFAULT '[]',
+                export_preset TEXT,
+                bibliography TEXT NOT NULL DEFAULT 'none',
+                builtin INTEGER NOT NULL DEFAULT 0,
+                sort_order INTEGER NOT NULL DEFAULT 0
+            );
+        ")?;
+
+        for project_type in project_types::builtin() {
+            self.save_project_type(&project_type)?;
+        }
+
+        // SQLite can't drop a CHECK constraint, so rebuild projects without the fixed type list.
+        // With foreign keys on, dropping the old table would null every note's project_id
+        // and delete all project settings, so turn them off for the rebuild. The pragma
+        // is a no-op inside a transaction, which is why this runs outside one.
+        let foreign_keys: bool = self.conn.query_row("PRAGMA foreign_keys", [], |row| row.get(0))?;
+        self.conn.execute_batch("PRAGMA foreign_keys = OFF;")?;
+        let rebuilt = self.conn.execute_batch("
+            CREATE TABLE projects_new (
+                id TEXT PRIMARY KEY DEFAULT (lower(hex(randomblob(16)))),
+                name TEXT NOT NULL,
+                description TEXT,
+                type TEXT NOT NULL DEFAULT 'generic',
+                color TEXT,
+                icon TEXT,
+                settings TEXT,
+                created_at INTEGER DEFAULT (strftime('%s', 'now')),
+                updated_at INTEGER DEFAULT (strftime('%s', 'now'))
+            );
+
+            INSERT INTO projects_new (id, name, description, type, color, icon, settings, created_at, updated_at)
+            SELECT id, name, description, COALESCE(type, 'generic'), color, icon, settings, created_at, updated_at
+            FROM projects;
+
+            DROP TABLE projects;
+            ALTER TABLE projects_new RENAME TO projects;
+
+            CREATE INDEX IF NOT EXISTS idx_projects_name ON projects(name);
+            CREATE INDEX IF NOT EXISTS idx_projects_type ON projects(type);
+        ");
+        if foreign_keys {
+            self.conn.execute_batch("PRAGMA foreign_keys = ON;")?;
+        }
+        rebuilt?;
+
+        let dangling: i64 = self.conn.query_row(
+            "SELECT COUNT(*) FROM pragma_foreign_key_check WHERE parent = 'projects'",
+            [],
+            |row| row.get(0),
+        )?;
+        if dangling > 0 {
+            println!("  ⚠️  {} rows refer to projects that don't exist", dangling);
+        }
+
+        println!("  ✅ Project types table created; projects.type is no longer a fixed list");
+        Ok(())
+    }
+
+    fn run_migration_023_project_archive(&self) -> SqlResult<()> {
+        println!("Running database migration 023 (project archiving)");
+
+        self.conn.execute_batch("
+            ALTER TABLE projects ADD COLUMN archived_at INTEGER;
+            CREATE INDEX IF NOT EXISTS idx_projects_archived_at ON projects(archived_at);
+        ")?;
+
+        println!("  ✅ Added archived_at to projects");
+        Ok(())
+    }
+
+    // Note CRUD operations
+
+    /// Validate properties JSON structure and type constraints
+    fn validate_properties(properties_json: &str) -> Result<(), String> {
+        // Parse JSON
+        let properties: HashMap<String, Property> = serde_json::from_str(properties_json)
+            .map_err(|e| format!("Invalid properties JSON: {}", e))?;
+
+        // Validate each property's value matches its type
+        for (key, prop) in properties.iter() {
+            match (&prop.prop_type, &prop.value) {
+                (PropertyType::Number, v) if !v.is_number() => {
+                    return Err(format!("Property '{}' should be a number, got: {:?}", key, v));
+                }
+                (PropertyType::Checkbox, v) if !v.is_boolean() => {
+                    return Err(format!("Property '{}' should be a boolean, got: {:?}", key, v));
+                }
+                (PropertyType::List | PropertyType::Tags, v) if !v.is_array() => {
+                    return Err(format!("Property '{}' should be an array, got: {:?}", key, v));
+                }
+                (PropertyType::Text | PropertyType::Date | PropertyType::Link, v) if !v.is_string() => {
+                    return Err(format!("Property '{}' should be a string, got: {:?}", key, v));
+                }
+                _ => {} // Type matches or is acceptable
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn create_note(&self, title: &str, content: &str, folder: &str, project_id: Option<&str>, properties: Option<&str>) -> SqlResult<Note> {
+        self.create_note_as(title, content, folder, project_id, properties, NoteEdit::User)
+    }
+
+    fn create_note_as(
+        &self,
+        title: &str,
+        content: &str,
+        folder: &str,
+        project_id: Option<&str>,
+        properties: Option<&str>,
+        edit: NoteEdit,
+    ) -> SqlResult<Note> {
+        // Validate properties if provided
+        if let Some(props) = properties {
+            if !props.is_empty() {
+                if let Err(e) = Self::validate_properties(props) {
+                    return Err(rusqlite::Error::ToSqlConversionFailure(
+                        Box::new(std::io::Error::new(std::io::ErrorKind::InvalidInput, e))
+                    ));
+                }
+            }
+        }
+
+        self.conn.execute(
+            "INSERT INTO notes (title, content, folder, project_id, properties, word_count) VALUES (?, ?, ?, ?, ?, ?)",
+            rusqlite::params![title, content, folder, project_id, properties, markdown::word_count(content) as i64],
+        )?;
+
+        let note = self.conn.query_row(
+            &format!(
+                "SELECT {} FROM notes WHERE rowid = last_insert_rowid()",
+                NOTE_COLUMNS
+            ),
+            [],
+            Self::note_from_row,
+        )?;
+        
+        // Parse tags, links and tasks
+        self.update_note_tags(&note.id, content)?;
+        self.update_note_links(&note.id, content)?;
+        self.update_note_tasks(&note.id, content)?;
+
+        // Other notes may already link to this title
+        self.resolve_pending_links(&note.title)?;
+
+        if edit == NoteEdit::User {
+            self.log_words(&note.id, markdown::word_count(content) as i64)?;
+        }
+        
+        Ok(note)
+    }
+    
+    pub fn get_note(&self, id: &str) -> SqlResult<Option<Note>> {
+        let result = self.conn.query_row(
+            &format!(
+                "SELECT {} FROM notes WHERE id = ? AND deleted_at IS NULL",
+                NOTE_COLUMNS
+            ),
+            [id],
+            Self::note_from_row,
+        );
+
+        match result {
+            Ok(note) => Ok(Some(note)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+    
+    /// Notes in `folder` (or everywhere), pinned first. Archived notes are
+    /// left out unless `include_archived` is set.
+    pub fn list_notes(&self, folder: Option<&str>, include_archived: bool) -> SqlResult<Vec<Note>> {
+        let archived = if include_archived { "" } else { " AND archived_at IS NULL" };
+        if let Some(f) = folder {
+            let mut stmt = self.conn.prepare(
+                &format!(
+                    "SELECT {} FROM notes WHERE folder = ? AND deleted_at IS NULL{}
+                     ORDER BY pinned DESC, updated_at DESC",
+                    NOTE_COLUMNS, archived
+                ),
+            )?;
+
+            let notes = stmt.query_map([f], Self::note_from_row)?;
+
+            notes.collect()
+        } else {
+            let mut stmt = self.conn.prepare(
+                &format!(
+                    "SELECT {} FROM notes WHERE deleted_at IS NULL{}
+                     ORDER BY pinned DESC, updated_at DESC",
+                    NOTE_COLUMNS, archived
+                ),
+            )?;
+
+            let notes = stmt.query_map([], Self::note_from_row)?;
+
+            notes.collect()
+        }
+    }
+    
+    pub fn update_note(&self, id: &str, title: Option<&str>, content: Option<&str>, properties: Option<&str>) -> SqlResult<Option<Note>> {
+        self.update_note_as(id, title, content, properties, NoteEdit::User)
+    }
+
+    fn update_note_as(
+        &self,
+        id: &str,
+        title: Option<&str>,
+        content: Option<&str>,
+        properties: Option<&str>,
+        edit: NoteEdit,
+    ) -> SqlResult<Option<Note>> {
+        if title.is_none() && content.is_none() && properties.is_none() {
+            return self.get_note(id);
+        }
+
+        // Validate properties if provided
+        if let Some(props) = properties {
+            if !props.is_empty() {
+                if let Err(e) = Self::validate_properties(props) {
+                    return Err(rusqlite::Error::ToSqlConversionFailure(
+                        Box::new(std::io::Error::new(std::io::ErrorKind::InvalidInput, e))
+                    ));
+                }
+            }
+        }
+
+        // Remember the old title so links can follow a rename,
+        // and the old word count so the writing log can record the change
+        let old_title = match title {
+            Some(_) => self.get_note(id)?.map(|n| n.title),
+            None => None,
+        };
+        let old_word_count: Option<i64> = match content {
+            Some(_) if edit == NoteEdit::User => match self.conn.query_row("SELECT word_count FROM notes WHERE id = ?", [id], |row| row.get(0)) {
+                Ok(count) => Some(count),
+                Err(rusqlite::Error::QueryReturnedNoRows) => None,
+                Err(e) => return Err(e),
+            },
+            _ => None,
+        };
+
+        // Build dynamic SQL and collect owned values for params
+        let mut sql_parts = Vec::new();
+        let mut param_values: Vec<String> = Vec::new();
+
+        if let Some(t) = title {
+            sql_parts.push("title = ?");
+            param_values.push(t.to_string());
+        }
+        if let Some(c) = content {
+            sql_parts.push("content = ?");
+            param_values.push(c.to_string());
+            sql_parts.push("word_count = ?");
+            param_values.push(markdown::word_count(c).to_string());
+        }
+        if let Some(p) = properties {
+            sql_parts.push("properties = ?");
+            param_values.push(p.to_string());
+        }
+        sql_parts.push("updated_at = strftime('%s', 'now')");
+        param_values.push(id.to_string());
+
+        let sql = format!("UPDATE notes SET {} WHERE id = ?", sql_parts.join(", "));
+
+        // Convert to references for params_from_iter
+        let params: Vec<&dyn rusqlite::ToSql> = param_values.iter().map(|s| s as &dyn rusqlite::ToSql).collect();
+        self.conn.execute(&sql, params_from_iter(params))?;
+
+        let note = self.get_note(id)?;
+        if let Some(n) = &note {
+            if content.is_some() {
+                self.update_note_tags(&n.id, &n.content)?;
+                self.update_note_links(&n.id, &n.content)?;
+                self.update_note_tasks(&n.id, &n.content)?;
+            }
+            if let Some(old_count) = old_word_count {
+                self.log_words(&n.id, markdown::word_count(&n.content) as i64 - old_count)?;
+            }
+            if let Some(old) = old_title.as_deref() {
+                if old != n.title {
+                    self.relink_after_title_change(&n.id, old, &n.title)?;
+                }
+            }
+        }
+
+        Ok(note)
+    }
+    
+    /// A page of note summaries, pinned notes first. Pagination is keyset-based,
+    /// so pages stay stable while notes are added or edited elsewhere in the list.
+    pub fn list_note_summaries(
+        &self,
+        filter: &NoteSummaryFilter,
+        sort: &NoteSort,
+        cursor: Option<&str>,
+        limit: Option<usize>,
+    ) -> SqlResult<NoteSummaryPage> {
+        let limit = limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+        let descending = sort.descending.unwrap_or(matches!(
+            sort.field,
+            NoteSortField::Created | NoteSortField::Updated | NoteSortField::WordCount
+        ));
+        let (direction, compare) = if descending { ("DESC", "<") } else { ("ASC", ">") };
+
+        // `missing` orders notes without a sort value after the rest; `value` is the sort key
+        let mut params: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
+        let property_path = match &sort.field {
+            NoteSortField::Property { key } => {
+                if key.is_empty() || key.contains('"') {
+                    return Err(invalid_input(format!("Invalid property key: {}", key)));
+                }
+                Some(format!("$.\"{}\".value", key))
+            }
+            _ => None,
+        };
+        let (missing_expr, value_expr) = match &sort.field {
+            NoteSortField::Title => ("0", "n.title COLLATE NOCASE"),
+            NoteSortField::Created => ("0", "n.created_at"),
+            NoteSortField::Updated => ("0", "n.updated_at"),
+            NoteSortField::WordCount => ("0", "n.word_count"),
+            NoteSortField::Property { .. } => (
+                "(CASE WHEN json_valid(n.properties) THEN json_extract(n.properties, ?) END IS NULL)",
+                "CASE WHEN json_valid(n.properties) THEN json_extract(n.properties, ?) END",
+            ),
+        };
+        let push_path = |params: &mut Vec<Box<dyn rusqlite::ToSql>>| {
+            if let Some(path) = &property_path {
+                params.push(Box::new(path.clone()));
+            }
+        };
+
+        push_path(&mut params);
+        push_path(&mut params);
+        let mut sql = format!(
+            "SELECT n.id, n.title, n.folder, n.project_id, p.name, n.word_count, n.content,
+                    n.created_at, n.updated_at, n.pinned, n.favorite, n.archived_at,
+                    {} AS sort_missing, {} AS sort_value
+             FROM notes n
+             LEFT JOIN projects p ON p.id = n.project_id
+             WHERE n.deleted_at IS NULL",
+            missing_expr, value_expr
+        );
+
+        if let Some(folder) = filter.folder.as_deref() {
+            let folder = Self::normalize_folder_path(folder)?;
+            if filter.include_subfolders {
+                sql.push_str(" AND (n.folder = ? OR substr(n.folder, 1, length(?) + 1) = ? || '/')");
+                params.push(Box::new(folder.clone()));
+                params.push(Box::new(folder.clone()));
+            } else {
+                sql.push_str(" AND n.folder = ?");
+            }
+            params.push(Box::new(folder));
+        }
+        if let Some(project_id) = &filter.project_id {
+            sql.push_str(" AND n.project_id = ?");
+            params.push(Box::new(project_id.clone()));
+        }
+        let state = NoteStateFilter {
+            archived: Some(filter.state.archived.unwrap_or(false)),
+            ..filter.state.clone()
+        };
+        sql.push_str(&state.sql("n"));
+        if !filter.tag_ids.is_empty() {
+            let tag_ids: HashSet<&String> = filter.tag_ids.iter().collect();
+            sql.push_str(&format!(
+                " AND n.id IN (SELECT note_id FROM note_tags WHERE tag_id IN ({}) GROUP BY note_id HAVING COUNT(*) = {})",
+                vec!["?"; tag_ids.len()].join(", "),
+                tag_ids.len()
+            ));
+            for tag_id in tag_ids {
+                params.push(Box::new(tag_id.clone()));
+            }
+        }
+
+        if let Some(cursor) = cursor {
+            let cursor: NoteCursor = serde_json::from_str(cursor)
+                .map_err(|_| invalid_input("Invalid cursor"))?;
+            sql.push_str(" AND (n.pinned < ? OR (n.pinned = ?");
+            params.push(Box::new(cursor.pinned));
+            params.push(Box::new(cursor.pinned));
+            if cursor.missing {
+                sql.push_str(&format!(" AND {} = 1 AND n.id {} ?", missing_expr, compare));
+                push_path(&mut params);
+            } else {
+                sql.push_str(&format!(
+                    " AND ({m} = 1 OR ({m} = 0 AND ({v} {c} ? OR ({v} = ? AND n.id {c} ?))))",
+                    m = missing_expr,
+                    v = value_expr,
+                    c = compare
+                ));
+                let value = match cursor.value {
+                    JsonValue::Number(n) => match n.as_i64() {
+                        Some(i) => rusqlite::types::Value::Integer(i),
+                        None => rusqlite::types::Value::Real(n.as_f64().unwrap_or(0.0)),
+                    },
+                    JsonValue::String(s) => rusqlite::types::Value::Text(s),
+                    _ => return Err(invalid_input("Invalid cursor")),
+                };
+                push_path(&mut params);
+                push_path(&mut params);
+                push_path(&mut params);
+                params.push(Box::new(value.clone()));
+                push_path(&mut params);
+                params.push(Box::new(value));
+            }
+            params.push(Box::new(cursor.id));
+            sql.push_str("))");
+        }
+
+        sql.push_str(&format!(
+            " ORDER BY n.pinned DESC, sort_missing ASC, sort_value {d}, n.id {d} LIMIT {}",
+            limit + 1,
+            d = direction
+        ));
+
+        let mut stmt = self.conn.prepare(&sql)?;
+        let mut rows: Vec<(NoteSummary, bool, rusqlite::types::Value)> = stmt
+            .query_map(params_from_iter(params.iter()), |row| {
+                // Frontmatter is skipped before truncating, so a long one can't leak into the preview
+                let content: String = row.get(6)?;
+                let body = markdown::strip_frontmatter(&content);
+                let excerpt = body.char_indices().nth(PREVIEW_SOURCE_CHARS).map_or(body, |(end, _)| &body[..end]);
+                Ok((
+                    NoteSummary {
+                        id: row.get(0)?,
+                        title: row.get(1)?,
+                        folder: row.get(2)?,
+                        project_id: row.get(3)?,
+                        project_name: row.get(4)?,
+                        tags: Vec::new(),
+                        word_count: row.get(5)?,
+                        preview: markdown::preview(excerpt, PREVIEW_CHARS),
+                        created_at: row.get(7)?,
+                        updated_at: row.get(8)?,
+                        pinned: row.get(9)?,
+                        favorite: row.get(10)?,
+                        archived_at: row.get(11)?,
+                    },
+                    row.get(12)?,
+                    row.get(13)?,
+                ))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let has_more = rows.len() > limit;
+        rows.truncate(limit);
+
+        let next_cursor = match rows.last() {
+            Some((note, missing, value)) if has_more => {
+                let value = match value {
+                    rusqlite::types::Value::Integer(i) => JsonValue::from(*i),
+                    rusqlite::types::Value::Real(f) => JsonValue::from(*f),
+                    rusqlite::types::Value::Text(s) => JsonValue::from(s.clone()),
+                    _ => JsonValue::Null,
+                };
+                let cursor = NoteCursor { pinned: note.pinned, missing: *missing, value, id: note.id.clone() };
+                Some(serde_json::to_string(&cursor).map_err(|e| invalid_input(e.to_string()))?)
+            }
+            _ => None,
+        };
+
+        let mut notes: Vec<NoteSummary> = rows.into_iter().map(|(note, _, _)| note).collect();
+        if !notes.is_empty() {
+            let sql = format!(
+                "SELECT nt.note_id, t.name FROM note_tags nt
+                 JOIN tags t ON t.id = nt.tag_id
+                 WHERE nt.note_id IN ({})
+                 ORDER BY t.name COLLATE NOCASE",
+                vec!["?"; notes.len()].join(", ")
+            );
+            let mut stmt = self.conn.prepare(&sql)?;
+            let mut tags: HashMap<String, Vec<String>> = HashMap::new();
+            let rows = stmt.query_map(params_from_iter(notes.iter().map(|n| &n.id)), |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+            })?;
+            for row in rows {
+                let (note_id, name) = row?;
+                tags.entry(note_id).or_default().push(name);
+            }
+            for note in &mut notes {
+                note.tags = tags.remove(&note.id).unwrap_or_default();
+            }
+        }
+
+        Ok(NoteSummaryPage { notes, next_cursor })
+    }
+
+    pub fn delete_note(&self, id: &str) -> SqlResult<bool> {
+        let changes = self.conn.execute(
+            "UPDATE notes SET deleted_at = strftime('%s', 'now') WHERE id = ?",
+            [id],
+        )?;
+        Ok(changes > 0)
+    }
+
+    fn note_from_row(row: &rusqlite::Row) -> SqlResult<Note> {
+        Ok(Note {
+            id: row.get(0)?,
+            title: row.get(1)?,
+            content: row.get(2)?,
+            folder: row.get(3)?,
+            project_id: row.get(4)?,
+            properties: row.get(5)?,
+            created_at: row.get(6)?,
+            updated_at: row.get(7)?,
+            deleted_at: row.get(8)?,
+            pinned: row.get(9)?,
+            favorite: row.get(10)?,
+            archived_at: row.get(11)?,
+        })
+    }
+
+    pub fn set_note_pinned(&self, id: &str, pinned: bool) -> SqlResult<Option<Note>> {
+        self.conn.execute("UPDATE notes SET pinned = ? WHERE id = ? AND deleted_at IS NULL", rusqlite::params![pinned, id])?;
+        self.get_note(id)
+    }
+
+    pub fn set_note_favorite(&self, id: &str, favorite: bool) -> SqlResult<Option<Note>> {
+        self.conn.execute("UPDATE notes SET favorite = ? WHERE id = ? AND deleted_at IS NULL", rusqlite::params![favorite, id])?;
+        self.get_note(id)
+    }
+
+    /// Archive or unarchive a note; archiving keeps the original timestamp if already archived
+    pub fn set_note_archived(&self, id: &str, archived: bool) -> SqlResult<Option<Note>> {
+        let sql = if archived {
+            "UPDATE notes SET archived_at = COALESCE(archived_at, strftime('%s', 'now')) WHERE id = ? AND deleted_at IS NULL"
+        } else {
+            "UPDATE notes SET archived_at = NULL WHERE id = ? AND deleted_at IS NULL"
+        };
+        self.conn.execute(sql, [id])?;
+        self.get_note(id)
+    }
+
+    /// Bring a note back from the trash
+    pub fn restore_note(&self, id: &str) -> SqlResult<bool> {
+        let changes = self.conn.execute(
+            "UPDATE notes SET deleted_at = NULL, updated_at = strftime('%s', 'now') WHERE id = ?",
+            [id],
+        )?;
+        Ok(changes > 0)
+    }
+    
+    /// Full-text search. Archived notes are included unless `filter` says otherwise.
+    pub fn search_notes(&self, query: &str, filter: &NoteStateFilter) -> SqlResult<Vec<Note>> {
+        let mut stmt = self.conn.prepare(
+            &format!(
+                "SELECT {} FROM notes
+                 JOIN notes_fts ON notes.id = notes_fts.note_id
+                 WHERE notes_fts MATCH ? AND notes.deleted_at IS NULL{}
+                 ORDER BY rank
+                 LIMIT 50",
+                NOTE_COLUMNS,
+                filter.sql("notes")
+            ),
+        )?;
+
+        let notes = stmt.query_map([query], Self::note_from_row)?;
+
+        notes.collect()
+    }
+    
+    pub fn get_folders(&self) -> SqlResult<Vec<Folder>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT path, color, icon, sort_order FROM folders ORDER BY sort_order",
+        )?;
+        
+        let folders = stmt.query_map([], |row| {
+            Ok(Folder {
+                path: row.get(0)?,
+                color: row.get(1)?,
+                icon: row.get(2)?,
+                sort_order: row.get(3)?,
+            })
+        })?;
+        
+        folders.collect()
+    }
+
+    /// Trim slashes and whitespace from a folder path and reject empty or relative segments
+    fn normalize_folder_path(path: &str) -> SqlResult<String> {
+        let segments: Vec<&str> = path.trim().trim_matches('/').split('/').map(str::trim).collect();
+        if segments.iter().any(|s| s.is_empty() || *s == "." || *s == ".." || s.contains('\\')) {
+            return Err(invalid_input(format!("Invalid folder path: {}", path)));
+        }
+        Ok(segments.join("/"))
+    }
+
+    fn get_folder(&self, path: &str) -> SqlResult<Option<Folder>> {
+        let result = self.conn.query_row(
+            "SELECT path, color, icon, sort_order FROM folders WHERE path = ?",
+            [path],
+            |row| {
+                Ok(Folder {
+                    path: row.get(0)?,
+                    color: row.get(1)?,
+                    icon: row.get(2)?,
+                    sort_order: row.get(3)?,
+                })
+            },
+        );
+
+        match result {
+            Ok(folder) => Ok(Some(folder)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Insert rows for a folder and any missing ancestors, appended to the sort order
+    fn ensure_folder(&self, path: &str) -> SqlResult<()> {
+        let segments: Vec<&str> = path.split('/').collect();
+        for depth in 1..=segments.len() {
+            self.conn.execute(
+                "INSERT OR IGNORE INTO folders (path, sort_order)
+                 VALUES (?, (SELECT COALESCE(MAX(sort_order), 0) + 1 FROM folders))",
+                [segments[..depth].join("/")],
+            )?;
+        }
+        Ok(())
+    }
+
+    pub fn create_folder(&self, path: &str, color: Option<&str>, icon: Option<&str>) -> SqlResult<Folder> {
+        let path = Self::normalize_folder_path(path)?;
+        if self.get_folder(&path)?.is_some() {
+            return Err(invalid_input(format!("Folder already exists: {}", path)));
+        }
+
+        self.ensure_folder(&path)?;
+        self.conn.execute(
+            "UPDATE folders SET color = ?, icon = ? WHERE path = ?",
+            rusqlite::params![color, icon, path],
+        )?;
+
+        Ok(self.get_folder(&path)?.unwrap())
+    }
+
+    pub fn update_folder(&self, path: &str, updates: &FolderUpdate) -> SqlResult<Option<Folder>> {
+        let path = Self::normalize_folder_path(path)?;
+        let mut sql_parts = Vec::new();
+        let mut params: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
+        let clearable = |value: &str| if value.is_empty() { None } else { Some(value.to_string()) };
+
+        if let Some(c) = &updates.color {
+            sql_parts.push("color = ?");
+            params.push(Box::new(clearable(c)));
+        }
+        if let Some(i) = &updates.icon {
+            sql_parts.push("icon = ?");
+            params.push(Box::new(clearable(i)));
+        }
+        if let Some(o) = updates.sort_order {
+            sql_parts.push("sort_order = ?");
+            params.push(Box::new(o));
+        }
+
+        if !sql_parts.is_empty() {
+            params.push(Box::new(path.clone()));
+            let sql = format!("UPDATE folders SET {} WHERE path = ?", sql_parts.join(", "));
+            self.conn.execute(&sql, params_from_iter(params))?;
+        }
+
+        self.get_folder(&path)
+    }
+
+    /// Rename or move a folder. Subfolders and every note inside (trashed ones too) follow.
+    pub fn rename_folder(&self, path: &str, new_path: &str) -> SqlResult<Option<Folder>> {
+        let path = Self::normalize_folder_path(path)?;
+        let new_path = Self::normalize_folder_path(new_path)?;
+        if self.get_folder(&path)?.is_none() {
+            return Ok(None);
+        }
+        if new_path == path {
+            return self.get_folder(&path);
+        }
+        if SYSTEM_FOLDERS.contains(&path.as_str()) {
+            return Err(invalid_input(format!("The {} folder can't be renamed", path)));
+        }
+        if new_path.starts_with(&format!("{}/", path)) {
+            return Err(invalid_input("A folder can't be moved inside itself"));
+        }
+        if self.get_folder(&new_path)?.is_some() {
+            return Err(invalid_input(format!("Folder already exists: {}", new_path)));
+        }
+
+        let tx = self.conn.unchecked_transaction()?;
+
+        // `path` itself and everything under `path/`
+        self.conn.execute(
+            "UPDATE folders SET path = ?2 || substr(path, length(?1) + 1)
+             WHERE path = ?1 OR substr(path, 1, length(?1) + 1) = ?1 || '/'",
+            [&path, &new_path],
+        )?;
+        self.conn.execute(
+            "UPDATE notes SET folder = ?2 || substr(folder, length(?1) + 1), updated_at = strftime('%s', 'now')
+             WHERE folder = ?1 OR substr(folder, 1, length(?1) + 1) = ?1 || '/'",
+            [&path, &new_path],
+        )?;
+        self.ensure_folder(&new_path)?;
+
+        tx.commit()?;
+        self.get_folder(&new_path)
+    }
+
+    /// Move a folder under `new_parent` (or to the top level), keeping its name
+    pub fn move_folder(&self, path: &str, new_parent: Option<&str>) -> SqlResult<Option<Folder>> {
+        let path = Self::normalize_folder_path(path)?;
+        let name = path.rsplit('/').next().unwrap_or(&path);
+        let new_path = match new_parent.map(str::trim).filter(|p| !p.is_empty()) {
+            Some(parent) => format!("{}/{}", Self::normalize_folder_path(parent)?, name),
+            None => name.to_string(),
+        };
+        self.rename_folder(&path, &new_path)
+    }
+
+    /// Delete a folder and its subfolders. Notes inside move to the inbox when
+    /// `move_notes_to_inbox` is set; otherwise a non-empty folder is refused.
+    pub fn delete_folder(&self, path: &str, move_notes_to_inbox: bool) -> SqlResult<bool> {
+        let path = Self::normalize_folder_path(path)?;
+        let path = path.as_str();
+        if SYSTEM_FOLDERS.contains(&path) {
+            return Err(invalid_input(format!("The {} folder can't be deleted", path)));
+        }
+
+        let in_subtree = "folder = ?1 OR substr(folder, 1, length(?1) + 1) = ?1 || '/'";
+        let note_count: i64 = self.conn.query_row(
+            &format!("SELECT COUNT(*) FROM notes WHERE {}", in_subtree),
+            [path],
+            |row| row.get(0),
+        )?;
+        if note_count > 0 && !move_notes_to_inbox {
+            return Err(invalid_input(format!("Folder {} still contains {} notes", path, note_count)));
+        }
+
+        let tx = self.conn.unchecked_transaction()?;
+        self.conn.execute(
+            &format!("UPDATE notes SET folder = 'inbox', updated_at = strftime('%s', 'now') WHERE {}", in_subtree),
+            [path],
+        )?;
+        let deleted = self.conn.execute(
+            "DELETE FROM folders WHERE path = ?1 OR substr(path, 1, length(?1) + 1) = ?1 || '/'",
+            [path],
+        )?;
+        tx.commit()?;
+
+        Ok(deleted > 0)
+    }
+
+    /// `update_note` followed by a move into `folder`, in one transaction
+    pub fn update_and_move_note(
+        &self,
+        id: &str,
+        title: Option<&str>,
+        content: Option<&str>,
+        properties: Option<&str>,
+        folder: Option<&str>,
+    ) -> SqlResult<Option<Note>> {
+        let tx = self.conn.unchecked_transaction()?;
+        let note = self.update_note(id, title, content, properties)?;
+        let note = match (note, folder) {
+            (Some(_), Some(folder)) => {
+                self.set_notes_folder(&[id.to_string()], folder)?;
+                self.get_note(id)?
+            }
+            (note, _) => note,
+        };
+        tx.commit()?;
+        Ok(note)
+    }
+
+    pub fn move_note(&self, id: &str, folder: &str) -> SqlResult<Option<Note>> {
+        let moved = self.move_notes(&[id.to_string()], folder)?;
+        if moved == 0 {
+            return Ok(None);
+        }
+        self.get_note(id)
+    }
+
+    /// Move notes into `folder` (created if missing); returns how many notes moved
+    pub fn move_notes(&self, ids: &[String], folder: &str) -> SqlResult<usize> {
+        let tx = self.conn.unchecked_transaction()?;
+        let moved = self.set_notes_folder(ids, folder)?;
+        tx.commit()?;
+
+        Ok(moved)
+    }
+
+    /// `move_notes` without its own transaction. The folder is only created if a note moved.
+    fn set_notes_folder(&self, ids: &[String], folder: &str) -> SqlResult<usize> {
+        let folder = Self::normalize_folder_path(folder)?;
+
+        let mut moved = 0;
+        for id in ids {
+            moved += self.conn.execute(
+                "UPDATE notes SET folder = ?, updated_at = strftime('%s', 'now') WHERE id = ?",
+                [&folder, id],
+            )?;
+        }
+        if moved > 0 {
+            self.ensure_folder(&folder)?;
+        }
+
+        Ok(moved)
+    }
+
+    // Bulk note operations
+
+    /// Apply `operations` to every note in one transaction. Each note runs in its
+    /// own savepoint, so a failing note is rolled back and reported while the rest apply.
+    pub fn bulk_update_notes(&self, ids: &[String], operations: &[BulkNoteOperation]) -> SqlResult<Vec<BulkNoteResult>> {
+        // Validate shared arguments once, up front
+        for operation in operations {
+            match operation {
+                BulkNoteOperation::MoveFolder { folder } => {
+                    Self::normalize_folder_path(folder)?;
+                }
+                BulkNoteOperation::AddTag { tag } | BulkNoteOperation::RemoveTag { tag }
+                    if !markdown::is_valid_tag_name(tag.trim_start_matches('#')) =>
+                {
+                    return Err(invalid_input(format!("Invalid tag name: {}", tag)));
+                }
+                BulkNoteOperation::AssignProject { project_id: Some(project_id) }
+                    if self.get_project(project_id)?.is_none() =>
+                {
+                    return Err(invalid_input(format!("Project not found: {}", project_id)));
+                }
+                _ => {}
+            }
+        }
+
+        let tx = self.conn.unchecked_transaction()?;
+        let mut results = Vec::with_capacity(ids.len());
+
+        for id in ids {
+            self.conn.execute_batch("SAVEPOINT bulk_note")?;
+            let outcome = self.apply_note_operations(id, operations);
+            match &outcome {
+                Ok(()) => self.conn.execute_batch("RELEASE bulk_note")?,
+                Err(_) => self.conn.execute_batch("ROLLBACK TO bulk_note; RELEASE bulk_note")?,
+            }
+            results.push(BulkNoteResult {
+                note_id: id.clone(),
+                success: outcome.is_ok(),
+                error: outcome.err().map(|e| e.to_string()),
+            });
+        }
+
+        tx.commit()?;
+        Ok(results)
+    }
+
+    fn apply_note_operations(&self, id: &str, operations: &[BulkNoteOperation]) -> SqlResult<()> {
+        let exists: bool = self.conn.query_row(
+            "SELECT EXISTS (SELECT 1 FROM notes WHERE id = ?)",
+            [id],
+            |row| row.get(0),
+        )?;
+        if !exists {
+            return Err(invalid_input(format!("Note not found: {}", id)));
+        }
+
+        for operation in operations {
+            match operation {
+                BulkNoteOperation::MoveFolder { folder } => {
+                    let folder = Self::normalize_folder_path(folder)?;
+                    self.ensure_folder(&folder)?;
+                    self.conn.execute(
+                        "UPDATE notes SET folder = ?, updated_at = strftime('%s', 'now') WHERE id = ?",
+                        [&folder, id],
+                    )?;
+                }
+                BulkNoteOperation::AssignProject { project_id } => {
+                    self.assign_note_to_project(id, project_id.as_deref())?;
+                }
+                BulkNoteOperation::AddTag { tag } | BulkNoteOperation::RemoveTag { tag } => {
+                    let tag = tag.trim_start_matches('#');
+                    let content: String = self.conn.query_row("SELECT content FROM notes WHERE id = ?", [id], |row| row.get(0))?;
+                    let new_content = match operation {
+                        BulkNoteOperation::AddTag { .. } => markdown::append_tag(&content, tag),
+                        _ => markdown::remove_tag(&content, tag),
+                    };
+                    if new_content != content {
+                        self.conn.execute(
+                            "UPDATE notes SET content = ?, word_count = ?, updated_at = strftime('%s', 'now') WHERE id = ?",
+                            rusqlite::params![&new_content, markdown::word_count(&new_content) as i64, id],
+                        )?;
+                        self.update_note_tags(id, &new_content)?;
+                        self.update_note_tasks(id, &new_content)?;
+                    }
+                }
+                BulkNoteOperation::SetProperty { key, property_type, value } => {
+                    let current: Option<String> = self.conn.query_row("SELECT properties FROM notes WHERE id = ?", [id], |row| row.get(0))?;
+                    let mut properties: HashMap<String, Property> = match current.as_deref() {
+                        Some(json) if !json.trim().is_empty() => serde_json::from_str(json)
+                            .map_err(|e| invalid_input(format!("Invalid properties JSON: {}", e)))?,
+                        _ => HashMap::new(),
+                    };
+
+                    // Read-only properties can't be removed either
+                    if properties.get(key).is_some_and(|p| p.readonly) {
+                        return Err(invalid_input(format!("Property '{}' is read-only", key)));
+                    }
+                    if value.is_null() {
+                        properties.remove(key);
+                    } else {
+                        properties.insert(key.clone(), Property {
+                            key: key.clone(),
+                            prop_type: property_type.clone(),
+                            value: value.clone(),
+                            readonly: false,
+                        });
+                    }
+
+                    let json = serde_json::to_string(&properties).map_err(|e| invalid_input(e.to_string()))?;
+                    Self::validate_properties(&json).map_err(invalid_input)?;
+                    self.conn.execute(
+                        "UPDATE notes SET properties = ?, updated_at = strftime('%s', 'now') WHERE id = ?",
+                        [&json, id],
+                    )?;
+                }
+                BulkNoteOperation::Trash => {
+                    self.delete_note(id)?;
+                }
+                BulkNoteOperation::Restore => {
+                    self.restore_note(id)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    // Tag operations
+
+    pub fn create_tag(&self, name: &str, color: Option<&str>) -> SqlResult<Tag> {
+        self.conn.execute(
+            "INSERT INTO tags (name, color) VALUES (?, ?)",
+            [name, color.unwrap_or("")],
+        )?;
+        
+        let tag = self.conn.query_row(
+            &format!("SELECT {} FROM tags WHERE rowid = last_insert_rowid()", TAG_COLUMNS),
+            [],
+            Self::tag_from_row,
+        )?;
+        
+        Ok(tag)
+    }
+
+    pub fn get_tag(&self, id: &str) -> SqlResult<Option<Tag>> {
+        let result = self.conn.query_row(
+            &format!("SELECT {} FROM tags WHERE id = ?", TAG_COLUMNS),
+            [id],
+            Self::tag_from_row,
+        );
+        
+        match result {
+            Ok(tag) => Ok(Some(tag)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn get_tag_by_name(&self, name: &str) -> SqlResult<Option<Tag>> {
+        let result = self.conn.query_row(
+            &format!("SELECT {} FROM tags WHERE name = ? COLLATE NOCASE", TAG_COLUMNS),
+            [name],
+            Self::tag_from_row,
+        );
+        
+        match result {
+            Ok(tag) => Ok(Some(tag)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn get_all_tags(&self) -> SqlResult<Vec<serde_json::Value>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT tags.id, tags.name, tags.color, tags.created_at, COUNT(note_tags.note_id) as note_count,
+                    tags.description, tags.icon, tags.pinned, tags.sort_order, tags.parent_id
+             FROM tags
+             LEFT JOIN note_tags ON tags.id = note_tags.tag_id
+             GROUP BY tags.id
+             ORDER BY tags.pinned DESC, tags.sort_order, tags.name COLLATE NOCASE",
+        )?;
+        
+        let tags = stmt.query_map([], |row| {
+            Ok(serde_json::json!({
+                "id": row.get::<_, String>(0)?,
+                "name": row.get::<_, String>(1)?,
+                "color": row.get::<_, Option<String>>(2)?,
+                "created_at": row.get::<_, i64>(3)?,
+                "note_count": row.get::<_, i64>(4)?,
+                "description": row.get::<_, Option<String>>(5)?,
+                "icon": row.get::<_, Option<String>>(6)?,
+                "pinned": row.get::<_, bool>(7)?,
+                "sort_order": row.get::<_, i64>(8)?,
+                "parent_id": row.get::<_, Option<String>>(9)?
+            }))
+        })?;
+        
+        tags.collect()
+    }
+
+    fn tag_from_row(row: &rusqlite::Row) -> SqlResult<Tag> {
+        Ok(Tag {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            color: row.get(2)?,
+            created_at: row.get(3)?,
+            description: row.get(4)?,
+            icon: row.get(5)?,
+            pinned: row.get(6)?,
+            sort_order: row.get(7)?,
+            parent_id: row.get(8)?,
+        })
+    }
+
+    /// Update tag metadata. A new name is applied the same way as `rename_tag_in_notes`,
+    /// so `#tag` text in notes follows the rename. Renaming onto an existing tag merges
+    /// into it, and the other updates then apply to that tag.
+    pub fn update_tag(&self, id: &str, updates: &TagUpdate) -> SqlResult<Option<Tag>> {
+        let tag = match self.get_tag(id)? {
+            Some(t) => t,
+            None => return Ok(None),
+        };
+
+        let tx = self.conn.unchecked_transaction()?;
+        let mut id = id.to_string();
+
+        if let Some(name) = updates.name.as_deref().map(|n| n.trim_start_matches('#')) {
+            if name != tag.name {
+                let result = self.apply_tag_renames(&[(tag.name.clone(), name.to_string())], false)?;
+                if let Some(target_id) = result.tags.iter().find(|c| c.tag_id == id).and_then(|c| c.merged_into.clone()) {
+                    id = target_id;
+                }
+            }
+        }
+
+        if let Some(parent_id) = updates.parent_id.as_deref().filter(|p| !p.is_empty()) {
+            // Walk up from the new parent; reaching this tag would make a cycle
+            let mut current = Some(parent_id.to_string());
+            while let Some(ancestor_id) = current {
+                if ancestor_id == id {
+                    return Err(invalid_input("A tag can't be nested under itself"));
+                }
+                current = match self.get_tag(&ancestor_id)? {
+                    Some(ancestor) => ancestor.parent_id,
+                    None if ancestor_id == parent_id => return Err(invalid_input("Parent tag not found")),
+                    None => None,
+                };
+            }
+        }
+
+        let mut sql_parts = Vec::new();
+        let mut params: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
+        let clearable = |value: &str| if value.is_empty() { None } else { Some(value.to_string()) };
+
+        if let Some(c) = &updates.color {
+            sql_parts.push("color = ?");
+            params.push(Box::new(clearable(c)));
+        }
+        if let Some(d) = &updates.description {
+            sql_parts.push("description = ?");
+            params.push(Box::new(clearable(d)));
+        }
+        if let Some(i) = &updates.icon {
+            sql_parts.push("icon = ?");
+            params.push(Box::new(clearable(i)));
+        }
+        if let Some(p) = updates.pinned {
+            sql_parts.push("pinned = ?");
+            params.push(Box::new(p));
+        }
+        if let Some(o) = updates.sort_order {
+            sql_parts.push("sort_order = ?");
+            params.push(Box::new(o));
+        }
+        if let Some(p) = &updates.parent_id {
+            sql_parts.push("parent_id = ?");
+            params.push(Box::new(clearable(p)));
+        }
+
+        if !sql_parts.is_empty() {
+            params.push(Box::new(id.clone()));
+            let sql = format!("UPDATE tags SET {} WHERE id = ?", sql_parts.join(", "));
+            self.conn.execute(&sql, params_from_iter(params))?;
+        }
+
+        tx.commit()?;
+        self.get_tag(&id)
+    }
+
+    /// All tags nested by `/`, with direct and rolled-up counts of live notes
+    pub fn get_tag_tree(&self) -> SqlResult<Vec<TagTreeNode>> {
+        let tags: Vec<Tag> = self.conn
+            .prepare(&format!("SELECT {} FROM tags ORDER BY tags.name COLLATE NOCASE", TAG_COLUMNS))?
+            .query_map([], Self::tag_from_row)?
+            .collect::<Result<_, _>>()?;
+
+        let tagged: Vec<(String, String)> = self.conn
+            .prepare(
+                "SELECT note_tags.tag_id, note_tags.note_id
+                 FROM note_tags
+                 JOIN notes ON notes.id = note_tags.note_id
+                 WHERE notes.deleted_at IS NULL",
+            )?
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<_, _>>()?;
+
+        // One entry per path (keyed case-insensitively), including implied parents
+        let mut nodes: HashMap<String, (TagTreeNode, HashSet<String>)> = HashMap::new();
+        let mut key_by_id: HashMap<String, String> = HashMap::new();
+        let mut overrides: Vec<(String, String)> = Vec::new();
+
+        for tag in tags {
+            let segments: Vec<&str> = tag.name.split('/').filter(|s| !s.is_empty()).collect();
+            for depth in 1..=segments.len() {
+                let path = segments[..depth].join("/");
+                let node = &mut nodes.entry(path.to_lowercase()).or_insert_with(|| {
+                    let node = TagTreeNode {
+                        name: segments[depth - 1].to_string(),
+                        path: path.clone(),
+                        id: None,
+                        color: None,
+                        description: None,
+                        icon: None,
+                        pinned: false,
+                        sort_order: 0,
+                        direct_count: 0,
+                        total_count: 0,
+                        children: Vec::new(),
+                    };
+                    (node, HashSet::new())
+                }).0;
+                if depth == segments.len() {
+                    node.id = Some(tag.id.clone());
+                    node.path = path;
+                    node.color = tag.color.clone();
+                    node.description = tag.description.clone();
+                    node.icon = tag.icon.clone();
+                    node.pinned = tag.pinned;
+                    node.sort_order = tag.sort_order;
+                }
+            }
+
+            let key = segments.join("/").to_lowercase();
+            if let Some(parent_id) = tag.parent_id {
+                overrides.push((key.clone(), parent_id));
+            }
+            key_by_id.insert(tag.id, key);
+        }
+
+        for (tag_id, note_id) in tagged {
+            if let Some((_, direct)) = key_by_id.get(&tag_id).and_then(|key| nodes.get_mut(key)) {
+                direct.insert(note_id);
+            }
+        }
+
+        // Each node sits under its `/` parent unless a parent override moves it
+        let mut parents: HashMap<String, String> = nodes
+            .keys()
+            .filter_map(|key| key.rfind('/').map(|pos| (key.clone(), key[..pos].to_string())))
+            .collect();
+        for (key, parent_id) in overrides {
+            let Some(parent_key) = key_by_id.get(&parent_id).filter(|p| **p != key) else { continue };
+            // Skip overrides that would loop back through this node
+            let mut ancestor = Some(parent_key);
+            let mut loops = false;
+            while let Some(current) = ancestor {
+                if *current == key {
+                    loops = true;
+                    break;
+                }
+                ancestor = parents.get(current);
+            }
+            if !loops {
+                parents.insert(key, parent_key.clone());
+            }
+        }
+
+        let mut children: HashMap<String, Vec<String>> = HashMap::new();
+        let mut root_keys = Vec::new();
+        for key in nodes.keys() {
+            match parents.get(key) {
+                Some(parent) => children.entry(parent.clone()).or_default().push(key.clone()),
+                None => root_keys.push(key.clone()),
+            }
+        }
+
+        fn assemble(
+            key: &str,
+            nodes: &mut HashMap<String, (TagTreeNode, HashSet<String>)>,
+            children: &HashMap<String, Vec<String>>,
+        ) -> (TagTreeNode, HashSet<String>) {
+            let (mut node, direct) = nodes.remove(key).unwrap();
+            node.direct_count = direct.len();
+
+            let mut total = direct;
+            for child_key in children.get(key).into_iter().flatten() {
+                let (child, notes) = assemble(child_key, nodes, children);
+                total.extend(notes);
+                node.children.push(child);
+            }
+            node.total_count = total.len();
+            sort_tag_tree_nodes(&mut node.children);
+
+            (node, total)
+        }
+
+        let mut roots: Vec<TagTreeNode> = root_keys
+            .iter()
+            .map(|key| assemble(key, &mut nodes, &children).0)
+            .collect();
+        sort_tag_tree_nodes(&mut roots);
+
+        Ok(roots)
+    }
+
+    /// Rename a tag everywhere, like `rename_tag_in_notes`. Returns false if the tag doesn't exist.
+    pub fn rename_tag(&self, id: &str, new_name: &str) -> SqlResult<bool> {
+        Ok(self.rename_tag_in_notes(id, new_name, false)?.is_some())
+    }
+
+    /// Rename a tag and its children (`#a/b` → `#c/b`) in the tags table and in
+    /// every note body. Renaming onto an existing tag merges into it.
+    pub fn rename_tag_in_notes(&self, id: &str, new_name: &str, dry_run: bool) -> SqlResult<Option<TagRewriteResult>> {
+        let tag = match self.get_tag(id)? {
+            Some(t) => t,
+            None => return Ok(None),
+        };
+
+        self.rewrite_tags(&[(tag.name, new_name.trim_start_matches('#').to_string())], dry_run).map(Some)
+    }
+
+    /// Merge tags (and their children) into `target_id`, rewriting note bodies
+    /// and deleting the merged-away tags
+    pub fn merge_tags(&self, source_ids: &[String], target_id: &str, dry_run: bool) -> SqlResult<Option<TagRewriteResult>> {
+        let target = match self.get_tag(target_id)? {
+            Some(t) => t,
+            None => return Ok(None),
+        };
+
+        let mut renames = Vec::new();
+        for source_id in source_ids.iter().filter(|id| id.as_str() != target_id) {
+            match self.get_tag(source_id)? {
+                Some(source) => renames.push((source.name, target.name.clone())),
+                None => return Ok(None),
+            }
+        }
+
+        self.rewrite_tags(&renames, dry_run).map(Some)
+    }
+
+    /// Apply `(old, new)` tag path renames to tags and note bodies in one transaction.
+    /// Trashed notes are rewritten too, so restoring them doesn't bring old tags back.
+    fn rewrite_tags(&self, renames: &[(String, String)], dry_run: bool) -> SqlResult<TagRewriteResult> {
+        if dry_run {
+            return self.apply_tag_renames(renames, true);
+        }
+
+        let tx = self.conn.unchecked_transaction()?;
+        let result = self.apply_tag_renames(renames, false)?;
+        tx.commit()?;
+        Ok(result)
+    }
+
+    /// `rewrite_tags` without its own transaction, for callers that already hold one
+    fn apply_tag_renames(&self, renames: &[(String, String)], dry_run: bool) -> SqlResult<TagRewriteResult> {
+        for (old, new) in renames {
+            if !markdown::is_valid_tag_name(new) {
+                return Err(invalid_input(format!("Invalid tag name: {}", new)));
+            }
+            if new.to_lowercase().starts_with(&format!("{}/", old.to_lowercase())) {
+                return Err(invalid_input(format!("Cannot move #{} under itself", old)));
+            }
+        }
+
+        // `#old` or `#old/child` → `#new` / `#new/child`, matched case-insensitively
+        let rename = |name: &str| -> Option<String> {
+            renames.iter().find_map(|(old, new)| {
+                let rest = markdown::strip_tag_prefix(name, old)?;
+                (name != format!("{}{}", new, rest)).then(|| format!("{}{}", new, rest))
+            })
+        };
+
+        let mut result = TagRewriteResult { dry_run, tags: Vec::new(), modified_notes: Vec::new() };
+
+        // Plan tag renames in order, tracking which names are taken as we go
+        let all_tags: Vec<(String, String)> = self.conn
+            .prepare("SELECT id, name FROM tags ORDER BY name COLLATE NOCASE")?
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<_, _>>()?;
+        let mut names: HashMap<String, String> = all_tags.iter().map(|(id, name)| (name.to_lowercase(), id.clone())).collect();
+
+        for (tag_id, old_name) in &all_tags {
+            let Some(new_name) = rename(old_name) else { continue };
+            if names.get(&old_name.to_lowercase()) == Some(tag_id) {
+                names.remove(&old_name.to_lowercase());
+            }
+            let merged_into = names.get(&new_name.to_lowercase()).filter(|id| *id != tag_id).cloned();
+            if merged_into.is_none() {
+                names.insert(new_name.to_lowercase(), tag_id.clone());
+            }
+            result.tags.push(TagRename { tag_id: tag_id.clone(), old_name: old_name.clone(), new_name, merged_into });
+        }
+
+        // Rewrite `#tag` occurrences in note bodies
+        let mut rewrites: Vec<(String, String)> = Vec::new();
+        {
+            let mut stmt = self.conn.prepare(
+                "SELECT id, title, content FROM notes WHERE instr(content, '#') > 0 ORDER BY title COLLATE NOCASE",
+            )?;
+            let rows = stmt.query_map([], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?))
+            })?;
+
+            for row in rows {
+                let (note_id, title, content) = row?;
+                let mut new_content = String::with_capacity(content.len());
+                let mut last = 0;
+                let mut occurrences = 0;
+
+                for (start, end) in markdown::tag_spans(&content) {
+                    if let Some(new_name) = rename(&content[start..end]) {
+                        new_content.push_str(&content[last..start]);
+                        new_content.push_str(&new_name);
+                        last = end;
+                        occurrences += 1;
+                    }
+                }
+                if occurrences == 0 {
+                    continue;
+                }
+                new_content.push_str(&content[last..]);
+
+                result.modified_notes.push(TagNoteChange { note_id: note_id.clone(), title, occurrences });
+                rewrites.push((note_id, new_content));
+            }
+        }
+
+        if dry_run {
+            return Ok(result);
+        }
+
+        for (note_id, new_content) in &rewrites {
+            self.conn.execute(
+                "UPDATE notes SET content = ?, word_count = ?, updated_at = strftime('%s', 'now') WHERE id = ?",
+                rusqlite::params![new_content, markdown::word_count(new_content) as i64, note_id],
+            )?;
+            self.update_note_tasks(note_id, new_content)?;
+        }
+
+        for change in &result.tags {
+            match &change.merged_into {
+                Some(target_id) => {
+                    self.conn.execute(
+                        "UPDATE OR IGNORE note_tags SET tag_id = ? WHERE tag_id = ?",
+                        [target_id, &change.tag_id],
+                    )?;
+                    self.conn.execute("DELETE FROM note_tags WHERE tag_id = ?", [&change.tag_id])?;
+                    self.conn.execute("DELETE FROM tags WHERE id = ?", [&change.tag_id])?;
+                }
+                None => {
+                    self.conn.execute(
+                        "UPDATE tags SET name = ? WHERE id = ?",
+                        [&change.new_name, &change.tag_id],
+                    )?;
+                }
+            }
+        }
+
+        Ok(result)
+    }
+
+    pub fn delete_tag(&self, id: &str) -> SqlResult<bool> {
+        let changes = self.conn.execute("DELETE FROM tags WHERE id = ?", [id])?;
+        Ok(changes > 0)
+    }
+
+    pub fn add_tag_to_note(&self, note_id: &str, tag_name: &str) -> SqlResult<()> {
+        // Tag syncing only reads id/name/color so migrations can run it before later tag columns exist
+        let existing = self.conn.query_row(
+            "SELECT id FROM tags WHERE name = ? COLLATE NOCASE",
+            [tag_name],
+            |row| row.get::<_, String>(0),
+        );
+
+        let tag_id = match existing {
+            Ok(id) => id,
+            Err(rusqlite::Error::QueryReturnedNoRows) => {
+                let color = self.generate_tag_color(tag_name);
+                self.conn.execute("INSERT INTO tags (name, color) VALUES (?, ?)", [tag_name, &color])?;
+                self.conn.query_row("SELECT id FROM tags WHERE rowid = last_insert_rowid()", [], |row| row.get(0))?
+            }
+            Err(e) => return Err(e),
+        };
+        
+        self.conn.execute(
+            "INSERT OR IGNORE INTO note_tags (note_id, tag_id) VALUES (?, ?)",
+            [note_id, &tag_id],
+        )?;
+        
+        Ok(())
+    }
+
+    pub fn remove_tag_from_note(&self, note_id: &str, tag_id: &str) -> SqlResult<()> {
+        self.conn.execute(
+            "DELETE FROM note_tags WHERE note_id = ? AND tag_id = ?",
+            [note_id, tag_id],
+        )?;
+        Ok(())
+    }
+
+    pub fn get_note_tags(&self, note_id: &str) -> SqlResult<Vec<Tag>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {}
+             FROM tags
+             JOIN note_tags ON tags.id = note_tags.tag_id
+             WHERE note_tags.note_id = ?
+             ORDER BY tags.name COLLATE NOCASE",
+            TAG_COLUMNS
+        ))?;
+        
+        let tags = stmt.query_map([note_id], Self::tag_from_row)?;
+        
+        tags.collect()
+    }
+
+    /// Ids of a tag and, when `include_descendants` is set, every tag nested under it
+    fn tag_ids_with_descendants(&self, tag_id: &str, include_descendants: bool) -> SqlResult<Vec<String>> {
+        if !include_descendants {
+            return Ok(vec![tag_id.to_string()]);
+        }
+
+        let Some(root) = self.get_tag(tag_id)? else {
+            return Ok(Vec::new());
+        };
+
+        // Matched in Rust, since SQLite's `lower()` only folds ASCII
+        let mut stmt = self.conn.prepare("SELECT id, name FROM tags")?;
+        let tags = stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?;
+        let mut ids = Vec::new();
+        for tag in tags {
+            let (id, name) = tag?;
+            if markdown::strip_tag_prefix(&name, &root.name).is_some() {
+                ids.push(id);
+            }
+        }
+        Ok(ids)
+    }
+
+    pub fn get_notes_by_tag(&self, tag_id: &str, include_descendants: bool) -> SqlResult<Vec<Note>> {
+        let tag_ids = self.tag_ids_with_descendants(tag_id, include_descendants)?;
+        let placeholders = vec!["?"; tag_ids.len()].join(",");
+
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT DISTINCT {}
+             FROM notes
+             JOIN note_tags ON notes.id = note_tags.note_id
+             WHERE note_tags.tag_id IN ({}) AND notes.deleted_at IS NULL AND notes.archived_at IS NULL
+             ORDER BY notes.pinned DESC, notes.updated_at DESC",
+            NOTE_COLUMNS, placeholders
+        ))?;
+
+        let notes = stmt.query_map(params_from_iter(tag_ids), Self::note_from_row)?;
+
+        notes.collect()
+    }
+
+    pub fn filter_notes_by_tags(&self, tag_ids: Vec<String>, match_all: bool, include_descendants: bool) -> SqlResult<Vec<Note>> {
+        if tag_ids.is_empty() {
+            return self.list_notes(None, false);
+        }
+
+        // Each requested tag becomes a group: the tag itself plus (optionally) its descendants
+        let groups = tag_ids
+            .iter()
+            .map(|id| self.tag_ids_with_descendants(id, include_descendants))
+            .collect::<SqlResult<Vec<_>>>()?;
+
+        let sql = if match_all {
+            // Every group must match at least one of the note's tags
+            let clauses: Vec<String> = groups
+                .iter()
+                .map(|group| format!(
+                    "notes.id IN (SELECT note_id FROM note_tags WHERE tag_id IN ({}))",
+                    vec!["?"; group.len()].join(",")
+                ))
+                .collect();
+            format!(
+                "SELECT {} FROM notes
+                 WHERE notes.deleted_at IS NULL AND notes.archived_at IS NULL
+                 AND {}
+                 ORDER BY notes.pinned DESC, notes.updated_at DESC",
+                NOTE_COLUMNS, clauses.join(" AND ")
+            )
+        } else {
+            let count: usize = groups.iter().map(|group| group.len()).sum();
+            format!(
+                "SELECT DISTINCT {} FROM notes
+                 JOIN note_tags ON notes.id = note_tags.note_id
+                 WHERE notes.deleted_at IS NULL AND notes.archived_at IS NULL
+                 AND note_tags.tag_id IN ({})
+                 ORDER BY notes.pinned DESC, notes.updated_at DESC",
+                NOTE_COLUMNS, vec!["?"; count].join(",")
+            )
+        };
+
+        let params: Vec<Box<dyn rusqlite::ToSql>> = groups
+            .into_iter()
+            .flatten()
+            .map(|id| Box::new(id) as Box<dyn rusqlite::ToSql>)
+            .collect();
+
+        let mut stmt = self.conn.prepare(&sql)?;
+        let notes_iter = stmt.query_map(params_from_iter(params), Self::note_from_row)?;
+
+        notes_iter.collect()
+    }
+
+    pub fn update_note_tags(&self, note_id: &str, content: &str) -> SqlResult<()> {
+        // Support hierarchical tags with / (e.g., #research/statistics/mediation)
+        let tags: std::collections::HashSet<String> = markdown::tag_spans(content)
+            .into_iter()
+            .map(|(start, end)| content[start..end].to_string())
+            .collect();
+
+        // Get current tags (id, name)
+        let current_tags: Vec<(String, String)> = self.conn
+            .prepare(
+                "SELECT tags.id, tags.name FROM tags
+                 JOIN note_tags ON tags.id = note_tags.tag_id
+                 WHERE note_tags.note_id = ?",
+            )?
+            .query_map([note_id], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<_, _>>()?;
+        let current_tag_names: std::collections::HashSet<String> = current_tags.iter().map(|(_, name)| name.to_lowercase()).collect();
+
+        // Add new tags
+        for tag_name in &tags {
+            if !current_tag_names.contains(&tag_name.to_lowercase()) {
+                self.add_tag_to_note(note_id, tag_name)?;
+            }
+        }
+
+        // Remove tags no longer in content
+        for (tag_id, name) in current_tags {
+            if !tags.iter().any(|t| t.to_lowercase() == name.to_lowercase()) {
+                self.remove_tag_from_note(note_id, &tag_id)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    // Link operations
+
+    pub fn update_note_links(&self, note_id: &str, content: &str) -> SqlResult<()> {
+        let links = markdown::parse_wiki_links(content);
+
+        // Delete existing links
+        self.conn.execute("DELETE FROM links WHERE source_note_id = ?", [note_id])?;
+
+        // Add new links (unresolved ones are kept with a NULL target)
+        for link in links {
+            let target_id = self.resolve_link_target(note_id, &link.target)?;
+
+            self.conn.execute(
+                "INSERT OR IGNORE INTO links (source_note_id, target_note_id, target_title, kind, anchor, alias)
+                 VALUES (?, ?, ?, ?, ?, ?)",
+                rusqlite::params![note_id, target_id, link.target, link.kind, link.anchor, link.alias],
+            )?;
+        }
+
+        Ok(())
+    }
+
+    // Task operations
+
+    pub fn update_note_tasks(&self, note_id: &str, content: &str) -> SqlResult<()> {
+        self.conn.execute("DELETE FROM tasks WHERE note_id = ?", [note_id])?;
+
+        let mut stmt = self.conn.prepare_cached(
+            "INSERT INTO tasks (note_id, line, text, status, checked, due_date, priority, tags)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+        )?;
+        for item in markdown::task_items(content) {
+            let details = markdown::task_details(&item.text);
+            let tags = serde_json::to_string(&details.tags).unwrap_or_else(|_| "[]".to_string());
+            stmt.execute(rusqlite::params![
+                note_id,
+                item.line as i64,
+                details.text,
+                item.status.to_string(),
+                matches!(item.status, 'x' | 'X'),
+                details.due.map(|d| d.to_string()),
+                details.priority,
+                tags,
+            ])?;
+        }
+
+        Ok(())
+    }
+
+    fn task_from_row(row: &rusqlite::Row) -> rusqlite::Result<Task> {
+        let tags: String = row.get(9)?;
+        Ok(Task {
+            note_id: row.get(0)?,
+            note_title: row.get(1)?,
+            project_id: row.get(2)?,
+            line: row.get(3)?,
+            text: row.get(4)?,
+            status: row.get(5)?,
+            checked: row.get(6)?,
+            due_date: row.get(7)?,
+            priority: row.get(8)?,
+            tags: serde_json::from_str(&tags).unwrap_or_default(),
+        })
+    }
+
+    /// Tasks in live, unarchived notes, soonest due first (undated last), then by priority
+    pub fn query_tasks(&self, query: &TaskQuery) -> SqlResult<Vec<Task>> {
+        let mut conditions = vec!["notes.deleted_at IS NULL", "notes.archived_at IS NULL"];
+        let mut params: Vec<String> = Vec::new();
+
+        if !query.include_done {
+            conditions.push("tasks.status = ' '");
+        }
+        if let Some(from) = &query.due_from {
+            conditions.push("tasks.due_date >= ?");
+            params.push(from.clone());
+        }
+        if let Some(to) = &query.due_to {
+            conditions.push("tasks.due_date <= ?");
+            params.push(to.clone());
+        }
+        if let Some(project_id) = &query.project_id {
+            conditions.push("notes.project_id = ?");
+            params.push(project_id.clone());
+        }
+        if let Some(note_id) = &query.note_id {
+            conditions.push("tasks.note_id = ?");
+            params.push(note_id.clone());
+        }
+
+        let sql = format!(
+            "SELECT tasks.note_id, notes.title, notes.project_id, tasks.line, tasks.text,
+                    tasks.status, tasks.checked, tasks.due_date, tasks.priority, tasks.tags
+             FROM tasks
+             JOIN notes ON notes.id = tasks.note_id
+             WHERE {}
+             ORDER BY tasks.due_date IS NULL, tasks.due_date,
+                      CASE tasks.priority
+                          WHEN 'highest' THEN 0 WHEN 'high' THEN 1 WHEN 'medium' THEN 2
+                          WHEN 'low' THEN 4 WHEN 'lowest' THEN 5 ELSE 3
+                      END,
+                      notes.title, tasks.line",
+            conditions.join(" AND ")
+        );
+
+        let mut stmt = self.conn.prepare(&sql)?;
+        let tasks = stmt.query_map(params_from_iter(params), Self::task_from_row)?;
+
+        // The tag filter (the tag or its children) runs here, since SQLite's `lower()` only folds ASCII
+        let tag = query.tag.as_deref().map(|t| t.trim_start_matches('#'));
+        let mut matching = Vec::new();
+        for task in tasks {
+            let task = task?;
+            if tag.is_some_and(|tag| !task.tags.iter().any(|name| markdown::strip_tag_prefix(name, tag).is_some())) {
+                continue;
+            }
+            matching.push(task);
+        }
+        Ok(matching)
+    }
+
+    /// Check or uncheck the task on `line` of a note by rewriting that line.
+    /// Open tasks become `[x]`; anything else (done, migrated) is reopened.
+    pub fn toggle_task(&self, note_id: &str, line: i64) -> SqlResult<Option<Task>> {
+        let note = match self.get_note(note_id)? {
+            Some(n) => n,
+            None => return Ok(None),
+        };
+
+        let items = markdown::task_items(&note.content);
+        let item = match items.iter().find(|t| t.line as i64 == line) {
+            Some(t) => t,
+            None => return Ok(None),
+        };
+
+        let status = if item.status == markdown::TASK_OPEN { 'x' } else { markdown::TASK_OPEN };
+        let content = markdown::set_task_status(&note.content, &[item], status);
+        self.update_note_as(note_id, None, Some(&content), None, NoteEdit::Generated)?;
+
+        let result = self.conn.query_row(
+            "SELECT tasks.note_id, notes.title, notes.project_id, tasks.line, tasks.text,
+                    tasks.status, tasks.checked, tasks.due_date, tasks.priority, tasks.tags
+             FROM tasks
+             JOIN notes ON notes.id = tasks.note_id
+             WHERE tasks.note_id = ? AND tasks.line = ?",
+            rusqlite::params![note_id, line],
+            Self::task_from_row,
+        );
+
+        match result {
+            Ok(task) => Ok(Some(task)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Outgoing link rows for a note, including kind, anchor and alias
+    pub fn get_note_links(&self, note_id: &str) -> SqlResult<Vec<Link>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT source_note_id, target_note_id, target_title, kind, anchor, alias
+             FROM links WHERE source_note_id = ?
+             ORDER BY id",
+        )?;
+
+        let links = stmt.query_map([note_id], |row| {
+            Ok(Link {
+                source_note_id: row.get(0)?,
+                target_note_id: row.get(1)?,
+                target_title: row.get(2)?,
+                kind: row.get(3)?,
+                anchor: row.get(4)?,
+                alias: row.get(5)?,
+            })
+        })?;
+
+        links.collect()
+    }
+
+    /// Resolve what a link points at: the heading section or block for an anchor,
+    /// or the whole note body when there is none. Used for hover previews and embeds.
+    pub fn get_link_preview(&self, note_id: &str, anchor: Option<&str>) -> SqlResult<Option<LinkPreview>> {
+        let note = match self.get_note(note_id)? {
+            Some(n) => n,
+            None => return Ok(None),
+        };
+
+        let content = match anchor {
+            Some(a) => match markdown::extract_section(&note.content, a) {
+                Some(section) => section,
+                None => return Ok(None),
+            },
+            None => note.content,
+        };
+
+        Ok(Some(LinkPreview {
+            note_id: note.id,
+            title: note.title,
+            anchor: anchor.map(|a| a.to_string()),
+            content,
+        }))
+    }
+
+    /// Notes a link's target text could refer to, oldest first.
+    ///
+    /// `[[Meeting]]` matches every note titled "Meeting". When no note carries the
+    /// full text as its title, a path-qualified link like `[[projects/Thesis/Meeting]]`
+    /// matches "Meeting" notes whose folder is (or ends with) `projects/Thesis`, or
+    /// whose project is named `Thesis`.
+    pub fn find_link_candidates(&self, target: &str) -> SqlResult<Vec<LinkCandidate>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT notes.id, notes.title, notes.folder, notes.project_id, projects.name
+             FROM notes
+             LEFT JOIN projects ON projects.id = notes.project_id
+             WHERE notes.title = ? AND notes.deleted_at IS NULL
+             ORDER BY notes.created_at, notes.rowid",
+        )?;
+        let mut query = |title: &str| -> SqlResult<Vec<LinkCandidate>> {
+            stmt.query_map([title], |row| {
+                Ok(LinkCandidate {
+                    id: row.get(0)?,
+                    title: row.get(1)?,
+                    folder: row.get(2)?,
+                    project_id: row.get(3)?,
+                    project_name: row.get(4)?,
+                })
+            })?.collect()
+        };
+
+        let exact = query(target)?;
+        if !exact.is_empty() {
+            return Ok(exact);
+        }
+
+        let (qualifier, title) = match target.rsplit_once('/') {
+            Some((q, t)) if !q.trim().is_empty() && !t.trim().is_empty() => (q.trim().trim_matches('/'), t.trim()),
+            _ => return Ok(Vec::new()),
+        };
+        let qualifier = qualifier.to_lowercase();
+
+        Ok(query(title)?
+            .into_iter()
+            .filter(|c| {
+                let folder = c.folder.to_lowercase();
+                let project = c.project_name.as_deref().map(|p| p.to_lowercase());
+                folder == qualifier
+                    || folder.ends_with(&format!("/{}", qualifier))
+                    || project.as_deref() == Some(qualifier.as_str())
+                    || project.map(|p| format!("projects/{}", p)).as_deref() == Some(qualifier.as_str())
+            })
+            .collect())
+    }
+
+    /// Pick the note a link from `source_note_id` should point at.
+    /// Among several candidates, prefer the source's project, then its folder,
+    /// then the oldest note.
+    fn resolve_link_target(&self, source_note_id: &str, target: &str) -> SqlResult<Option<String>> {
+        let candidates = self.find_link_candidates(target)?;
+        if candidates.len() <= 1 {
+            return Ok(candidates.into_iter().next().map(|c| c.id));
+        }
+
+        let source: Option<(String, Option<String>)> = match self.conn.query_row(
+            "SELECT folder, project_id FROM notes WHERE id = ?",
+            [source_note_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        ) {
+            Ok(s) => Some(s),
+            Err(rusqlite::Error::QueryReturnedNoRows) => None,
+            Err(e) => return Err(e),
+        };
+
+        let preferred = source.and_then(|(folder, project_id)| {
+            candidates.iter()
+                .find(|c| project_id.is_some() && c.project_id == project_id)
+                .or_else(|| candidates.iter().find(|c| c.folder == folder))
+                .map(|c| c.id.clone())
+        });
+
+        Ok(preferred.or_else(|| candidates.into_iter().next().map(|c| c.id)))
+    }
+
+    /// Point unresolved links at the note that now carries `title`, whether they
+    /// were written as `[[title]]` or path-qualified as `[[folder/title]]`.
+    /// Returns the number of links that were resolved.
+    pub fn resolve_pending_links(&self, title: &str) -> SqlResult<usize> {
+        let pending: Vec<(i64, String, String)> = self.conn
+            .prepare(
+                "SELECT id, source_note_id, target_title FROM links
+                 WHERE target_note_id IS NULL
+                   AND (target_title = ?1 OR substr(target_title, -(length(?1) + 1)) = '/' || ?1)",
+            )?
+            .query_map([title], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut resolved = 0;
+        for (link_id, source_note_id, target_title) in pending {
+            if let Some(target_id) = self.resolve_link_target(&source_note_id, &target_title)? {
+                resolved += self.conn.execute(
+                    "UPDATE links SET target_note_id = ? WHERE id = ?",
+                    rusqlite::params![target_id, link_id],
+                )?;
+            }
+        }
+
+        Ok(resolved)
+    }
+
+    /// After a note is retitled, links written as `[[old title]]` no longer match it,
+    /// while links written as `[[new title]]` may now resolve to it.
+    fn relink_after_title_change(&self, note_id: &str, old_title: &str, new_title: &str) -> SqlResult<()> {
+        self.conn.execute(
+            "UPDATE links SET target_note_id = NULL
+             WHERE target_note_id = ?1
+               AND (target_title = ?2 OR substr(target_title, -(length(?2) + 1)) = '/' || ?2)",
+            [note_id, old_title],
+        )?;
+
+        // Another note may still carry the old title
+        self.resolve_pending_links(old_title)?;
+        self.resolve_pending_links(new_title)?;
+        Ok(())
+    }
+
+    /// Links whose target text matches more than one note, so the chosen target
+    /// depends on project/folder preference rather than the text alone
+    pub fn get_ambiguous_links(&self) -> SqlResult<Vec<AmbiguousLink>> {
+        let rows: Vec<(String, String, String, Option<String>)> = self.conn
+            .prepare(
+                "SELECT links.source_note_id, notes.title, links.target_title, links.target_note_id
+                 FROM links
+                 JOIN notes ON notes.id = links.source_note_id
+                 WHERE notes.deleted_at IS NULL
+                 ORDER BY links.target_title COLLATE NOCASE, notes.title COLLATE NOCASE",
+            )?
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)))?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut candidates_by_title: HashMap<String, Vec<LinkCandidate>> = HashMap::new();
+        let mut ambiguous: Vec<AmbiguousLink> = Vec::new();
+
+        for (source_note_id, source_title, target_title, target_note_id) in rows {
+            if !candidates_by_title.contains_key(&target_title) {
+                let candidates = self.find_link_candidates(&target_title)?;
+                candidates_by_title.insert(target_title.clone(), candidates);
+            }
+            let candidates = &candidates_by_title[&target_title];
+            if candidates.len() < 2 {
+                continue;
+            }
+
+            // One source may link the same title with several anchors; report it once
+            if ambiguous.iter().any(|a| a.source_note_id == source_note_id && a.target_title == target_title) {
+                continue;
+            }
+
+            ambiguous.push(AmbiguousLink {
+                source_note_id,
+                source_title,
+                target_title,
+                target_note_id,
+                candidates: candidates.clone(),
+            });
+        }
+
+        Ok(ambiguous)
+    }
+
+    /// Rename a note and rewrite every `[[Old Title]]` reference to it, keeping
+    /// aliases (`|alias`), heading anchors (`#Heading`) and block refs (`^id`).
+    /// With `dry_run` nothing is written and the result previews the changes.
+    /// Fails if another note in the same project already has `new_title`.
+    pub fn rename_note(&self, id: &str, new_title: &str, dry_run: bool) -> SqlResult<Option<RenameNoteResult>> {
+        let note = match self.get_note(id)? {
+            Some(n) => n,
+            None => return Ok(None),
+        };
+
+        let old_title = note.title;
+        let mut result = RenameNoteResult {
+            note_id: id.to_string(),
+            old_title: old_title.clone(),
+            new_title: new_title.to_string(),
+            dry_run,
+            modified_notes: Vec::new(),
+        };
+
+        if old_title == new_title {
+            return Ok(Some(result));
+        }
+
+        // Links resolve to the same project first, so a second note with the new
+        // title there would make every `[[new title]]` ambiguous
+        let taken: bool = self.conn.query_row(
+            "SELECT COUNT(*) > 0 FROM notes
+             WHERE id != ? AND title = ? COLLATE NOCASE AND project_id IS ? AND deleted_at IS NULL",
+            rusqlite::params![id, new_title, note.project_id],
+            |row| row.get(0),
+        )?;
+        if taken {
+            return Err(invalid_input(format!("A note titled {} already exists", new_title)));
+        }
+
+        // Where the title starts in a link target that names this note, after any `folder/` qualifier
+        let old_lower = old_title.to_lowercase();
+        let title_start = |target: &str| -> Option<usize> {
+            let start = target.len().checked_sub(old_title.len())?;
+            let title = target.get(start..)?;
+            (title.to_lowercase() == old_lower && (start == 0 || target[..start].ends_with('/'))).then_some(start)
+        };
+
+        // Only links that resolve to this note are rewritten; with duplicate titles,
+        // `[[old title]]` elsewhere may point at another note
+        let mut rewrites: Vec<(String, String)> = Vec::new();
+        {
+            let mut stmt = self.conn.prepare(
+                "SELECT notes.id, notes.title, notes.content, group_concat(links.target_title, char(10))
+                 FROM notes
+                 JOIN links ON links.source_note_id = notes.id
+                 WHERE links.target_note_id = ? AND notes.deleted_at IS NULL
+                 GROUP BY notes.id
+                 ORDER BY notes.title COLLATE NOCASE",
+            )?;
+            let rows = stmt.query_map([id], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?, row.get::<_, String>(3)?))
+            })?.collect::<Result<Vec<_>, _>>()?;
+
+            for (note_id, title, content, targets) in rows {
+                let targets: HashSet<&str> = targets.split('\n').collect();
+
+                // Parsed links skip code, so only what the link index sees is rewritten
+                let mut new_content = String::with_capacity(content.len());
+                let mut last = 0;
+                let mut occurrences = 0;
+                for link in markdown::parse_wiki_links(&content) {
+                    if !targets.contains(link.target.as_str()) {
+                        continue;
+                    }
+                    let Some(offset) = title_start(&link.target) else { continue };
+                    let target = markdown::link_target_range(&content, &link);
+                    new_content.push_str(&content[last..target.start + offset]);
+                    new_content.push_str(new_title);
+                    last = target.end;
+                    occurrences += 1;
+                }
+                if occurrences == 0 {
+                    continue;
+                }
+                new_content.push_str(&content[last..]);
+
+                result.modified_notes.push(RenamedLinkChange { note_id: note_id.clone(), title, occurrences });
+                rewrites.push((note_id, new_content));
+            }
+        }
+
+        if dry_run {
+            return Ok(Some(result));
+        }
 
-            CREATE TRIGGER notes_ai AFTER INSERT ON notes BEGIN
-                INSERT INTO notes_fts(note_id, title, content)
-                VALUES (new.id, new.title, new.content);
-            END;
+        let tx = self.conn.unchecked_transaction()?;
 
-            CREATE TRIGGER notes_ad AFTER DELETE ON notes BEGIN
-                DELETE FROM notes_fts WHERE note_id = old.id;
-            END;
+        self.conn.execute(
+            "UPDATE notes SET title = ?, updated_at = strftime('%s', 'now') WHERE id = ?",
+            [new_title, id],
+        )?;
 
-            CREATE TRIGGER notes_au AFTER UPDATE ON notes BEGIN
-                DELETE FROM notes_fts WHERE note_id = old.id;
-                INSERT INTO notes_fts(note_id, title, content)
-                VALUES (new.id, new.title, new.content);
-            END;
+        for (note_id, new_content) in &rewrites {
+            self.conn.execute(
+                "UPDATE notes SET content = ?, word_count = ?, updated_at = strftime('%s', 'now') WHERE id = ?",
+                rusqlite::params![new_content, markdown::word_count(new_content) as i64, note_id],
+            )?;
+            self.update_note_links(note_id, new_content)?;
+            self.update_note_tasks(note_id, new_content)?;
+        }
 
-            CREATE INDEX idx_notes_folder ON notes(folder);
-            CREATE INDEX idx_notes_updated ON notes(updated_at DESC);
-            CREATE INDEX idx_notes_deleted ON notes(deleted_at);
+        self.relink_after_title_change(id, &old_title, new_title)?;
 
-            CREATE TABLE tags (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                note_id TEXT NOT NULL,
-                tag TEXT NOT NULL,
-                FOREIGN KEY (note_id) REFERENCES notes(id) ON DELETE CASCADE,
-                UNIQUE(note_id, tag)
-            );
+        tx.commit()?;
+        Ok(Some(result))
+    }
 
-            CREATE INDEX idx_tags_note ON tags(note_id);
-            CREATE INDEX idx_tags_tag ON tags(tag);
+    pub fn get_unresolved_links(&self, note_id: Option<&str>) -> SqlResult<Vec<UnresolvedLink>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT links.source_note_id, notes.title, links.target_title, links.created_at
+             FROM links
+             JOIN notes ON notes.id = links.source_note_id
+             WHERE links.target_note_id IS NULL AND notes.deleted_at IS NULL
+               AND (?1 IS NULL OR links.source_note_id = ?1)
+             ORDER BY links.target_title COLLATE NOCASE, notes.title COLLATE NOCASE",
+        )?;
 
-            CREATE TABLE folders (
-                path TEXT PRIMARY KEY,
-                color TEXT,
-                icon TEXT,
-                sort_order INTEGER DEFAULT 0
-            );
+        let links = stmt.query_map([note_id], |row| {
+            Ok(UnresolvedLink {
+                source_note_id: row.get(0)?,
+                source_title: row.get(1)?,
+                target_title: row.get(2)?,
+                created_at: row.get(3)?,
+            })
+        })?;
 
-            INSERT INTO folders (path, sort_order) VALUES
-                ('inbox', 1),
-                ('projects', 2),
-                ('areas', 3),
-                ('resources', 4),
-                ('archive', 5);",
+        links.collect()
+    }
+
+    pub fn get_backlinks(&self, note_id: &str) -> SqlResult<Vec<Note>> {
+        let mut stmt = self.conn.prepare(
+            &format!(
+                "SELECT DISTINCT {} FROM notes
+                 JOIN links ON notes.id = links.source_note_id
+                 WHERE links.target_note_id = ? AND notes.deleted_at IS NULL
+                 ORDER BY notes.updated_at DESC",
+                NOTE_COLUMNS
+            ),
         )?;
-        
-        Ok(())
+
+        let notes = stmt.query_map([note_id], Self::note_from_row)?;
+
+        notes.collect()
     }
-    
-    fn run_migration_002(&self) -> SqlResult<()> {
-        println!("✅ Database updated (schema version 2)");
-        
-        self.conn.execute_batch(
-            "CREATE TABLE IF NOT EXISTS links (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                source_note_id TEXT NOT NULL,
-                target_note_id TEXT NOT NULL,
-                created_at INTEGER DEFAULT (strftime('%s', 'now')),
-                FOREIGN KEY (source_note_id) REFERENCES notes(id) ON DELETE CASCADE,
-                FOREIGN KEY (target_note_id) REFERENCES notes(id) ON DELETE CASCADE,
-                UNIQUE(source_note_id, target_note_id)
-            );
 
-            CREATE INDEX IF NOT EXISTS idx_links_source ON links(source_note_id);
-            CREATE INDEX IF NOT EXISTS idx_links_target ON links(target_note_id);",
+    /// Backlinks with the paragraph (or list item) around each `[[link]]`
+    pub fn get_backlinks_with_context(&self, note_id: &str) -> SqlResult<Vec<BacklinkContext>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT notes.id, notes.title, notes.folder, notes.updated_at, notes.content, links.target_title
+             FROM links
+             JOIN notes ON notes.id = links.source_note_id
+             WHERE links.target_note_id = ?1 AND notes.id != ?1 AND notes.deleted_at IS NULL
+             ORDER BY notes.updated_at DESC, notes.id",
         )?;
-        
-        Ok(())
+
+        let rows = stmt.query_map([note_id], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, i64>(3)?,
+                row.get::<_, String>(4)?,
+                row.get::<_, String>(5)?,
+            ))
+        })?;
+
+        // Group the link rows by source note, remembering which target texts resolve here
+        let mut sources: Vec<(BacklinkContext, String, HashSet<String>)> = Vec::new();
+        for row in rows {
+            let (id, title, folder, updated_at, content, target_title) = row?;
+            match sources.iter_mut().find(|(source, _, _)| source.note_id == id) {
+                Some((_, _, targets)) => {
+                    targets.insert(target_title);
+                }
+                None => {
+                    let source = BacklinkContext { note_id: id, title, folder, updated_at, mentions: Vec::new() };
+                    sources.push((source, content, HashSet::from([target_title])));
+                }
+            }
+        }
+
+        Ok(sources
+            .into_iter()
+            .map(|(mut source, content, targets)| {
+                source.mentions = markdown::parse_wiki_links(&content)
+                    .into_iter()
+                    .filter(|link| targets.contains(&link.target))
+                    .map(|link| {
+                        let mut context = Self::link_context(&content, link.start, link.end, link.kind);
+                        context.anchor = link.anchor;
+                        context.alias = link.alias;
+                        context
+                    })
+                    .collect();
+                source
+            })
+            .collect())
     }
-    
-    fn run_migration_003(&self) -> SqlResult<()> {
-        println!("✅ Database updated (schema version 3)");
-        
-        self.conn.execute_batch(
-            "DROP TABLE IF EXISTS tags;
 
-            CREATE TABLE IF NOT EXISTS tags (
-                id TEXT PRIMARY KEY DEFAULT (lower(hex(randomblob(16)))),
-                name TEXT NOT NULL UNIQUE COLLATE NOCASE,
-                color TEXT,
-                created_at INTEGER DEFAULT (strftime('%s', 'now')),
-                UNIQUE(name COLLATE NOCASE)
-            );
+    /// Notes that mention this note's title as plain text, without linking it.
+    /// Matching is left to `find_mentions`, since SQLite's `lower()` only folds ASCII.
+    pub fn get_unlinked_mentions(&self, note_id: &str) -> SqlResult<Vec<BacklinkContext>> {
+        let title: String = match self.conn.query_row(
+            "SELECT title FROM notes WHERE id = ? AND deleted_at IS NULL",
+            [note_id],
+            |row| row.get(0),
+        ) {
+            Ok(title) => title,
+            Err(rusqlite::Error::QueryReturnedNoRows) => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+        if title.trim().is_empty() {
+            return Ok(Vec::new());
+        }
 
-            CREATE INDEX IF NOT EXISTS idx_tags_name ON tags(name COLLATE NOCASE);
+        let mut stmt = self.conn.prepare(
+            "SELECT id, title, folder, updated_at, content
+             FROM notes
+             WHERE deleted_at IS NULL AND id != ?
+             ORDER BY updated_at DESC, id",
+        )?;
 
-            CREATE TABLE IF NOT EXISTS note_tags (
-                note_id TEXT NOT NULL,
-                tag_id TEXT NOT NULL,
-                created_at INTEGER DEFAULT (strftime('%s', 'now')),
-                PRIMARY KEY (note_id, tag_id),
-                FOREIGN KEY (note_id) REFERENCES notes(id) ON DELETE CASCADE,
-                FOREIGN KEY (tag_id) REFERENCES tags(id) ON DELETE CASCADE
-            );
+        let rows = stmt.query_map([note_id], |row| {
+            Ok((
+                BacklinkContext {
+                    note_id: row.get(0)?,
+                    title: row.get(1)?,
+                    folder: row.get(2)?,
+                    updated_at: row.get(3)?,
+                    mentions: Vec::new(),
+                },
+                row.get::<_, String>(4)?,
+            ))
+        })?;
 
-            CREATE INDEX IF NOT EXISTS idx_note_tags_note ON note_tags(note_id);
-            CREATE INDEX IF NOT EXISTS idx_note_tags_tag ON note_tags(tag_id);",
+        let mut results = Vec::new();
+        for row in rows {
+            let (mut source, content) = row?;
+            source.mentions = markdown::find_mentions(&content, &title)
+                .into_iter()
+                .map(|(start, end)| Self::link_context(&content, start, end, "mention"))
+                .collect();
+            if !source.mentions.is_empty() {
+                results.push(source);
+            }
+        }
+
+        Ok(results)
+    }
+
+    /// Context for a reference at byte range `start..end`, reported in character offsets
+    fn link_context(content: &str, start: usize, end: usize, kind: &str) -> LinkContext {
+        let chars = |byte: usize| content[..byte].chars().count();
+        let (context_start, context_end) = markdown::context_range(content, start);
+
+        LinkContext {
+            context: content[context_start..context_end].to_string(),
+            line: content[..start].matches('\n').count() + 1,
+            start: chars(start),
+            end: chars(end),
+            context_start: chars(context_start),
+            context_end: chars(context_end),
+            kind: kind.to_string(),
+            anchor: None,
+            alias: None,
+        }
+    }
+
+    pub fn get_outgoing_links(&self, note_id: &str) -> SqlResult<Vec<Note>> {
+        let mut stmt = self.conn.prepare(
+            &format!(
+                "SELECT DISTINCT {} FROM notes
+                 JOIN links ON notes.id = links.target_note_id
+                 WHERE links.source_note_id = ? AND notes.deleted_at IS NULL
+                 ORDER BY notes.updated_at DESC",
+                NOTE_COLUMNS
+            ),
         )?;
 
-        Ok(())
+        let notes = stmt.query_map([note_id], Self::note_from_row)?;
+
+        notes.collect()
     }
 
-    fn run_migration_004(&self) -> SqlResult<()> {
-        println!("Running database migration 004 (projects system)");
+    // Knowledge graph
 
-        self.conn.execute_batch(
-            "-- Create projects table
-            CREATE TABLE IF NOT EXISTS projects (
-                id TEXT PRIMARY KEY DEFAULT (lower(hex(randomblob(16)))),
-                name TEXT NOT NULL,
-                description TEXT,
-                type TEXT CHECK(type IN ('research', 'teaching', 'r-package', 'r-dev', 'generic')) DEFAULT 'generic',
-                color TEXT,
-                icon TEXT,
-                settings TEXT,
-                created_at INTEGER DEFAULT (strftime('%s', 'now')),
-                updated_at INTEGER DEFAULT (strftime('%s', 'now'))
-            );
+    /// Nodes and edges for the whole vault, a project, a tag or one note's
+    /// neighbourhood, with degree, PageRank centrality and a community id per node.
+    /// `depth` adds that many hops of linked notes around the scope
+    /// (default: 1 for a note, 0 otherwise).
+    pub fn get_graph(&self, scope: &GraphScope, depth: Option<u32>, filters: &GraphFilters) -> SqlResult<Graph> {
+        let in_folders = |folder: &str| match &filters.folders {
+            Some(folders) => folders.iter().any(|f| folder == f || folder.starts_with(&format!("{}/", f))),
+            None => true,
+        };
 
-            -- Add project_id column to notes table
-            ALTER TABLE notes ADD COLUMN project_id TEXT REFERENCES projects(id) ON DELETE SET NULL;
+        // Live notes, without content
+        let notes: Vec<(String, String, String, Option<String>)> = self.conn
+            .prepare(
+                "SELECT id, title, folder, project_id FROM notes
+                 WHERE deleted_at IS NULL
+                 ORDER BY title COLLATE NOCASE, id",
+            )?
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get::<_, String>(2)?, row.get(3)?)))?
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .filter(|(_, _, folder, _)| in_folders(folder))
+            .collect();
 
-            -- Create index for efficient project-based note lookups
-            CREATE INDEX IF NOT EXISTS idx_notes_project ON notes(project_id);
+        let index: HashMap<&str, usize> = notes.iter().enumerate().map(|(i, n)| (n.0.as_str(), i)).collect();
 
-            -- Create index for project name lookups
-            CREATE INDEX IF NOT EXISTS idx_projects_name ON projects(name);
-            CREATE INDEX IF NOT EXISTS idx_projects_type ON projects(type);",
-        )?;
+        // Resolved links between those notes, one edge per (source, target) pair
+        let include_embeds = filters.include_embeds.unwrap_or(true);
+        let mut edges: Vec<(usize, usize, usize, &'static str)> = Vec::new();
+        {
+            let mut stmt = self.conn.prepare(
+                "SELECT source_note_id, target_note_id, COUNT(*), SUM(kind = 'embed')
+                 FROM links
+                 WHERE target_note_id IS NOT NULL AND source_note_id != target_note_id
+                 GROUP BY source_note_id, target_note_id",
+            )?;
+            let rows = stmt.query_map([], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, i64>(2)?, row.get::<_, i64>(3)?))
+            })?;
 
-        println!("Database migration 004 completed (projects system)");
-        Ok(())
+            for row in rows {
+                let (source, target, count, embeds) = row?;
+                let (Some(&s), Some(&t)) = (index.get(source.as_str()), index.get(target.as_str())) else {
+                    continue;
+                };
+                let weight = if include_embeds { count } else { count - embeds };
+                if weight > 0 {
+                    let kind = if embeds == count { markdown::LINK_KIND_EMBED } else { markdown::LINK_KIND_LINK };
+                    edges.push((s, t, weight as usize, kind));
+                }
+            }
+        }
+
+        // Seed the selection from the scope
+        let mut selected = vec![false; notes.len()];
+        let mut centre: Option<usize> = None;
+        match scope {
+            GraphScope::Vault => selected.iter_mut().for_each(|s| *s = true),
+            GraphScope::Project { id } => {
+                for (i, note) in notes.iter().enumerate() {
+                    selected[i] = note.3.as_deref() == Some(id.as_str());
+                }
+            }
+            GraphScope::Tag { id } => {
+                let mut stmt = self.conn.prepare("SELECT note_id FROM note_tags WHERE tag_id = ?")?;
+                let tagged = stmt.query_map([id], |row| row.get::<_, String>(0))?;
+                for note_id in tagged {
+                    if let Some(&i) = index.get(note_id?.as_str()) {
+                        selected[i] = true;
+                    }
+                }
+            }
+            GraphScope::Note { id } => {
+                if let Some(&i) = index.get(id.as_str()) {
+                    selected[i] = true;
+                    centre = Some(i);
+                }
+            }
+        }
+
+        // Expand N hops, following links in either direction
+        let default_depth = if matches!(scope, GraphScope::Note { .. }) { 1 } else { 0 };
+        for _ in 0..depth.unwrap_or(default_depth) {
+            let frontier = selected.clone();
+            for &(s, t, _, _) in &edges {
+                if frontier[s] {
+                    selected[t] = true;
+                }
+                if frontier[t] {
+                    selected[s] = true;
+                }
+            }
+        }
+
+        let sub_edges: Vec<(usize, usize, usize, &str)> = edges
+            .into_iter()
+            .filter(|(s, t, _, _)| selected[*s] && selected[*t])
+            .collect();
+
+        if !filters.include_orphans.unwrap_or(true) {
+            let mut connected = vec![false; notes.len()];
+            for &(s, t, _, _) in &sub_edges {
+                connected[s] = true;
+                connected[t] = true;
+            }
+            for (i, keep) in selected.iter_mut().enumerate() {
+                *keep = *keep && (connected[i] || centre == Some(i));
+            }
+        }
+
+        // Renumber the selected nodes and compute metrics on the subgraph
+        let members: Vec<usize> = (0..notes.len()).filter(|&i| selected[i]).collect();
+        let position: HashMap<usize, usize> = members.iter().enumerate().map(|(p, &i)| (i, p)).collect();
+        let weighted: Vec<(usize, usize, f64)> = sub_edges
+            .iter()
+            .map(|&(s, t, w, _)| (position[&s], position[&t], w as f64))
+            .collect();
+
+        let rank = graph::pagerank(members.len(), &weighted);
+        let clusters = graph::communities(members.len(), &weighted);
+        let mut in_degree = vec![0; members.len()];
+        let mut out_degree = vec![0; members.len()];
+        for &(s, t, _) in &weighted {
+            out_degree[s] += 1;
+            in_degree[t] += 1;
+        }
+
+        let nodes = members
+            .iter()
+            .enumerate()
+            .map(|(p, &i)| {
+                let (id, title, folder, project_id) = notes[i].clone();
+                GraphNode {
+                    id,
+                    title,
+                    folder,
+                    project_id,
+                    in_degree: in_degree[p],
+                    out_degree: out_degree[p],
+                    degree: in_degree[p] + out_degree[p],
+                    centrality: rank[p],
+                    community: clusters[p],
+                }
+            })
+            .collect();
+
+        let edges = sub_edges
+            .into_iter()
+            .map(|(s, t, weight, kind)| GraphEdge {
+                source: notes[s].0.clone(),
+                target: notes[t].0.clone(),
+                weight,
+                kind: kind.to_string(),
+            })
+            .collect();
+
+        Ok(Graph { nodes, edges })
     }
 
-    fn run_migration_005(&self) -> SqlResult<()> {
-        println!("Running database migration 005 (note properties)");
+    // Vault health
 
-        // Add properties column to notes table
-        self.conn.execute(
-            "ALTER TABLE notes ADD COLUMN properties TEXT",
-            [],
+    /// Build the vault health report. Link counts only include resolved links
+    /// between live notes, ignoring self links.
+    pub fn get_vault_health(&self, stale_days: Option<u32>, hub_min_links: Option<u32>) -> SqlResult<VaultHealthReport> {
+        let stale_before = chrono::Utc::now().timestamp() - i64::from(stale_days.unwrap_or(14)) * 86_400;
+        let hub_min_links = hub_min_links.unwrap_or(5) as usize;
+
+        let mut stmt = self.conn.prepare(
+            "SELECT n.id, n.title, n.folder, n.project_id, n.created_at, n.updated_at,
+                    (SELECT COUNT(DISTINCT l.source_note_id) FROM links l
+                       JOIN notes s ON s.id = l.source_note_id AND s.deleted_at IS NULL
+                      WHERE l.target_note_id = n.id AND l.source_note_id != n.id),
+                    (SELECT COUNT(DISTINCT l.target_note_id) FROM links l
+                       JOIN notes t ON t.id = l.target_note_id AND t.deleted_at IS NULL
+                      WHERE l.source_note_id = n.id AND l.target_note_id != n.id),
+                    EXISTS (SELECT 1 FROM note_tags nt WHERE nt.note_id = n.id),
+                    trim(n.content, ' ' || char(9, 10, 13)) = ''
+             FROM notes n
+             WHERE n.deleted_at IS NULL
+             ORDER BY n.updated_at DESC, n.id",
         )?;
 
-        println!("Database migration 005 completed (note properties)");
-        Ok(())
-    }
+        let rows = stmt.query_map([], |row| {
+            let note = HealthNote {
+                id: row.get(0)?,
+                title: row.get(1)?,
+                folder: row.get(2)?,
+                project_id: row.get(3)?,
+                created_at: row.get(4)?,
+                updated_at: row.get(5)?,
+                inbound_links: row.get::<_, i64>(6)? as usize,
+                outbound_links: row.get::<_, i64>(7)? as usize,
+            };
+            Ok((note, row.get::<_, bool>(8)?, row.get::<_, bool>(9)?))
+        })?;
 
-    fn run_migration_006(&self) -> SqlResult<()> {
-        println!("Running database migration 006 (project settings table)");
+        let mut report = VaultHealthReport {
+            orphans: Vec::new(),
+            dead_ends: Vec::new(),
+            hubs: Vec::new(),
+            unfiled: Vec::new(),
+            stale_inbox: Vec::new(),
+            empty: Vec::new(),
+            duplicate_titles: Vec::new(),
+        };
+        let mut by_title: HashMap<String, Vec<HealthNote>> = HashMap::new();
 
-        self.conn.execute_batch(
-            "CREATE TABLE IF NOT EXISTS project_settings (
-                project_id TEXT PRIMARY KEY REFERENCES projects(id) ON DELETE CASCADE,
-                settings TEXT NOT NULL DEFAULT '{}'
-            );",
+        for row in rows {
+            let (note, tagged, empty) = row?;
+
+            match (note.inbound_links, note.outbound_links) {
+                (0, 0) => report.orphans.push(note.clone()),
+                (_, 0) => report.dead_ends.push(note.clone()),
+                _ => {}
+            }
+            if note.inbound_links + note.outbound_links >= hub_min_links {
+                report.hubs.push(note.clone());
+            }
+            if !tagged && note.project_id.is_none() {
+                report.unfiled.push(note.clone());
+            }
+            if note.folder == "inbox" && note.created_at < stale_before {
+                report.stale_inbox.push(note.clone());
+            }
+            if empty {
+                report.empty.push(note.clone());
+            }
+            by_title.entry(note.title.trim().to_lowercase()).or_default().push(note);
+        }
+
+        report.hubs.sort_by(|a, b| {
+            (b.inbound_links + b.outbound_links).cmp(&(a.inbound_links + a.outbound_links))
+        });
+        report.stale_inbox.sort_by_key(|note| note.created_at);
+
+        report.duplicate_titles = by_title
+            .into_values()
+            .filter(|notes| notes.len() > 1)
+            .map(|mut notes| {
+                notes.sort_by_key(|note| note.created_at);
+                DuplicateTitle { title: notes[0].title.clone(), notes }
+            })
+            .collect();
+        report.duplicate_titles.sort_by_key(|dup| dup.title.to_lowercase());
+
+        Ok(report)
+    }
+
+    pub fn get_note_by_title_and_folder(&self, title: &str, folder: &str) -> SqlResult<Option<Note>> {
+        let mut stmt = self.conn.prepare(
+            &format!(
+                "SELECT {} FROM notes WHERE title = ? AND folder = ? AND deleted_at IS NULL LIMIT 1",
+                NOTE_COLUMNS
+            ),
         )?;
+        let note = stmt.query_row([title, folder], Self::note_from_row);
 
-        println!("Database migration 006 completed (project settings table)");
-        Ok(())
+        match note {
+            Ok(n) => Ok(Some(n)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+
+    fn generate_tag_color(&self, name: &str) -> String {
+        TAG_PALETTE[Self::tag_color_hash(name) as usize % TAG_PALETTE.len()].to_string()
+    }
+
+    fn tag_color_hash(name: &str) -> u32 {
+        name.chars().fold(0u32, |acc, c| {
+            (c as u32).wrapping_add((acc << 5).wrapping_sub(acc))
+        })
     }
 
-    /// Migration 007: Seed demo data for new users
-    ///
-    /// Creates a "Getting Started" project with example notes to help
-    /// users understand how Scribe works. Only runs if no projects exist.
-    fn run_migration_007_seed_demo_data(&self) -> SqlResult<()> {
-        println!("Running database migration 007 (demo data seeding)");
-
-        // Check if any projects already exist (don't seed if user has data)
-        let project_count: i64 = self.conn.query_row(
-            "SELECT COUNT(*) FROM projects",
-            [],
-            |row| row.get(0),
-        )?;
+    // Template operations
+
+    fn template_from_row(row: &rusqlite::Row) -> SqlResult<Template> {
+        let tags: String = row.get(5)?;
+        Ok(Template {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            description: row.get(2)?,
+            content: row.get(3)?,
+            properties: row.get(4)?,
+            tags: serde_json::from_str(&tags).unwrap_or_default(),
+            created_at: row.get(6)?,
+            updated_at: row.get(7)?,
+        })
+    }
 
-        if project_count > 0 {
-            println!("  Skipping demo data - projects already exist");
-            return Ok(());
+    /// Normalise template tags (`#` dropped) and reject invalid names
+    fn template_tags_json(tags: &[String]) -> SqlResult<String> {
+        let mut names: Vec<&str> = Vec::new();
+        for tag in tags {
+            let name = tag.trim().trim_start_matches('#');
+            if !markdown::is_valid_tag_name(name) {
+                return Err(invalid_input(format!("Invalid tag name: {}", tag)));
+            }
+            if !names.iter().any(|n| n.eq_ignore_ascii_case(name)) {
+                names.push(name);
+            }
         }
+        serde_json::to_string(&names).map_err(|e| invalid_input(e.to_string()))
+    }
 
-        println!("  Seeding demo data for new user...");
+    pub fn list_templates(&self) -> SqlResult<Vec<Template>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM templates ORDER BY name COLLATE NOCASE",
+            TEMPLATE_COLUMNS
+        ))?;
+        let templates = stmt.query_map([], Self::template_from_row)?;
+        templates.collect()
+    }
 
-        // Generate UUIDs for our entities
-        let project_id: String = self.conn.query_row(
-            "SELECT lower(hex(randomblob(16)))",
-            [],
-            |row| row.get(0),
-        )?;
+    pub fn get_template(&self, id: &str) -> SqlResult<Option<Template>> {
+        let result = self.conn.query_row(
+            &format!("SELECT {} FROM templates WHERE id = ?", TEMPLATE_COLUMNS),
+            [id],
+            Self::template_from_row,
+        );
 
-        let note1_id: String = self.conn.query_row(
-            "SELECT lower(hex(randomblob(16)))",
-            [],
-            |row| row.get(0),
-        )?;
+        match result {
+            Ok(template) => Ok(Some(template)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
 
-        let note2_id: String = self.conn.query_row(
-            "SELECT lower(hex(randomblob(16)))",
-            [],
-            |row| row.get(0),
+    pub fn create_template(
+        &self,
+        name: &str,
+        content: &str,
+        description: Option<&str>,
+        properties: Option<&str>,
+        tags: &[String],
+    ) -> SqlResult<Template> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(invalid_input("Template name cannot be empty"));
+        }
+        let properties = properties.filter(|p| !p.trim().is_empty());
+        if let Some(props) = properties {
+            Self::validate_properties(props).map_err(invalid_input)?;
+        }
+
+        self.conn.execute(
+            "INSERT INTO templates (name, description, content, properties, tags) VALUES (?, ?, ?, ?, ?)",
+            rusqlite::params![name, description, content, properties, Self::template_tags_json(tags)?],
         )?;
 
-        let note3_id: String = self.conn.query_row(
-            "SELECT lower(hex(randomblob(16)))",
+        self.conn.query_row(
+            &format!("SELECT {} FROM templates WHERE rowid = last_insert_rowid()", TEMPLATE_COLUMNS),
             [],
-            |row| row.get(0),
-        )?;
+            Self::template_from_row,
+        )
+    }
 
-        // Create demo project
-        self.conn.execute(
-            "INSERT INTO projects (id, name, description, type, color, icon) VALUES (?, ?, ?, ?, ?, ?)",
-            rusqlite::params![
-                &project_id,
-                "Getting Started",
-                "Learn how to use Scribe with these example notes",
-                "generic",
-                "#3B82F6",
-                "BookOpen"
-            ],
-        )?;
+    pub fn update_template(&self, id: &str, update: &TemplateUpdate) -> SqlResult<Option<Template>> {
+        let mut sql_parts: Vec<&str> = Vec::new();
+        let mut params: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
 
-        // Create demo notes
-        let welcome_content = r#"# Welcome to Scribe! 👋
+        if let Some(name) = &update.name {
+            let name = name.trim();
+            if name.is_empty() {
+                return Err(invalid_input("Template name cannot be empty"));
+            }
+            sql_parts.push("name = ?");
+            params.push(Box::new(name.to_string()));
+        }
+        if let Some(description) = &update.description {
+            sql_parts.push("description = ?");
+            params.push(Box::new(Some(description.clone()).filter(|d| !d.is_empty())));
+        }
+        if let Some(content) = &update.content {
+            sql_parts.push("content = ?");
+            params.push(Box::new(content.clone()));
+        }
+        if let Some(properties) = &update.properties {
+            let properties = Some(properties.clone()).filter(|p| !p.trim().is_empty());
+            if let Some(props) = &properties {
+                Self::validate_properties(props).map_err(invalid_input)?;
+            }
+            sql_parts.push("properties = ?");
+            params.push(Box::new(properties));
+        }
+        if let Some(tags) = &update.tags {
+            sql_parts.push("tags = ?");
+            params.push(Box::new(Self::template_tags_json(tags)?));
+        }
 
-Scribe is an **ADHD-friendly distraction-free writer** designed to help you focus.
+        if !sql_parts.is_empty() {
+            sql_parts.push("updated_at = strftime('%s', 'now')");
+            params.push(Box::new(id.to_string()));
+            let sql = format!("UPDATE templates SET {} WHERE id = ?", sql_parts.join(", "));
+            self.conn.execute(&sql, params_from_iter(params.iter()))?;
+        }
 
-## Quick Tips
+        self.get_template(id)
+    }
 
-- Press **⌘N** to create a new note
-- Press **⌘D** to open today's daily note
-- Press **⌘K** to open the command palette
-- Press **Escape** to close panels
+    /// Delete a template; the daily note setting stops pointing at it
+    pub fn delete_template(&self, id: &str) -> SqlResult<bool> {
+        let changes = self.conn.execute("DELETE FROM templates WHERE id = ?", [id])?;
+        if changes > 0 && self.get_app_setting(DAILY_TEMPLATE_SETTING)?.as_deref() == Some(id) {
+            self.set_app_setting(DAILY_TEMPLATE_SETTING, None)?;
+        }
+        Ok(changes > 0)
+    }
 
-## Tauri Desktop App
+    pub fn get_app_setting(&self, key: &str) -> SqlResult<Option<String>> {
+        let result = self.conn.query_row("SELECT value FROM app_settings WHERE key = ?", [key], |row| row.get(0));
+        match result {
+            Ok(value) => Ok(Some(value)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
 
-You're running Scribe as a **native desktop app** with SQLite storage.
-Your notes are saved locally and sync instantly!
+    /// Store a setting; `None` removes it
+    pub fn set_app_setting(&self, key: &str, value: Option<&str>) -> SqlResult<()> {
+        match value {
+            Some(value) => self.conn.execute(
+                "INSERT INTO app_settings (key, value) VALUES (?, ?)
+                 ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+                [key, value],
+            )?,
+            None => self.conn.execute("DELETE FROM app_settings WHERE key = ?", [key])?,
+        };
+        Ok(())
+    }
 
-## Getting Started
+    pub fn get_daily_note_template(&self) -> SqlResult<Option<Template>> {
+        match self.get_app_setting(DAILY_TEMPLATE_SETTING)? {
+            Some(id) => self.get_template(&id),
+            None => Ok(None),
+        }
+    }
 
-1. Create a new note with ⌘N
-2. Start writing without distractions
-3. Use #tags to organize your notes
-4. Link notes with [[wiki links]]
+    /// Choose the template for new daily notes; `None` goes back to the plain `## date` heading
+    pub fn set_daily_note_template(&self, template_id: Option<&str>) -> SqlResult<()> {
+        if let Some(id) = template_id {
+            if self.get_template(id)?.is_none() {
+                return Err(invalid_input(format!("Template not found: {}", id)));
+            }
+        }
+        self.set_app_setting(DAILY_TEMPLATE_SETTING, template_id)
+    }
 
-See the [[Features Overview]] note for more details."#;
+    /// Name the template used for new notes in a project (`defaultTemplateId` in its settings JSON)
+    pub fn set_project_default_template(&self, project_id: &str, template_id: Option<&str>) -> SqlResult<Option<Project>> {
+        let Some(project) = self.get_project(project_id)? else {
+            return Ok(None);
+        };
+        if let Some(id) = template_id {
+            if self.get_template(id)?.is_none() {
+                return Err(invalid_input(format!("Template not found: {}", id)));
+            }
+        }
 
-        let features_content = r#"# Features Overview
+        let mut settings: serde_json::Map<String, JsonValue> = project.settings
+            .as_deref()
+            .and_then(|s| serde_json::from_str(s).ok())
+            .unwrap_or_default();
+        match template_id {
+            Some(id) => settings.insert(PROJECT_TEMPLATE_SETTING.to_string(), JsonValue::from(id)),
+            None => settings.remove(PROJECT_TEMPLATE_SETTING),
+        };
 
-Scribe includes these core features to help you write:
+        self.conn.execute(
+            "UPDATE projects SET settings = ?, updated_at = strftime('%s', 'now') WHERE id = ?",
+            [JsonValue::Object(settings).to_string(), project_id.to_string()],
+        )?;
+        self.get_project(project_id)
+    }
 
-## ✍️ Writing
-- Clean, minimal editor
-- Auto-save (never lose work)
-- Dark mode for less eye strain
-- Word count & reading time
+    fn project_default_template(&self, project: &Project) -> SqlResult<Option<Template>> {
+        let id = project.settings
+            .as_deref()
+            .and_then(|s| serde_json::from_str::<JsonValue>(s).ok())
+            .and_then(|settings| settings.get(PROJECT_TEMPLATE_SETTING)?.as_str().map(String::from));
+        match id {
+            Some(id) => self.get_template(&id),
+            None => Ok(None),
+        }
+    }
 
-## 🏷️ Organization
-- **Tags**: Add #tags anywhere in your notes
-- **Folders**: inbox, notes, archive
-- **Projects**: Group related notes together
+    /// Create a note from a template. Without `template_id` the project's default
+    /// template is used, or an empty note when there is none. `date` fills
+    /// `{{date}}` and defaults to today.
+    pub fn create_note_from_template(
+        &self,
+        template_id: Option<&str>,
+        title: &str,
+        folder: &str,
+        project_id: Option<&str>,
+        date: Option<chrono::NaiveDate>,
+    ) -> SqlResult<NoteFromTemplate> {
+        let project = match project_id {
+            Some(id) => Some(self.get_project(id)?.ok_or_else(|| invalid_input(format!("Project not found: {}", id)))?),
+            None => None,
+        };
+        let template = match template_id {
+            Some(id) => Some(self.get_template(id)?.ok_or_else(|| invalid_input(format!("Template not found: {}", id)))?),
+            None => match &project {
+                Some(project) => self.project_default_template(project)?,
+                None => None,
+            },
+        };
 
-## 🔗 Knowledge
-- **Wiki Links**: Connect notes with [[Note Title]]
-- **Backlinks**: See what links to the current note
-- **Daily Notes**: Quick journal entries
+        let Some(template) = template else {
+            let note = self.create_note_as(title, "", folder, project_id, None, NoteEdit::Generated)?;
+            return Ok(NoteFromTemplate { note, cursor: None });
+        };
 
-## 📚 Academic Features
-- Citation management with Zotero
-- Export to PDF, Word, LaTeX
-- Equation support with KaTeX
+        let now = chrono::Local::now().naive_local();
+        let ctx = TemplateContext {
+            title,
+            folder,
+            project_name: project.as_ref().map(|p| p.name.as_str()),
+            date: date.unwrap_or(now.date()),
+            now,
+        };
 
-## 🤖 AI Integration
-- Claude CLI integration
-- Gemini CLI integration
-- No API keys needed - uses your installed CLI tools"#;
+        let rendered = templates::render(&template.content, &ctx);
+        let mut content = rendered.content;
+        for tag in &template.tags {
+            content = markdown::append_tag(&content, tag);
+        }
 
-        let daily_content = format!(r#"# Daily Note Example
+        let properties = match template.properties.as_deref() {
+            Some(json) => {
+                let mut properties: HashMap<String, Property> = serde_json::from_str(json)
+                    .map_err(|e| invalid_input(format!("Invalid properties JSON: {}", e)))?;
+                for property in properties.values_mut() {
+                    if let JsonValue::String(text) = &property.value {
+                        property.value = JsonValue::String(templates::render(text, &ctx).content);
+                    }
+                }
+                Some(serde_json::to_string(&properties).map_err(|e| invalid_input(e.to_string()))?)
+            }
+            None => None,
+        };
 
-## Focus for Today
-- [ ] Review the [[Welcome to Scribe]] tutorial
-- [ ] Create your first note
-- [ ] Try adding some #tags
+        let note = self.create_note_as(title, &content, folder, project_id, properties.as_deref(), NoteEdit::Generated)?;
+        Ok(NoteFromTemplate { note, cursor: rendered.cursor })
+    }
 
-## Notes
-Use this space for quick thoughts and ideas.
+    // Periodic notes
 
-Press ⌘D anytime to open today's daily note.
+    pub fn get_periodic_config(&self, period: Period) -> SqlResult<PeriodicConfig> {
+        #[derive(Deserialize, Default)]
+        #[serde(default)]
+        struct Stored {
+            folder: Option<String>,
+            title_format: Option<String>,
+            template_id: Option<String>,
+        }
 
-## End of Day Review
-What did you accomplish today?"#);
+        let key = format!("{}{}", PERIODIC_SETTING_PREFIX, period.as_str());
+        let stored: Stored = self.get_app_setting(&key)?
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default();
 
-        // Insert notes
-        self.conn.execute(
-            "INSERT INTO notes (id, title, content, folder, project_id) VALUES (?, ?, ?, ?, ?)",
-            rusqlite::params![&note1_id, "Welcome to Scribe", welcome_content, "inbox", &project_id],
-        )?;
+        // The daily template keeps its own setting so `set_daily_note_template` stays in sync
+        let template_id = match period {
+            Period::Daily => self.get_app_setting(DAILY_TEMPLATE_SETTING)?,
+            _ => stored.template_id,
+        };
 
-        self.conn.execute(
-            "INSERT INTO notes (id, title, content, folder, project_id) VALUES (?, ?, ?, ?, ?)",
-            rusqlite::params![&note2_id, "Features Overview", features_content, "notes", &project_id],
-        )?;
+        Ok(PeriodicConfig {
+            period,
+            folder: stored.folder.unwrap_or_else(|| period.default_folder().to_string()),
+            title_format: stored.title_format.unwrap_or_else(|| period.default_title_format().to_string()),
+            template_id,
+        })
+    }
 
-        self.conn.execute(
-            "INSERT INTO notes (id, title, content, folder, project_id) VALUES (?, ?, ?, ?, ?)",
-            rusqlite::params![&note3_id, "Daily Note Example", &daily_content, "notes", rusqlite::types::Null],
-        )?;
+    pub fn get_periodic_configs(&self) -> SqlResult<Vec<PeriodicConfig>> {
+        Period::ALL.iter().map(|period| self.get_periodic_config(*period)).collect()
+    }
 
-        // Update FTS index (properties column will be added in migration 008, so don't use it here)
-        self.conn.execute(
-            "INSERT INTO notes_fts (note_id, title, content) VALUES (?, ?, ?)",
-            rusqlite::params![&note1_id, "Welcome to Scribe", welcome_content],
-        )?;
-        self.conn.execute(
-            "INSERT INTO notes_fts (note_id, title, content) VALUES (?, ?, ?)",
-            rusqlite::params![&note2_id, "Features Overview", features_content],
-        )?;
-        self.conn.execute(
-            "INSERT INTO notes_fts (note_id, title, content) VALUES (?, ?, ?)",
-            rusqlite::params![&note3_id, "Daily Note Example", &daily_content],
-        )?;
+    pub fn set_periodic_config(&self, config: &PeriodicConfig) -> SqlResult<PeriodicConfig> {
+        let folder = Self::normalize_folder_path(&config.folder)?;
+        let title_format = config.title_format.trim();
+        if title_format.is_empty() {
+            return Err(invalid_input("Title format cannot be empty"));
+        }
+        if let Some(id) = &config.template_id {
+            if self.get_template(id)?.is_none() {
+                return Err(invalid_input(format!("Template not found: {}", id)));
+            }
+        }
 
-        // Create demo tags
-        let tag_welcome_id: String = self.conn.query_row(
-            "SELECT lower(hex(randomblob(16)))",
-            [],
-            |row| row.get(0),
-        )?;
-        let tag_tutorial_id: String = self.conn.query_row(
-            "SELECT lower(hex(randomblob(16)))",
-            [],
-            |row| row.get(0),
-        )?;
-        let tag_tips_id: String = self.conn.query_row(
-            "SELECT lower(hex(randomblob(16)))",
-            [],
-            |row| row.get(0),
-        )?;
+        let mut stored = serde_json::json!({ "folder": folder, "title_format": title_format });
+        match config.period {
+            Period::Daily => self.set_app_setting(DAILY_TEMPLATE_SETTING, config.template_id.as_deref())?,
+            _ => stored["template_id"] = JsonValue::from(config.template_id.clone()),
+        }
+        let key = format!("{}{}", PERIODIC_SETTING_PREFIX, config.period.as_str());
+        self.set_app_setting(&key, Some(&stored.to_string()))?;
 
-        self.conn.execute(
-            "INSERT INTO tags (id, name, color) VALUES (?, ?, ?)",
-            rusqlite::params![&tag_welcome_id, "welcome", "#10B981"],
-        )?;
-        self.conn.execute(
-            "INSERT INTO tags (id, name, color) VALUES (?, ?, ?)",
-            rusqlite::params![&tag_tutorial_id, "tutorial", "#8B5CF6"],
-        )?;
-        self.conn.execute(
-            "INSERT INTO tags (id, name, color) VALUES (?, ?, ?)",
-            rusqlite::params![&tag_tips_id, "tips", "#F59E0B"],
-        )?;
+        self.get_periodic_config(config.period)
+    }
 
-        // Associate tags with notes
-        self.conn.execute(
-            "INSERT INTO note_tags (note_id, tag_id) VALUES (?, ?)",
-            rusqlite::params![&note1_id, &tag_welcome_id],
-        )?;
-        self.conn.execute(
-            "INSERT INTO note_tags (note_id, tag_id) VALUES (?, ?)",
-            rusqlite::params![&note1_id, &tag_tutorial_id],
-        )?;
-        self.conn.execute(
-            "INSERT INTO note_tags (note_id, tag_id) VALUES (?, ?)",
-            rusqlite::params![&note2_id, &tag_tutorial_id],
-        )?;
-        self.conn.execute(
-            "INSERT INTO note_tags (note_id, tag_id) VALUES (?, ?)",
-            rusqlite::params![&note2_id, &tag_tips_id],
-        )?;
+    fn periodic_note(&self, period: Period, start: chrono::NaiveDate) -> SqlResult<Option<Note>> {
+        let result = self.conn.query_row(
+            &format!(
+                "SELECT {} FROM notes
+                 JOIN periodic_notes ON periodic_notes.note_id = notes.id
+                 WHERE periodic_notes.period = ? AND periodic_notes.start_date = ? AND notes.deleted_at IS NULL",
+                NOTE_COLUMNS
+            ),
+            [period.as_str(), &start.to_string()],
+            Self::note_from_row,
+        );
+
+        match result {
+            Ok(note) => Ok(Some(note)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// The period containing `date`, shifted by `offset` periods, with its note if one exists
+    pub fn get_periodic_note(&self, period: Period, date: chrono::NaiveDate, offset: i32) -> SqlResult<PeriodicNote> {
+        let start = period.shift(date, offset);
+        let config = self.get_periodic_config(period)?;
+        Ok(PeriodicNote {
+            period,
+            start_date: start.to_string(),
+            end_date: period.end(start).to_string(),
+            title: templates::format_date(start.and_time(chrono::NaiveTime::MIN), &config.title_format),
+            note: self.periodic_note(period, start)?,
+        })
+    }
+
+    /// The note for the period containing `date`, created from the period's template if missing.
+    /// A note that already has the period's title in its folder is adopted instead.
+    pub fn get_or_create_periodic_note(&self, period: Period, date: chrono::NaiveDate) -> SqlResult<Note> {
+        let start = period.start(date);
+        if let Some(note) = self.periodic_note(period, start)? {
+            return Ok(note);
+        }
+
+        let config = self.get_periodic_config(period)?;
+        let title = templates::format_date(start.and_time(chrono::NaiveTime::MIN), &config.title_format);
+        let folder = Self::normalize_folder_path(&config.folder)?;
+        self.ensure_folder(&folder)?;
+
+        let existing = self.get_note_by_title_and_folder(&title, &folder)?;
+        let created = existing.is_none();
+        let note = match existing {
+            Some(note) => note,
+            None => {
+                let template_id = match &config.template_id {
+                    Some(id) if self.get_template(id)?.is_some() => Some(id.as_str()),
+                    _ => None,
+                };
+                match template_id {
+                    Some(id) => self.create_note_from_template(Some(id), &title, &folder, None, Some(start))?.note,
+                    None => {
+                        let heading = if period == Period::Daily { "##" } else { "#" };
+                        let content = format!("{} {}\n\n", heading, title);
+                        self.create_note_as(&title, &content, &folder, None, None, NoteEdit::Generated)?
+                    }
+                }
+            }
+        };
 
-        // Create wiki link from Welcome to Features Overview
         self.conn.execute(
-            "INSERT INTO links (source_note_id, target_note_id) VALUES (?, ?)",
-            rusqlite::params![&note1_id, &note2_id],
+            "INSERT OR REPLACE INTO periodic_notes (period, start_date, note_id) VALUES (?, ?, ?)",
+            [period.as_str(), &start.to_string(), &note.id],
         )?;
 
-        println!("  ✅ Demo data seeded successfully:");
-        println!("     - 1 project: 'Getting Started'");
-        println!("     - 3 notes: Welcome, Features, Daily Example");
-        println!("     - 3 tags: #welcome, #tutorial, #tips");
+        if created && period == Period::Daily && start == chrono::Local::now().date_naive() {
+            let rollup = self.get_daily_rollup_config()?;
+            if rollup.enabled {
+                self.apply_daily_rollup(&note, start, &rollup)?;
+                return Ok(self.get_note(&note.id)?.unwrap_or(note));
+            }
+        }
+        Ok(note)
+    }
 
-        Ok(())
+    /// The daily note for `date` (`YYYY-MM-DD`)
+    pub fn get_or_create_daily_note(&self, date: &str) -> SqlResult<Note> {
+        let day = chrono::NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d")
+            .map_err(|_| invalid_input(format!("Invalid date: {}", date)))?;
+        self.get_or_create_periodic_note(Period::Daily, day)
     }
 
-    #[cfg(not(test))]
-    fn run_migration_008_add_properties_to_fts(&self) -> SqlResult<()> {
-        println!("Running database migration 008 (add properties to FTS index)");
+    pub fn get_daily_rollup_config(&self) -> SqlResult<DailyRollupConfig> {
+        Ok(self.get_app_setting(DAILY_ROLLUP_SETTING)?
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default())
+    }
 
-        // Drop old FTS table and triggers
-        self.conn.execute_batch("
-            DROP TRIGGER IF EXISTS notes_au;
-            DROP TRIGGER IF EXISTS notes_ad;
-            DROP TRIGGER IF EXISTS notes_ai;
-            DROP TABLE IF EXISTS notes_fts;
-        ")?;
+    pub fn set_daily_rollup_config(&self, config: &DailyRollupConfig) -> SqlResult<()> {
+        let json = serde_json::to_string(config).map_err(|e| invalid_input(e.to_string()))?;
+        self.set_app_setting(DAILY_ROLLUP_SETTING, Some(&json))
+    }
 
-        // Recreate FTS table with properties column
-        self.conn.execute_batch("
-            CREATE VIRTUAL TABLE notes_fts USING fts5(
-                note_id UNINDEXED,
-                title,
-                content,
-                properties
-            );
+    /// Carry unfinished tasks and yesterday's notes into the daily note for `date`,
+    /// creating it if needed. Safe to run again: migrated tasks and notes already
+    /// linked are skipped.
+    pub fn rollup_daily_note(&self, date: chrono::NaiveDate) -> SqlResult<DailyRollup> {
+        let note = self.get_or_create_periodic_note(Period::Daily, date)?;
+        let note = self.get_note(&note.id)?.unwrap_or(note);
+        self.apply_daily_rollup(&note, date, &self.get_daily_rollup_config()?)
+    }
 
-            CREATE TRIGGER notes_ai AFTER INSERT ON notes BEGIN
-                INSERT INTO notes_fts(note_id, title, content, properties)
-                VALUES (new.id, new.title, new.content, COALESCE(new.properties, ''));
-            END;
+    fn apply_daily_rollup(&self, note: &Note, date: chrono::NaiveDate, config: &DailyRollupConfig) -> SqlResult<DailyRollup> {
+        let tx = self.conn.unchecked_transaction()?;
+        let mut result = DailyRollup { note_id: note.id.clone(), ..Default::default() };
+
+        let earliest = date - chrono::Days::new(u64::from(config.lookback_days));
+        let sources: Vec<(String, String, String)> = self.conn
+            .prepare(
+                "SELECT notes.id, notes.title, notes.content FROM notes
+                 JOIN periodic_notes ON periodic_notes.note_id = notes.id
+                 WHERE periodic_notes.period = 'daily'
+                   AND periodic_notes.start_date >= ? AND periodic_notes.start_date < ?
+                   AND notes.id != ? AND notes.deleted_at IS NULL
+                 ORDER BY periodic_notes.start_date",
+            )?
+            .query_map([earliest.to_string(), date.to_string(), note.id.clone()], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut task_lines = Vec::new();
+        for (id, title, content) in &sources {
+            let tasks = markdown::task_items(content);
+            let open: Vec<&markdown::TaskItem> = tasks.iter().filter(|t| t.status == markdown::TASK_OPEN).collect();
+            if open.is_empty() {
+                continue;
+            }
 
-            CREATE TRIGGER notes_ad AFTER DELETE ON notes BEGIN
-                DELETE FROM notes_fts WHERE note_id = old.id;
-            END;
+            // A task carried over before keeps the link to where it started
+            task_lines.extend(open.iter().map(|t| {
+                if markdown::ends_with_source_link(&t.text) {
+                    format!("- [ ] {}", t.text)
+                } else {
+                    format!("- [ ] {} ([[{}]])", t.text, title)
+                }
+            }));
+            let migrated = markdown::set_task_status(content, &open, markdown::TASK_MIGRATED);
+            self.update_note_as(id, None, Some(&migrated), None, NoteEdit::Generated)?;
+            result.carried_tasks += open.len();
+            result.source_note_ids.push(id.clone());
+        }
 
-            CREATE TRIGGER notes_au AFTER UPDATE ON notes BEGIN
-                DELETE FROM notes_fts WHERE note_id = old.id;
-                INSERT INTO notes_fts(note_id, title, content, properties)
-                VALUES (new.id, new.title, new.content, COALESCE(new.properties, ''));
-            END;
-        ")?;
+        let mut link_lines = Vec::new();
+        if config.link_recent_notes {
+            let yesterday = date - chrono::Days::new(1);
+            let recent: Vec<String> = self.conn
+                .prepare(
+                    "SELECT title FROM notes
+                     WHERE updated_at >= ? AND updated_at < ? AND id != ?
+                       AND deleted_at IS NULL AND archived_at IS NULL
+                       AND id NOT IN (SELECT note_id FROM periodic_notes)
+                     ORDER BY updated_at DESC",
+                )?
+                .query_map(
+                    rusqlite::params![local_day_start(yesterday), local_day_start(date), &note.id],
+                    |row| row.get(0),
+                )?
+                .collect::<Result<Vec<_>, _>>()?;
+
+            for title in recent {
+                let link = format!("[[{}]]", title);
+                if !note.content.contains(&link) && !link_lines.iter().any(|l: &String| l.ends_with(&link)) {
+                    link_lines.push(format!("- {}", link));
+                }
+            }
+            result.linked_notes = link_lines.len();
+        }
 
-        // Populate FTS table with existing notes
-        self.conn.execute("
-            INSERT INTO notes_fts(note_id, title, content, properties)
-            SELECT id, title, content, COALESCE(properties, '')
-            FROM notes
-            WHERE deleted_at IS NULL
-        ", [])?;
+        if !task_lines.is_empty() || !link_lines.is_empty() {
+            let mut content = note.content.clone();
+            for (heading, lines) in [(ROLLUP_TASKS_HEADING, &task_lines), (ROLLUP_LINKS_HEADING, &link_lines)] {
+                if !lines.is_empty() {
+                    content = markdown::append_to_section(&content, heading, lines);
+                }
+            }
+            self.update_note_as(&note.id, None, Some(&content), None, NoteEdit::Generated)?;
+        }
 
-        println!("  ✅ FTS index updated with properties column");
-        Ok(())
+        tx.commit()?;
+        Ok(result)
     }
 
-    #[cfg(test)]
-    pub(crate) fn run_migration_008_add_properties_to_fts(&self) -> SqlResult<()> {
-        println!("Running database migration 008 (add properties to FTS index)");
-
-        // Drop old FTS table and triggers
-        self.conn.execute_batch("
-            DROP TRIGGER IF EXISTS notes_au;
-            DROP TRIGGER IF EXISTS notes_ad;
-            DROP TRIGGER IF EXISTS notes_ai;
-            DROP TABLE IF EXISTS notes_fts;
-        ")?;
+    /// Periods between `from` and `to` (inclusive) that have a note
+    pub fn get_periodic_calendar(&self, period: Period, from: chrono::NaiveDate, to: chrono::NaiveDate) -> SqlResult<Vec<CalendarEntry>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT periodic_notes.start_date, notes.id, notes.title, notes.word_count
+             FROM periodic_notes
+             JOIN notes ON notes.id = periodic_notes.note_id
+             WHERE periodic_notes.period = ? AND periodic_notes.start_date BETWEEN ? AND ?
+               AND notes.deleted_at IS NULL
+             ORDER BY periodic_notes.start_date",
+        )?;
 
-        // Recreate FTS table with properties column
-        self.conn.execute_batch("
-            CREATE VIRTUAL TABLE notes_fts USING fts5(
-                note_id UNINDEXED,
-                title,
-                content,
-                properties
-            );
+        let entries = stmt.query_map(
+            [period.as_str(), &period.start(from).to_string(), &to.to_string()],
+            |row| {
+                Ok(CalendarEntry {
+                    date: row.get(0)?,
+                    note_id: row.get(1)?,
+                    title: row.get(2)?,
+                    word_count: row.get(3)?,
+                })
+            },
+        )?;
 
-            CREATE TRIGGER notes_ai AFTER INSERT ON notes BEGIN
-                INSERT INTO notes_fts(note_id, title, content, properties)
-                VALUES (new.id, new.title, new.content, COALESCE(new.properties, ''));
-            END;
+        entries.collect()
+    }
 
-            CREATE TRIGGER notes_ad AFTER DELETE ON notes BEGIN
-                DELETE FROM notes_fts WHERE note_id = old.id;
-            END;
+    // Project CRUD operations
 
-            CREATE TRIGGER notes_au AFTER UPDATE ON notes BEGIN
-                DELETE FROM notes_fts WHERE note_id = old.id;
-                INSERT INTO notes_fts(note_id, title, content, properties)
-                VALUES (new.id, new.title, new.content, COALESCE(new.properties, ''));
-            END;
-        ")?;
+    pub fn create_project(
+        &self,
+        name: &str,
+        description: Option<&str>,
+        project_type: &str,
+        color: Option<&str>,
+        icon: Option<&str>,
+        settings: Option<&str>,
+    ) -> SqlResult<Project> {
+        if self.get_project_type(project_type)?.is_none() {
+            return Err(invalid_input(format!("Unknown project type: {}", project_type)));
+        }
 
-        // Populate FTS table with existing notes
-        self.conn.execute("
-            INSERT INTO notes_fts(note_id, title, content, properties)
-            SELECT id, title, content, COALESCE(properties, '')
-            FROM notes
-            WHERE deleted_at IS NULL
-        ", [])?;
+        self.conn.execute(
+            "INSERT INTO projects (name, description, type, color, icon, settings) VALUES (?, ?, ?, ?, ?, ?)",
+            rusqlite::params![name, description, project_type, color, icon, settings],
+        )?;
 
-        println!("  ✅ FTS index updated with properties column");
-        Ok(())
+        self.conn.query_row(
+            &format!("SELECT {} FROM projects WHERE rowid = last_insert_rowid()", PROJECT_COLUMNS),
+            [],
+            Self::project_from_row,
+        )
     }
 
-    #[cfg(not(test))]
-    fn run_migration_009_chat_history(&self) -> SqlResult<()> {
-        println!("Running database migration 009 (chat history tables)");
+    fn project_from_row(row: &rusqlite::Row) -> SqlResult<Project> {
+        Ok(Project {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            description: row.get(2)?,
+            project_type: row.get(3)?,
+            color: row.get(4)?,
+            icon: row.get(5)?,
+            settings: row.get(6)?,
+            created_at: row.get(7)?,
+            updated_at: row.get(8)?,
+            archived_at: row.get(9)?,
+        })
+    }
 
-        // Create chat_sessions table
-        self.conn.execute_batch("
-            CREATE TABLE IF NOT EXISTS chat_sessions (
-                id TEXT PRIMARY KEY,
-                note_id TEXT NOT NULL,
-                created_at INTEGER NOT NULL,
-                updated_at INTEGER NOT NULL,
-                FOREIGN KEY (note_id) REFERENCES notes(id) ON DELETE CASCADE
-            );
+    pub fn get_project(&self, id: &str) -> SqlResult<Option<Project>> {
+        let result = self.conn.query_row(
+            &format!("SELECT {} FROM projects WHERE id = ?", PROJECT_COLUMNS),
+            [id],
+            Self::project_from_row,
+        );
 
-            CREATE INDEX IF NOT EXISTS idx_chat_sessions_note_id ON chat_sessions(note_id);
-            CREATE INDEX IF NOT EXISTS idx_chat_sessions_updated_at ON chat_sessions(updated_at DESC);
-        ")?;
+        match result {
+            Ok(project) => Ok(Some(project)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
 
-        // Create chat_messages table
-        self.conn.execute_batch("
-            CREATE TABLE IF NOT EXISTS chat_messages (
-                id TEXT PRIMARY KEY,
-                session_id TEXT NOT NULL,
-                role TEXT NOT NULL CHECK (role IN ('user', 'assistant')),
-                content TEXT NOT NULL,
-                timestamp INTEGER NOT NULL,
-                FOREIGN KEY (session_id) REFERENCES chat_sessions(id) ON DELETE CASCADE
-            );
+    /// Projects by name, optionally of one type. Archived projects are left
+    /// out unless `include_archived` is set.
+    pub fn list_projects(&self, project_type: Option<&str>, include_archived: bool) -> SqlResult<Vec<Project>> {
+        let archived = if include_archived { "" } else { " AND archived_at IS NULL" };
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM projects
+             WHERE (?1 IS NULL OR type = ?1){}
+             ORDER BY name COLLATE NOCASE",
+            PROJECT_COLUMNS, archived
+        ))?;
 
-            CREATE INDEX IF NOT EXISTS idx_chat_messages_session_id ON chat_messages(session_id);
-            CREATE INDEX IF NOT EXISTS idx_chat_messages_timestamp ON chat_messages(timestamp ASC);
-        ")?;
+        let projects = stmt.query_map([project_type], Self::project_from_row)?;
 
-        println!("  ✅ Chat history tables created");
-        Ok(())
+        projects.collect()
     }
 
-    #[cfg(test)]
-    pub(crate) fn run_migration_009_chat_history(&self) -> SqlResult<()> {
-        println!("Running database migration 009 (chat history tables)");
-
-        // Create chat_sessions table
-        self.conn.execute_batch("
-            CREATE TABLE IF NOT EXISTS chat_sessions (
-                id TEXT PRIMARY KEY,
-                note_id TEXT NOT NULL,
-                created_at INTEGER NOT NULL,
-                updated_at INTEGER NOT NULL,
-                FOREIGN KEY (note_id) REFERENCES notes(id) ON DELETE CASCADE
-            );
+    /// Archive or unarchive a project. Its notes, settings and history stay as they are.
+    pub fn set_project_archived(&self, id: &str, archived: bool) -> SqlResult<Option<Project>> {
+        let sql = if archived {
+            "UPDATE projects SET archived_at = COALESCE(archived_at, strftime('%s', 'now')) WHERE id = ?"
+        } else {
+            "UPDATE projects SET archived_at = NULL WHERE id = ?"
+        };
+        self.conn.execute(sql, [id])?;
+        self.get_project(id)
+    }
 
-            CREATE INDEX IF NOT EXISTS idx_chat_sessions_note_id ON chat_sessions(note_id);
-            CREATE INDEX IF NOT EXISTS idx_chat_sessions_updated_at ON chat_sessions(updated_at DESC);
-        ")?;
+    pub fn update_project(
+        &self,
+        id: &str,
+        name: Option<&str>,
+        description: Option<&str>,
+        project_type: Option<&str>,
+        color: Option<&str>,
+        icon: Option<&str>,
+        settings: Option<&str>,
+    ) -> SqlResult<Option<Project>> {
+        let mut sql_parts = Vec::new();
+        let mut params: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
 
-        // Create chat_messages table
-        self.conn.execute_batch("
-            CREATE TABLE IF NOT EXISTS chat_messages (
-                id TEXT PRIMARY KEY,
-                session_id TEXT NOT NULL,
-                role TEXT NOT NULL CHECK (role IN ('user', 'assistant')),
-                content TEXT NOT NULL,
-                timestamp INTEGER NOT NULL,
-                FOREIGN KEY (session_id) REFERENCES chat_sessions(id) ON DELETE CASCADE
-            );
+        if let Some(n) = name {
+            sql_parts.push("name = ?");
+            params.push(Box::new(n.to_string()));
+        }
+        if let Some(d) = description {
+            sql_parts.push("description = ?");
+            params.push(Box::new(d.to_string()));
+        }
+        if let Some(pt) = project_type {
+            if self.get_project_type(pt)?.is_none() {
+                return Err(invalid_input(format!("Unknown project type: {}", pt)));
+            }
+            sql_parts.push("type = ?");
+            params.push(Box::new(pt.to_string()));
+        }
+        if let Some(c) = color {
+            sql_parts.push("color = ?");
+            params.push(Box::new(c.to_string()));
+        }
+        if let Some(i) = icon {
+            sql_parts.push("icon = ?");
+            params.push(Box::new(i.to_string()));
+        }
+        if let Some(s) = settings {
+            sql_parts.push("settings = ?");
+            params.push(Box::new(s.to_string()));
+        }
 
-            CREATE INDEX IF NOT EXISTS idx_chat_messages_session_id ON chat_messages(session_id);
-            CREATE INDEX IF NOT EXISTS idx_chat_messages_timestamp ON chat_messages(timestamp ASC);
-        ")?;
+        if sql_parts.is_empty() {
+            return self.get_project(id);
+        }
 
-        println!("  ✅ Chat history tables created");
-        Ok(())
+        sql_parts.push("updated_at = strftime('%s', 'now')");
+        params.push(Box::new(id.to_string()));
+
+        let sql = format!("UPDATE projects SET {} WHERE id = ?", sql_parts.join(", "));
+        self.conn.execute(&sql, params_from_iter(params))?;
+
+        self.get_project(id)
     }
 
-    fn run_migration_010_add_project_icons(&self) -> SqlResult<()> {
-        println!("Running database migration 010 (add project icons)");
+    pub fn delete_project(&self, id: &str) -> SqlResult<bool> {
+        // First, unassign all notes from this project
+        self.conn.execute(
+            "UPDATE notes SET project_id = NULL WHERE project_id = ?",
+            [id],
+        )?;
 
-        // Check if icon column already exists (it's added in migration 004 for new databases)
-        let has_icon_column: bool = self.conn.query_row(
-            "SELECT COUNT(*) FROM pragma_table_info('projects') WHERE name = 'icon'",
-            [],
-            |row| row.get::<_, i32>(0).map(|count| count > 0),
+        let changes = self.conn.execute("DELETE FROM projects WHERE id = ?", [id])?;
+        Ok(changes > 0)
+    }
+
+    pub fn get_notes_by_project(&self, project_id: &str) -> SqlResult<Vec<Note>> {
+        let mut stmt = self.conn.prepare(
+            &format!(
+                "SELECT {} FROM notes
+                 WHERE project_id = ? AND deleted_at IS NULL AND archived_at IS NULL
+                 ORDER BY pinned DESC, updated_at DESC",
+                NOTE_COLUMNS
+            ),
         )?;
 
-        if !has_icon_column {
-            // Add icon column to projects table (for databases created before migration 004 update)
-            self.conn.execute_batch("
-                ALTER TABLE projects ADD COLUMN icon TEXT;
-            ")?;
-            println!("  ✅ Icon column added to projects table");
-        } else {
-            println!("  ℹ️  Icon column already exists (skipped)");
-        }
+        let notes = stmt.query_map([project_id], Self::note_from_row)?;
 
-        Ok(())
+        notes.collect()
     }
 
-    // Note CRUD operations
+    pub fn assign_note_to_project(&self, note_id: &str, project_id: Option<&str>) -> SqlResult<bool> {
+        let changes = self.conn.execute(
+            "UPDATE notes SET project_id = ?, updated_at = strftime('%s', 'now') WHERE id = ?",
+            rusqlite::params![project_id, note_id],
+        )?;
+        Ok(changes > 0)
+    }
 
-    /// Validate properties JSON structure and type constraints
-    fn validate_properties(properties_json: &str) -> Result<(), String> {
-        // Parse JSON
-        let properties: HashMap<String, Property> = serde_json::from_str(properties_json)
-            .map_err(|e| format!("Invalid properties JSON: {}", e))?;
+    pub fn get_project_note_count(&self, project_id: &str) -> SqlResult<i64> {
+        self.conn.query_row(
+            "SELECT COUNT(*) FROM notes WHERE project_id = ? AND deleted_at IS NULL",
+            [project_id],
+            |row| row.get(0),
+        )
+    }
 
-        // Validate each property's value matches its type
-        for (key, prop) in properties.iter() {
-            match (&prop.prop_type, &prop.value) {
-                (PropertyType::Number, v) if !v.is_number() => {
-                    return Err(format!("Property '{}' should be a number, got: {:?}", key, v));
-                }
-                (PropertyType::Checkbox, v) if !v.is_boolean() => {
-                    return Err(format!("Property '{}' should be a boolean, got: {:?}", key, v));
-                }
-                (PropertyType::List | PropertyType::Tags, v) if !v.is_array() => {
-                    return Err(format!("Property '{}' should be an array, got: {:?}", key, v));
-                }
-                (PropertyType::Text | PropertyType::Date | PropertyType::Link, v) if !v.is_string() => {
-                    return Err(format!("Property '{}' should be a string, got: {:?}", key, v));
-                }
-                _ => {} // Type matches or is acceptable
-            }
+    // Project bundles and duplication
+
+    /// The folder a project's files live under. Path separators would nest folders and
+    /// `.`/`..` aren't valid folder names, so those are replaced.
+    fn project_folder_name(name: &str) -> String {
+        let name = name.trim().replace(['/', '\\'], "-");
+        if name.chars().all(|c| c == '.') {
+            return "project".to_string();
         }
+        name
+    }
 
-        Ok(())
+    /// Move `folder` from under one project folder to another; other folders are kept
+    fn remap_project_folder(folder: &str, from: &str, to: &str) -> String {
+        match folder.strip_prefix(from) {
+            Some(rest) if rest.is_empty() || rest.starts_with('/') => format!("{}{}", to, rest),
+            _ => folder.to_string(),
+        }
     }
 
-    pub fn create_note(&self, title: &str, content: &str, folder: &str, project_id: Option<&str>, properties: Option<&str>) -> SqlResult<Note> {
-        // Validate properties if provided
-        if let Some(props) = properties {
-            if !props.is_empty() {
-                if let Err(e) = Self::validate_properties(props) {
-                    return Err(rusqlite::Error::ToSqlConversionFailure(
-                        Box::new(std::io::Error::new(std::io::ErrorKind::InvalidInput, e))
-                    ));
-                }
-            }
+    /// `name`, or `name (2)`, `name (3)`, ... if a project already has it
+    fn unique_project_name(&self, name: &str) -> SqlResult<String> {
+        let taken = |candidate: &str| -> SqlResult<bool> {
+            self.conn.query_row(
+                "SELECT COUNT(*) > 0 FROM projects WHERE name = ? COLLATE NOCASE",
+                [candidate],
+                |row| row.get(0),
+            )
+        };
+
+        let mut candidate = name.to_string();
+        let mut n = 2;
+        while taken(&candidate)? {
+            candidate = format!("{} ({})", name, n);
+            n += 1;
         }
+        Ok(candidate)
+    }
 
-        self.conn.execute(
-            "INSERT INTO notes (title, content, folder, project_id, properties) VALUES (?, ?, ?, ?, ?)",
-            rusqlite::params![title, content, folder, project_id, properties],
-        )?;
+    /// Collect a project's notes (archived ones included), their tags, links,
+    /// chat history and attachment references, plus the project's folders and settings
+    pub fn export_project_bundle(&self, project_id: &str) -> SqlResult<Option<ProjectBundle>> {
+        let Some(project) = self.get_project(project_id)? else {
+            return Ok(None);
+        };
 
-        let note = self.conn.query_row(
-            "SELECT id, title, content, folder, project_id, properties, created_at, updated_at, deleted_at
-             FROM notes WHERE rowid = last_insert_rowid()",
-            [],
-            |row| {
-                Ok(Note {
-                    id: row.get(0)?,
-                    title: row.get(1)?,
-                    content: row.get(2)?,
-                    folder: row.get(3)?,
-                    project_id: row.get(4)?,
-                    properties: row.get(5)?,
-                    created_at: row.get(6)?,
-                    updated_at: row.get(7)?,
-                    deleted_at: row.get(8)?,
-                })
-            },
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM notes WHERE project_id = ? AND deleted_at IS NULL ORDER BY created_at, rowid",
+            NOTE_COLUMNS
+        ))?;
+        let notes: Vec<Note> = stmt.query_map([project_id], Self::note_from_row)?.collect::<Result<Vec<_>, _>>()?;
+
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT DISTINCT {} FROM tags
+             JOIN note_tags ON tags.id = note_tags.tag_id
+             JOIN notes ON notes.id = note_tags.note_id
+             WHERE notes.project_id = ? AND notes.deleted_at IS NULL
+             ORDER BY tags.name COLLATE NOCASE",
+            TAG_COLUMNS
+        ))?;
+        let tags: Vec<Tag> = stmt.query_map([project_id], Self::tag_from_row)?.collect::<Result<Vec<_>, _>>()?;
+
+        let mut stmt = self.conn.prepare(
+            "SELECT note_tags.note_id, note_tags.tag_id FROM note_tags
+             JOIN notes ON notes.id = note_tags.note_id
+             WHERE notes.project_id = ? AND notes.deleted_at IS NULL",
         )?;
-        
-        // Parse tags and links
-        self.update_note_tags(&note.id, content)?;
-        self.update_note_links(&note.id, content)?;
-        
-        Ok(note)
-    }
-    
-    pub fn get_note(&self, id: &str) -> SqlResult<Option<Note>> {
-        let result = self.conn.query_row(
-            "SELECT id, title, content, folder, project_id, properties, created_at, updated_at, deleted_at
-             FROM notes WHERE id = ? AND deleted_at IS NULL",
-            [id],
-            |row| {
-                Ok(Note {
-                    id: row.get(0)?,
-                    title: row.get(1)?,
-                    content: row.get(2)?,
-                    folder: row.get(3)?,
-                    project_id: row.get(4)?,
-                    properties: row.get(5)?,
-                    created_at: row.get(6)?,
-                    updated_at: row.get(7)?,
-                    deleted_at: row.get(8)?,
-                })
-            },
-        );
+        let note_tags: Vec<NoteTag> = stmt.query_map([project_id], |row| {
+            Ok(NoteTag { note_id: row.get(0)?, tag_id: row.get(1)? })
+        })?.collect::<Result<Vec<_>, _>>()?;
 
-        match result {
-            Ok(note) => Ok(Some(note)),
-            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
-            Err(e) => Err(e),
-        }
-    }
-    
-    pub fn list_notes(&self, folder: Option<&str>) -> SqlResult<Vec<Note>> {
-        if let Some(f) = folder {
-            let mut stmt = self.conn.prepare(
-                "SELECT id, title, content, folder, project_id, properties, created_at, updated_at, deleted_at
-                 FROM notes WHERE folder = ? AND deleted_at IS NULL
-                 ORDER BY updated_at DESC",
-            )?;
+        let mut stmt = self.conn.prepare(
+            "SELECT links.source_note_id, links.target_note_id, links.target_title, links.kind, links.anchor, links.alias
+             FROM links
+             JOIN notes ON notes.id = links.source_note_id
+             WHERE notes.project_id = ? AND notes.deleted_at IS NULL",
+        )?;
+        let links: Vec<Link> = stmt.query_map([project_id], |row| {
+            Ok(Link {
+                source_note_id: row.get(0)?,
+                target_note_id: row.get(1)?,
+                target_title: row.get(2)?,
+                kind: row.get(3)?,
+                anchor: row.get(4)?,
+                alias: row.get(5)?,
+            })
+        })?.collect::<Result<Vec<_>, _>>()?;
 
-            let notes = stmt.query_map([f], |row| {
-                Ok(Note {
-                    id: row.get(0)?,
-                    title: row.get(1)?,
-                    content: row.get(2)?,
-                    folder: row.get(3)?,
-                    project_id: row.get(4)?,
-                    properties: row.get(5)?,
-                    created_at: row.get(6)?,
-                    updated_at: row.get(7)?,
-                    deleted_at: row.get(8)?,
-                })
-            })?;
+        // The project's own folder tree, plus wherever its notes are filed
+        let folder_name = Self::project_folder_name(&project.name);
+        let note_folders: HashSet<&str> = notes.iter().map(|n| n.folder.as_str()).collect();
+        let folders: Vec<Folder> = self.get_folders()?
+            .into_iter()
+            .filter(|f| {
+                note_folders.contains(f.path.as_str())
+                    || Self::remap_project_folder(&f.path, &folder_name, "") != f.path
+            })
+            .collect();
 
-            notes.collect()
-        } else {
+        let mut chat_sessions = Vec::new();
+        let mut stmt = self.conn.prepare(
+            "SELECT chat_sessions.id, chat_sessions.note_id, chat_sessions.created_at, chat_sessions.updated_at
+             FROM chat_sessions
+             JOIN notes ON notes.id = chat_sessions.note_id
+             WHERE notes.project_id = ? AND notes.deleted_at IS NULL
+             ORDER BY chat_sessions.created_at",
+        )?;
+        let sessions: Vec<(String, String, i64, i64)> = stmt
+            .query_map([project_id], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)))?
+            .collect::<Result<Vec<_>, _>>()?;
+        for (id, note_id, created_at, updated_at) in sessions {
             let mut stmt = self.conn.prepare(
-                "SELECT id, title, content, folder, project_id, properties, created_at, updated_at, deleted_at
-                 FROM notes WHERE deleted_at IS NULL
-                 ORDER BY updated_at DESC",
+                "SELECT role, content, timestamp FROM chat_messages WHERE session_id = ? ORDER BY timestamp ASC",
             )?;
+            let messages = stmt.query_map([&id], |row| {
+                Ok(BundleChatMessage { role: row.get(0)?, content: row.get(1)?, timestamp: row.get(2)? })
+            })?.collect::<Result<Vec<_>, _>>()?;
+            chat_sessions.push(BundleChatSession { id, note_id, created_at, updated_at, messages });
+        }
 
-            let notes = stmt.query_map([], |row| {
-                Ok(Note {
-                    id: row.get(0)?,
-                    title: row.get(1)?,
-                    content: row.get(2)?,
-                    folder: row.get(3)?,
-                    project_id: row.get(4)?,
-                    properties: row.get(5)?,
-                    created_at: row.get(6)?,
-                    updated_at: row.get(7)?,
-                    deleted_at: row.get(8)?,
-                })
-            })?;
-
-            notes.collect()
+        // Number the copies so files with the same name in different directories don't collide
+        let mut attachments: Vec<BundleAttachment> = Vec::new();
+        for note in &notes {
+            for path in markdown::attachment_paths(&note.content) {
+                if attachments.iter().any(|a| a.path == path) {
+                    continue;
+                }
+                let name = path.rsplit(['/', '\\']).next().unwrap_or("attachment");
+                let file = format!("{}-{}", attachments.len() + 1, name);
+                attachments.push(BundleAttachment { path, file });
+            }
         }
+
+        let project_type = self.get_project_type(&project.project_type)?.filter(|t| !t.builtin);
+        let settings = self.get_project_settings(project_id)?;
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as i64;
+
+        Ok(Some(ProjectBundle {
+            version: PROJECT_BUNDLE_VERSION.to_string(),
+            timestamp,
+            project,
+            settings,
+            project_type,
+            notes,
+            folders,
+            tags,
+            note_tags,
+            links,
+            chat_sessions,
+            attachments,
+        }))
     }
-    
-    pub fn update_note(&self, id: &str, title: Option<&str>, content: Option<&str>, properties: Option<&str>) -> SqlResult<Option<Note>> {
-        if title.is_none() && content.is_none() && properties.is_none() {
-            return self.get_note(id);
+
+    /// Recreate a bundled project with new ids. The project is renamed (to
+    /// `name`, or with a ` (2)` suffix if its name is taken) and folders under
+    /// its old name move with it. Attachment references found in `attachments`
+    /// are rewritten to the new paths. Tags are matched by name, and links are
+    /// re-resolved so they point at the imported notes.
+    pub fn import_project_bundle(
+        &self,
+        bundle: &ProjectBundle,
+        name: Option<&str>,
+        attachments: &HashMap<String, String>,
+    ) -> SqlResult<Project> {
+        let name = name.map(str::trim).filter(|n| !n.is_empty()).unwrap_or(&bundle.project.name);
+        if name.is_empty() {
+            return Err(invalid_input("Project name cannot be empty"));
         }
 
-        // Validate properties if provided
-        if let Some(props) = properties {
-            if !props.is_empty() {
-                if let Err(e) = Self::validate_properties(props) {
-                    return Err(rusqlite::Error::ToSqlConversionFailure(
-                        Box::new(std::io::Error::new(std::io::ErrorKind::InvalidInput, e))
-                    ));
-                }
+        let tx = self.conn.unchecked_transaction()?;
+
+        if let Some(project_type) = &bundle.project_type {
+            if self.get_project_type(&project_type.id)?.is_none() {
+                self.save_project_type(&ProjectType { builtin: false, ..project_type.clone() })?;
             }
         }
+        let project_type = match self.get_project_type(&bundle.project.project_type)? {
+            Some(_) => bundle.project.project_type.as_str(),
+            None => project_types::DEFAULT_PROJECT_TYPE,
+        };
 
-        // Build dynamic SQL and collect owned values for params
-        let mut sql_parts = Vec::new();
-        let mut param_values: Vec<String> = Vec::new();
+        let source = &bundle.project;
+        let name = self.unique_project_name(name)?;
+        let project = self.create_project(
+            &name,
+            source.description.as_deref(),
+            project_type,
+            source.color.as_deref(),
+            source.icon.as_deref(),
+            source.settings.as_deref(),
+        )?;
+        if let Some(settings) = &bundle.settings {
+            self.update_project_settings(&project.id, settings)?;
+        }
 
-        if let Some(t) = title {
-            sql_parts.push("title = ?");
-            param_values.push(t.to_string());
+        let old_folder = Self::project_folder_name(&source.name);
+        let new_folder = Self::project_folder_name(&name);
+        for folder in &bundle.folders {
+            let path = Self::remap_project_folder(&folder.path, &old_folder, &new_folder);
+            self.ensure_folder(&path)?;
+            self.conn.execute(
+                "UPDATE folders SET color = COALESCE(color, ?), icon = COALESCE(icon, ?) WHERE path = ?",
+                rusqlite::params![folder.color, folder.icon, path],
+            )?;
         }
-        if let Some(c) = content {
-            sql_parts.push("content = ?");
-            param_values.push(c.to_string());
+
+        // Tags that don't exist here yet keep their colour, icon and description
+        for tag in &bundle.tags {
+            self.conn.execute(
+                "INSERT INTO tags (name, color, description, icon)
+                 SELECT ?1, ?2, ?3, ?4 WHERE NOT EXISTS (SELECT 1 FROM tags WHERE name = ?1 COLLATE NOCASE)",
+                rusqlite::params![tag.name, tag.color, tag.description, tag.icon],
+            )?;
         }
-        if let Some(p) = properties {
-            sql_parts.push("properties = ?");
-            param_values.push(p.to_string());
+
+        let mut note_ids: HashMap<&str, String> = HashMap::new();
+        for note in &bundle.notes {
+            let folder = Self::remap_project_folder(&note.folder, &old_folder, &new_folder);
+            let content = markdown::replace_attachment_paths(&note.content, attachments);
+            self.ensure_folder(&folder)?;
+            let created = self.create_note_as(
+                &note.title, &content, &folder, Some(&project.id), note.properties.as_deref(), NoteEdit::Generated,
+            )?;
+            self.conn.execute(
+                "UPDATE notes SET created_at = ?, updated_at = ?, pinned = ?, favorite = ?, archived_at = ? WHERE id = ?",
+                rusqlite::params![note.created_at, note.updated_at, note.pinned, note.favorite, note.archived_at, created.id],
+            )?;
+            note_ids.insert(note.id.as_str(), created.id);
         }
-        sql_parts.push("updated_at = strftime('%s', 'now')");
-        param_values.push(id.to_string());
 
-        let sql = format!("UPDATE notes SET {} WHERE id = ?", sql_parts.join(", "));
+        // Tags added by hand rather than written in the content
+        let tag_names: HashMap<&str, &str> = bundle.tags.iter().map(|t| (t.id.as_str(), t.name.as_str())).collect();
+        for note_tag in &bundle.note_tags {
+            if let (Some(note_id), Some(tag_name)) = (note_ids.get(note_tag.note_id.as_str()), tag_names.get(note_tag.tag_id.as_str())) {
+                self.add_tag_to_note(note_id, tag_name)?;
+            }
+        }
 
-        // Convert to references for params_from_iter
-        let params: Vec<&dyn rusqlite::ToSql> = param_values.iter().map(|s| s as &dyn rusqlite::ToSql).collect();
-        self.conn.execute(&sql, params_from_iter(params))?;
+        // Links written before their target was imported may have resolved elsewhere
+        for note in &bundle.notes {
+            let content = markdown::replace_attachment_paths(&note.content, attachments);
+            self.update_note_links(&note_ids[note.id.as_str()], &content)?;
+        }
 
-        let note = self.get_note(id)?;
-        if let Some(n) = &note {
-            if content.is_some() {
-                self.update_note_tags(&n.id, &n.content)?;
-                self.update_note_links(&n.id, &n.content)?;
+        for session in &bundle.chat_sessions {
+            let Some(note_id) = note_ids.get(session.note_id.as_str()) else {
+                continue;
+            };
+            let session_id = uuid::Uuid::new_v4().to_string();
+            self.conn.execute(
+                "INSERT INTO chat_sessions (id, note_id, created_at, updated_at) VALUES (?, ?, ?, ?)",
+                params![session_id, note_id, session.created_at, session.updated_at],
+            )?;
+            for message in &session.messages {
+                self.conn.execute(
+                    "INSERT INTO chat_messages (id, session_id, role, content, timestamp) VALUES (?, ?, ?, ?, ?)",
+                    params![uuid::Uuid::new_v4().to_string(), session_id, message.role, message.content, message.timestamp],
+                )?;
             }
         }
 
-        Ok(note)
-    }
-    
-    pub fn delete_note(&self, id: &str) -> SqlResult<bool> {
-        let changes = self.conn.execute(
-            "UPDATE notes SET deleted_at = strftime('%s', 'now') WHERE id = ?",
-            [id],
-        )?;
-        Ok(changes > 0)
+        tx.commit()?;
+        Ok(self.get_project(&project.id)?.unwrap_or(project))
     }
-    
-    pub fn search_notes(&self, query: &str) -> SqlResult<Vec<Note>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT notes.id, notes.title, notes.content, notes.folder, notes.project_id,
-                    notes.properties, notes.created_at, notes.updated_at, notes.deleted_at
-             FROM notes
-             JOIN notes_fts ON notes.id = notes_fts.note_id
-             WHERE notes_fts MATCH ? AND notes.deleted_at IS NULL
-             ORDER BY rank
-             LIMIT 50",
-        )?;
 
-        let notes = stmt.query_map([query], |row| {
-            Ok(Note {
-                id: row.get(0)?,
-                title: row.get(1)?,
-                content: row.get(2)?,
-                folder: row.get(3)?,
-                project_id: row.get(4)?,
-                properties: row.get(5)?,
-                created_at: row.get(6)?,
-                updated_at: row.get(7)?,
-                deleted_at: row.get(8)?,
-            })
-        })?;
+    /// Copy a project with its settings and folders, and its notes unless
+    /// `include_notes` is false (for reusing the project as a template).
+    /// Chat history isn't copied.
+    pub fn duplicate_project(&self, id: &str, name: Option<&str>, include_notes: bool) -> SqlResult<Option<Project>> {
+        let Some(mut bundle) = self.export_project_bundle(id)? else {
+            return Ok(None);
+        };
 
-        notes.collect()
+        bundle.chat_sessions.clear();
+        bundle.project.archived_at = None;
+        if !include_notes {
+            bundle.notes.clear();
+            bundle.note_tags.clear();
+        }
+
+        let name = name.map(str::to_string).unwrap_or_else(|| format!("{} (copy)", bundle.project.name));
+        self.import_project_bundle(&bundle, Some(&name), &HashMap::new()).map(Some)
     }
-    
-    pub fn get_folders(&self) -> SqlResult<Vec<Folder>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT path, color, icon, sort_order FROM folders ORDER BY sort_order",
-        )?;
-        
-        let folders = stmt.query_map([], |row| {
-            Ok(Folder {
-                path: row.get(0)?,
-                color: row.get(1)?,
-                icon: row.get(2)?,
-                sort_order: row.get(3)?,
-            })
-        })?;
-        
-        folders.collect()
+
+    // Project type operations
+
+    fn project_type_from_row(row: &rusqlite::Row) -> SqlResult<ProjectType> {
+        let folders: String = row.get(5)?;
+        let starter_notes: String = row.get(6)?;
+        let properties: String = row.get(7)?;
+        let export_preset: Option<String> = row.get(8)?;
+        let bibliography: String = row.get(9)?;
+        Ok(ProjectType {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            description: row.get(2)?,
+            icon: row.get(3)?,
+            color: row.get(4)?,
+            folders: serde_json::from_str(&folders).unwrap_or_default(),
+            starter_notes: serde_json::from_str(&starter_notes).unwrap_or_default(),
+            properties: serde_json::from_str(&properties).unwrap_or_default(),
+            export_preset: export_preset.and_then(|p| serde_json::from_str(&p).ok()),
+            bibliography: BibliographyMode::parse(&bibliography),
+            builtin: row.get(10)?,
+            sort_order: row.get(11)?,
+        })
     }
 
-    // Tag operations
+    /// Insert or overwrite a type definition as given
+    fn save_project_type(&self, project_type: &ProjectType) -> SqlResult<()> {
+        let to_json = |value: serde_json::Result<String>| value.map_err(|e| invalid_input(e.to_string()));
+        let folders = to_json(serde_json::to_string(&project_type.folders))?;
+        let starter_notes = to_json(serde_json::to_string(&project_type.starter_notes))?;
+        let properties = to_json(serde_json::to_string(&project_type.properties))?;
+        let export_preset = project_type.export_preset
+            .as_ref()
+            .map(|preset| to_json(serde_json::to_string(preset)))
+            .transpose()?;
 
-    pub fn create_tag(&self, name: &str, color: Option<&str>) -> SqlResult<Tag> {
         self.conn.execute(
-            "INSERT INTO tags (name, color) VALUES (?, ?)",
-            [name, color.unwrap_or("")],
-        )?;
-        
-        let tag = self.conn.query_row(
-            "SELECT id, name, color, created_at FROM tags WHERE rowid = last_insert_rowid()",
-            [],
-            |row| {
-                Ok(Tag {
-                    id: row.get(0)?,
-                    name: row.get(1)?,
-                    color: row.get(2)?,
-                    created_at: row.get(3)?,
-                })
-            },
+            "INSERT OR REPLACE INTO project_types (id, name, description, icon, color, folders, starter_notes, properties, export_preset, bibliography, builtin, sort_order)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            params![
+                project_type.id,
+                project_type.name,
+                project_type.description,
+                project_type.icon,
+                project_type.color,
+                folders,
+                starter_notes,
+                properties,
+                export_preset,
+                project_type.bibliography.as_str(),
+                project_type.builtin,
+                project_type.sort_order,
+            ],
         )?;
-        
-        Ok(tag)
+        Ok(())
     }
 
-    pub fn get_tag(&self, id: &str) -> SqlResult<Option<Tag>> {
+    pub fn list_project_types(&self) -> SqlResult<Vec<ProjectType>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM project_types ORDER BY sort_order, name COLLATE NOCASE",
+            PROJECT_TYPE_COLUMNS
+        ))?;
+        let types = stmt.query_map([], Self::project_type_from_row)?;
+
+        types.collect()
+    }
+
+    pub fn get_project_type(&self, id: &str) -> SqlResult<Option<ProjectType>> {
         let result = self.conn.query_row(
-            "SELECT id, name, color, created_at FROM tags WHERE id = ?",
+            &format!("SELECT {} FROM project_types WHERE id = ?", PROJECT_TYPE_COLUMNS),
             [id],
-            |row| {
-                Ok(Tag {
-                    id: row.get(0)?,
-                    name: row.get(1)?,
-                    color: row.get(2)?,
-                    created_at: row.get(3)?,
-                })
-            },
+            Self::project_type_from_row,
         );
-        
+
         match result {
-            Ok(tag) => Ok(Some(tag)),
+            Ok(project_type) => Ok(Some(project_type)),
             Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
             Err(e) => Err(e),
         }
     }
 
-    pub fn get_tag_by_name(&self, name: &str) -> SqlResult<Option<Tag>> {
-        let result = self.conn.query_row(
-            "SELECT id, name, color, created_at FROM tags WHERE name = ? COLLATE NOCASE",
-            [name],
-            |row| {
-                Ok(Tag {
-                    id: row.get(0)?,
-                    name: row.get(1)?,
-                    color: row.get(2)?,
-                    created_at: row.get(3)?,
-                })
-            },
-        );
-        
-        match result {
-            Ok(tag) => Ok(Some(tag)),
-            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
-            Err(e) => Err(e),
+    /// Add a type; `id` is what projects store, so it must be a lowercase slug
+    pub fn create_project_type(&self, project_type: &ProjectType) -> SqlResult<ProjectType> {
+        let id = project_type.id.trim();
+        let valid_id = !id.is_empty()
+            && id.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_');
+        if !valid_id {
+            return Err(invalid_input(format!(
+                "Invalid project type id: {} (use lowercase letters, digits, - and _)",
+                project_type.id
+            )));
+        }
+        if self.get_project_type(id)?.is_some() {
+            return Err(invalid_input(format!("Project type already exists: {}", id)));
         }
-    }
 
-    pub fn get_all_tags(&self) -> SqlResult<Vec<serde_json::Value>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT tags.id, tags.name, tags.color, tags.created_at, COUNT(note_tags.note_id) as note_count
-             FROM tags
-             LEFT JOIN note_tags ON tags.id = note_tags.tag_id
-             GROUP BY tags.id
-             ORDER BY tags.name COLLATE NOCASE",
-        )?;
-        
-        let tags = stmt.query_map([], |row| {
-            Ok(serde_json::json!({
-                "id": row.get::<_, String>(0)?,
-                "name": row.get::<_, String>(1)?,
-                "color": row.get::<_, Option<String>>(2)?,
-                "created_at": row.get::<_, i64>(3)?,
-                "note_count": row.get::<_, i64>(4)?
-            }))
-        })?;
-        
-        tags.collect()
+        let project_type = ProjectType { id: id.to_string(), builtin: false, ..project_type.clone() };
+        self.validate_project_type(&project_type)?;
+        self.save_project_type(&project_type)?;
+        Ok(project_type)
     }
 
-    pub fn rename_tag(&self, id: &str, new_name: &str) -> SqlResult<bool> {
-        let changes = self.conn.execute(
-            "UPDATE tags SET name = ? WHERE id = ?",
-            [new_name, id],
-        )?;
-        Ok(changes > 0)
+    /// Replace a type's definition. The id and built-in flag can't change.
+    pub fn update_project_type(&self, id: &str, project_type: &ProjectType) -> SqlResult<Option<ProjectType>> {
+        let Some(existing) = self.get_project_type(id)? else {
+            return Ok(None);
+        };
+
+        let project_type = ProjectType { id: existing.id, builtin: existing.builtin, ..project_type.clone() };
+        self.validate_project_type(&project_type)?;
+        self.save_project_type(&project_type)?;
+        Ok(Some(project_type))
     }
 
-    pub fn delete_tag(&self, id: &str) -> SqlResult<bool> {
-        let changes = self.conn.execute("DELETE FROM tags WHERE id = ?", [id])?;
+    /// Delete a custom type that no project uses
+    pub fn delete_project_type(&self, id: &str) -> SqlResult<bool> {
+        let Some(project_type) = self.get_project_type(id)? else {
+            return Ok(false);
+        };
+        if project_type.builtin {
+            return Err(invalid_input(format!("Built-in project type {} can't be deleted", id)));
+        }
+        let in_use: i64 = self.conn.query_row("SELECT COUNT(*) FROM projects WHERE type = ?", [id], |row| row.get(0))?;
+        if in_use > 0 {
+            return Err(invalid_input(format!("Project type {} is used by {} projects", id, in_use)));
+        }
+
+        let changes = self.conn.execute("DELETE FROM project_types WHERE id = ?", [id])?;
         Ok(changes > 0)
     }
 
-    pub fn add_tag_to_note(&self, note_id: &str, tag_name: &str) -> SqlResult<()> {
-        let mut tag = self.get_tag_by_name(tag_name)?;
-        
-        if tag.is_none() {
-            // Generate a default color or just use null
-            let color = self.generate_tag_color(tag_name);
-            tag = Some(self.create_tag(tag_name, Some(&color))?);
+    fn validate_project_type(&self, project_type: &ProjectType) -> SqlResult<()> {
+        if project_type.name.trim().is_empty() {
+            return Err(invalid_input("Project type name cannot be empty"));
+        }
+        if let Some(note) = project_type.starter_notes.iter().find(|n| n.title.trim().is_empty()) {
+            return Err(invalid_input(format!("Starter note in {} needs a title", note.folder)));
         }
-        
-        let tag_id = tag.unwrap().id;
-        
-        self.conn.execute(
-            "INSERT OR IGNORE INTO note_tags (note_id, tag_id) VALUES (?, ?)",
-            [note_id, &tag_id],
-        )?;
-        
         Ok(())
     }
 
-    pub fn remove_tag_from_note(&self, note_id: &str, tag_id: &str) -> SqlResult<()> {
-        self.conn.execute(
-            "DELETE FROM note_tags WHERE note_id = ? AND tag_id = ?",
-            [note_id, tag_id],
-        )?;
-        Ok(())
+    /// Create the folders and starter notes of the project's type. Notes that
+    /// already exist (same title and folder in the project) are left alone, so
+    /// this can be re-run after the type changes.
+    pub fn scaffold_project(&self, project_id: &str, date: chrono::NaiveDate) -> SqlResult<Option<ProjectScaffold>> {
+        let tx = self.conn.unchecked_transaction()?;
+        let scaffold = self.apply_project_scaffold(project_id, date)?;
+        tx.commit()?;
+        Ok(scaffold)
     }
 
-    pub fn get_note_tags(&self, note_id: &str) -> SqlResult<Vec<Tag>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT tags.id, tags.name, tags.color, tags.created_at
-             FROM tags
-             JOIN note_tags ON tags.id = note_tags.tag_id
-             WHERE note_tags.note_id = ?
-             ORDER BY tags.name COLLATE NOCASE",
-        )?;
-        
-        let tags = stmt.query_map([note_id], |row| {
-            Ok(Tag {
-                id: row.get(0)?,
-                name: row.get(1)?,
-                color: row.get(2)?,
-                created_at: row.get(3)?,
-            })
-        })?;
-        
-        tags.collect()
+    /// `create_project` followed by `scaffold_project` for today; a failed scaffold
+    /// leaves no project behind
+    pub fn create_scaffolded_project(
+        &self,
+        name: &str,
+        description: Option<&str>,
+        project_type: &str,
+        color: Option<&str>,
+        icon: Option<&str>,
+        settings: Option<&str>,
+    ) -> SqlResult<Project> {
+        let tx = self.conn.unchecked_transaction()?;
+        let project = self.create_project(name, description, project_type, color, icon, settings)?;
+        self.apply_project_scaffold(&project.id, chrono::Local::now().date_naive())?;
+        tx.commit()?;
+        self.get_project(&project.id).map(|p| p.unwrap_or(project))
     }
 
-    pub fn get_notes_by_tag(&self, tag_id: &str) -> SqlResult<Vec<Note>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT notes.id, notes.title, notes.content, notes.folder, notes.project_id,
-                    notes.properties, notes.created_at, notes.updated_at, notes.deleted_at
-             FROM notes
-             JOIN note_tags ON notes.id = note_tags.note_id
-             WHERE note_tags.tag_id = ? AND notes.deleted_at IS NULL
-             ORDER BY notes.updated_at DESC",
-        )?;
+    /// `scaffold_project` without its own transaction
+    fn apply_project_scaffold(&self, project_id: &str, date: chrono::NaiveDate) -> SqlResult<Option<ProjectScaffold>> {
+        let Some(project) = self.get_project(project_id)? else {
+            return Ok(None);
+        };
+        let Some(project_type) = self.get_project_type(&project.project_type)? else {
+            return Ok(Some(ProjectScaffold::default()));
+        };
 
-        let notes = stmt.query_map([tag_id], |row| {
-            Ok(Note {
-                id: row.get(0)?,
-                title: row.get(1)?,
-                content: row.get(2)?,
-                folder: row.get(3)?,
-                project_id: row.get(4)?,
-                properties: row.get(5)?,
-                created_at: row.get(6)?,
-                updated_at: row.get(7)?,
-                deleted_at: row.get(8)?,
+        let folder_name = Self::project_folder_name(&project.name);
+        let now = chrono::Local::now().naive_local();
+        let render = |text: &str, title: &str, project_name: &str| {
+            let context = TemplateContext { title, folder: "", project_name: Some(project_name), date, now };
+            templates::render(text, &context).content
+        };
+
+        let properties: HashMap<String, Property> = project_type.properties
+            .iter()
+            .filter_map(|p| {
+                let value = p.default.clone()?;
+                Some((p.key.clone(), Property { key: p.key.clone(), prop_type: p.prop_type.clone(), value, readonly: false }))
             })
-        })?;
+            .collect();
+        let properties = match properties.is_empty() {
+            true => None,
+            false => Some(serde_json::to_string(&properties).map_err(|e| invalid_input(e.to_string()))?),
+        };
 
-        notes.collect()
-    }
+        let mut scaffold = ProjectScaffold::default();
 
-    pub fn filter_notes_by_tags(&self, tag_ids: Vec<String>, match_all: bool) -> SqlResult<Vec<Note>> {
-        if tag_ids.is_empty() {
-            return self.list_notes(None);
+        for folder in &project_type.folders {
+            let path = Self::normalize_folder_path(&render(folder, &project.name, &folder_name))?;
+            self.ensure_folder(&path)?;
+            scaffold.folders.push(path);
         }
 
-        let placeholders = vec!["?"; tag_ids.len()].join(",");
-        let columns = "notes.id, notes.title, notes.content, notes.folder, notes.project_id, notes.properties, notes.created_at, notes.updated_at, notes.deleted_at";
+        for starter in &project_type.starter_notes {
+            let title = render(&starter.title, &starter.title, &project.name);
+            let folder = Self::normalize_folder_path(&render(&starter.folder, &title, &folder_name))?;
+            let exists: bool = self.conn.query_row(
+                "SELECT COUNT(*) > 0 FROM notes WHERE project_id = ? AND folder = ? AND title = ? AND deleted_at IS NULL",
+                [project_id, &folder, &title],
+                |row| row.get(0),
+            )?;
+            if exists {
+                continue;
+            }
 
-        let sql = if match_all {
-            format!(
-                "SELECT {} FROM notes
-                 WHERE notes.deleted_at IS NULL
-                 AND notes.id IN (
-                   SELECT note_id FROM note_tags
-                   WHERE tag_id IN ({})
-                   GROUP BY note_id
-                   HAVING COUNT(DISTINCT tag_id) = ?
-                 )
-                 ORDER BY notes.updated_at DESC",
-                columns, placeholders
-            )
-        } else {
-            format!(
-                "SELECT DISTINCT {} FROM notes
-                 JOIN note_tags ON notes.id = note_tags.note_id
-                 WHERE notes.deleted_at IS NULL
-                 AND note_tags.tag_id IN ({})
-                 ORDER BY notes.updated_at DESC",
-                columns, placeholders
-            )
-        };
+            self.ensure_folder(&folder)?;
+            let content = render(&starter.content, &title, &project.name);
+            let note = self.create_note_as(&title, &content, &folder, Some(project_id), properties.as_deref(), NoteEdit::Generated)?;
+            scaffold.notes.push(note);
+        }
 
-        let mut params: Vec<Box<dyn rusqlite::ToSql>> = tag_ids
-            .into_iter()
-            .map(|id| Box::new(id) as Box<dyn rusqlite::ToSql>)
-            .collect();
+        // Citation style for the export dialog, unless the project already has one
+        if let (Some(preset), true) = (&project_type.export_preset, project_type.bibliography != BibliographyMode::None) {
+            let mut settings: serde_json::Map<String, JsonValue> = self.get_project_settings(project_id)?
+                .and_then(|s| serde_json::from_str(&s).ok())
+                .unwrap_or_default();
+            if !settings.contains_key(PROJECT_CITATION_STYLE_SETTING) {
+                settings.insert(PROJECT_CITATION_STYLE_SETTING.to_string(), JsonValue::from(preset.csl.clone()));
+                self.update_project_settings(project_id, &JsonValue::Object(settings).to_string())?;
+            }
+        }
+
+        Ok(Some(scaffold))
+    }
 
-        if match_all {
-            params.push(Box::new(params.len() as i32));
+    /// Statistics for a project's live notes, as of `today`
+    pub fn get_project_stats(&self, project_id: &str, today: chrono::NaiveDate) -> SqlResult<Option<ProjectStats>> {
+        if self.get_project(project_id)?.is_none() {
+            return Ok(None);
         }
 
-        let mut stmt = self.conn.prepare(&sql)?;
-        let notes_iter = stmt.query_map(params_from_iter(params), |row| {
-            Ok(Note {
-                id: row.get(0)?,
-                title: row.get(1)?,
-                content: row.get(2)?,
-                folder: row.get(3)?,
-                project_id: row.get(4)?,
-                properties: row.get(5)?,
-                created_at: row.get(6)?,
-                updated_at: row.get(7)?,
-                deleted_at: row.get(8)?,
-            })
-        })?;
+        let week_start = (today - chrono::Days::new(6)).to_string();
+        let timeline_start = today - chrono::Days::new(PROJECT_TIMELINE_DAYS - 1);
 
-        notes_iter.collect()
-    }
+        let (note_count, total_words, last_activity): (i64, i64, Option<i64>) = self.conn.query_row(
+            "SELECT COUNT(*), COALESCE(SUM(word_count), 0), MAX(updated_at)
+             FROM notes WHERE project_id = ? AND deleted_at IS NULL",
+            [project_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )?;
 
-    pub fn update_note_tags(&self, note_id: &str, content: &str) -> SqlResult<()> {
-        // Support hierarchical tags with / (e.g., #research/statistics/mediation)
-        let tag_regex = regex::Regex::new(r"#([a-zA-Z0-9_/-]+)").unwrap();
-        let tags: std::collections::HashSet<String> = tag_regex
-            .captures_iter(content)
-            .map(|cap| cap[1].to_string())
-            .collect();
+        let (words_added_7_days, words_added_30_days): (i64, i64) = self.conn.query_row(
+            "SELECT COALESCE(SUM(CASE WHEN writing_log.date >= ? THEN writing_log.words_added END), 0),
+                    COALESCE(SUM(writing_log.words_added), 0)
+             FROM writing_log
+             JOIN notes ON notes.id = writing_log.note_id
+             WHERE notes.project_id = ? AND writing_log.date BETWEEN ? AND ?",
+            rusqlite::params![week_start, project_id, timeline_start.to_string(), today.to_string()],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
 
-        // Get current tags
-        let current_tags = self.get_note_tags(note_id)?;
-        let current_tag_names: std::collections::HashSet<String> = current_tags.iter().map(|t| t.name.to_lowercase()).collect();
+        let tags = self.conn
+            .prepare(
+                "SELECT tags.id, tags.name, tags.color, COUNT(*)
+                 FROM note_tags
+                 JOIN tags ON tags.id = note_tags.tag_id
+                 JOIN notes ON notes.id = note_tags.note_id
+                 WHERE notes.project_id = ? AND notes.deleted_at IS NULL
+                 GROUP BY tags.id
+                 ORDER BY COUNT(*) DESC, tags.name COLLATE NOCASE",
+            )?
+            .query_map([project_id], |row| {
+                Ok(ProjectTagCount {
+                    tag_id: row.get(0)?,
+                    name: row.get(1)?,
+                    color: row.get(2)?,
+                    note_count: row.get(3)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let (link_count, internal_link_count): (i64, i64) = self.conn.query_row(
+            "SELECT COUNT(*), COALESCE(SUM(target.project_id IS source.project_id), 0)
+             FROM links
+             JOIN notes AS source ON source.id = links.source_note_id
+             JOIN notes AS target ON target.id = links.target_note_id
+             WHERE source.project_id = ? AND source.deleted_at IS NULL AND target.deleted_at IS NULL",
+            [project_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
 
-        // Add new tags
-        for tag_name in &tags {
-            if !current_tag_names.contains(&tag_name.to_lowercase()) {
-                self.add_tag_to_note(note_id, tag_name)?;
-            }
-        }
+        let (open_tasks, completed_tasks): (i64, i64) = self.conn.query_row(
+            "SELECT COALESCE(SUM(tasks.status = ' '), 0), COALESCE(SUM(tasks.checked), 0)
+             FROM tasks
+             JOIN notes ON notes.id = tasks.note_id
+             WHERE notes.project_id = ? AND notes.deleted_at IS NULL",
+            [project_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
 
-        // Remove tags no longer in content
-        for tag in current_tags {
-            if !tags.iter().any(|t| t.to_lowercase() == tag.name.to_lowercase()) {
-                self.remove_tag_from_note(note_id, &tag.id)?;
-            }
+        let timeline = self.conn
+            .prepare(
+                "SELECT day, SUM(created), SUM(words) FROM (
+                     SELECT date(created_at, 'unixepoch', 'localtime') AS day, 1 AS created, 0 AS words
+                     FROM notes
+                     WHERE project_id = ?1 AND deleted_at IS NULL AND created_at >= ?2
+                     UNION ALL
+                     SELECT writing_log.date, 0, writing_log.words_added
+                     FROM writing_log
+                     JOIN notes ON notes.id = writing_log.note_id
+                     WHERE notes.project_id = ?1 AND writing_log.date >= ?3
+                 )
+                 WHERE day <= ?4
+                 GROUP BY day
+                 HAVING SUM(created) > 0 OR SUM(words) > 0
+                 ORDER BY day",
+            )?
+            .query_map(
+                rusqlite::params![project_id, local_day_start(timeline_start), timeline_start.to_string(), today.to_string()],
+                |row| {
+                    Ok(ProjectActivityDay {
+                        date: row.get(0)?,
+                        notes_created: row.get(1)?,
+                        words_added: row.get(2)?,
+                    })
+                },
+            )?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let recent_notes = self.conn
+            .prepare(
+                "SELECT id, title, word_count, created_at, updated_at
+                 FROM notes WHERE project_id = ? AND deleted_at IS NULL
+                 ORDER BY updated_at DESC, rowid DESC
+                 LIMIT ?",
+            )?
+            .query_map(rusqlite::params![project_id, PROJECT_RECENT_NOTES as i64], |row| {
+                Ok(ProjectRecentNote {
+                    id: row.get(0)?,
+                    title: row.get(1)?,
+                    word_count: row.get(2)?,
+                    created_at: row.get(3)?,
+                    updated_at: row.get(4)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Some(ProjectStats {
+            project_id: project_id.to_string(),
+            note_count,
+            total_words,
+            words_added_7_days,
+            words_added_30_days,
+            tags,
+            link_count,
+            internal_link_count,
+            link_density: if note_count > 0 { link_count as f64 / note_count as f64 } else { 0.0 },
+            open_tasks,
+            completed_tasks,
+            last_activity,
+            timeline,
+            recent_notes,
+        }))
+    }
+
+    // Project Settings operations
+
+    pub fn get_project_settings(&self, project_id: &str) -> SqlResult<Option<String>> {
+        let result = self.conn.query_row(
+            "SELECT settings FROM project_settings WHERE project_id = ?",
+            [project_id],
+            |row| row.get(0),
+        );
+        match result {
+            Ok(settings) => Ok(Some(settings)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e),
         }
+    }
+
+    pub fn update_project_settings(&self, project_id: &str, settings: &str) -> SqlResult<()> {
+        self.conn.execute(
+            "INSERT INTO project_settings (project_id, settings) VALUES (?, ?)
+             ON CONFLICT(project_id) DO UPDATE SET settings = excluded.settings",
+            [project_id, settings],
+        )?;
+        Ok(())
+    }
+
+    // Writing progress operations
 
+    /// Add a word count change to today's writing log
+    fn log_words(&self, note_id: &str, delta: i64) -> SqlResult<()> {
+        if delta == 0 {
+            return Ok(());
+        }
+        self.conn.execute(
+            "INSERT INTO writing_log (date, note_id, words_added, words_removed) VALUES (?, ?, ?, ?)
+             ON CONFLICT(date, note_id) DO UPDATE SET
+                 words_added = words_added + excluded.words_added,
+                 words_removed = words_removed + excluded.words_removed",
+            rusqlite::params![
+                chrono::Local::now().date_naive().to_string(),
+                note_id,
+                delta.max(0),
+                (-delta).max(0),
+            ],
+        )?;
         Ok(())
     }
 
-    // Link operations
+    /// Words written per day between `from` and `to` (inclusive), only days with activity
+    pub fn get_writing_heatmap(
+        &self,
+        from: chrono::NaiveDate,
+        to: chrono::NaiveDate,
+        project_id: Option<&str>,
+    ) -> SqlResult<Vec<WritingDay>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT writing_log.date, SUM(writing_log.words_added), SUM(writing_log.words_removed)
+             FROM writing_log
+             LEFT JOIN notes ON notes.id = writing_log.note_id
+             WHERE writing_log.date BETWEEN ? AND ?
+               AND (?3 IS NULL OR notes.project_id = ?3)
+             GROUP BY writing_log.date
+             ORDER BY writing_log.date",
+        )?;
 
-    pub fn update_note_links(&self, note_id: &str, content: &str) -> SqlResult<()> {
-        let link_regex = regex::Regex::new(r"\[\[([^\]]+)\]\]").unwrap();
-        let linked_titles: Vec<String> = link_regex
-            .captures_iter(content)
-            .map(|cap| cap[1].trim().to_string())
-            .filter(|s| !s.is_empty())
+        let days = stmt.query_map(
+            rusqlite::params![from.to_string(), to.to_string(), project_id],
+            |row| {
+                Ok(WritingDay {
+                    date: row.get(0)?,
+                    words_added: row.get(1)?,
+                    words_removed: row.get(2)?,
+                })
+            },
+        )?;
+
+        days.collect()
+    }
+
+    pub fn get_writing_streak(&self, today: chrono::NaiveDate) -> SqlResult<WritingStreak> {
+        let dates: Vec<String> = self.conn
+            .prepare(
+                "SELECT date FROM writing_log
+                 GROUP BY date HAVING SUM(words_added) > 0
+                 ORDER BY date",
+            )?
+            .query_map([], |row| row.get(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+        let dates: Vec<chrono::NaiveDate> = dates
+            .iter()
+            .filter_map(|d| chrono::NaiveDate::parse_from_str(d, "%Y-%m-%d").ok())
+            .filter(|d| *d <= today)
             .collect();
 
-        // Delete existing links
-        self.conn.execute("DELETE FROM links WHERE source_note_id = ?", [note_id])?;
+        let mut streak = WritingStreak {
+            last_written: dates.last().map(|d| d.to_string()),
+            words_today: self.words_written_on(today, None)?,
+            ..Default::default()
+        };
 
-        // Add new links
-        for title in linked_titles {
-            let target_id_result: SqlResult<String> = self.conn.query_row(
-                "SELECT id FROM notes WHERE title = ? AND deleted_at IS NULL",
-                [title],
-                |row| row.get(0),
-            );
+        let mut run = 0;
+        let mut previous: Option<chrono::NaiveDate> = None;
+        for date in &dates {
+            run = match previous {
+                Some(p) if p.succ_opt() == Some(*date) => run + 1,
+                _ => 1,
+            };
+            streak.longest = streak.longest.max(run);
+            previous = Some(*date);
+        }
 
-            if let Ok(target_id) = target_id_result {
-                self.conn.execute(
-                    "INSERT OR IGNORE INTO links (source_note_id, target_note_id) VALUES (?, ?)",
-                    [note_id, &target_id],
-                )?;
+        // A streak stays alive until a whole day passes without writing
+        if let Some(last) = previous {
+            if last == today || last.succ_opt() == Some(today) {
+                streak.current = run;
             }
         }
 
-        Ok(())
+        Ok(streak)
     }
 
-    pub fn get_backlinks(&self, note_id: &str) -> SqlResult<Vec<Note>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT notes.id, notes.title, notes.content, notes.folder, notes.project_id,
-                    notes.properties, notes.created_at, notes.updated_at, notes.deleted_at
-             FROM notes
-             JOIN links ON notes.id = links.source_note_id
-             WHERE links.target_note_id = ? AND notes.deleted_at IS NULL
-             ORDER BY notes.updated_at DESC",
-        )?;
-
-        let notes = stmt.query_map([note_id], |row| {
-            Ok(Note {
-                id: row.get(0)?,
-                title: row.get(1)?,
-                content: row.get(2)?,
-                folder: row.get(3)?,
-                project_id: row.get(4)?,
-                properties: row.get(5)?,
-                created_at: row.get(6)?,
-                updated_at: row.get(7)?,
-                deleted_at: row.get(8)?,
-            })
-        })?;
-
-        notes.collect()
+    fn words_written_on(&self, date: chrono::NaiveDate, project_id: Option<&str>) -> SqlResult<i64> {
+        self.conn.query_row(
+            "SELECT COALESCE(SUM(writing_log.words_added), 0)
+             FROM writing_log
+             LEFT JOIN notes ON notes.id = writing_log.note_id
+             WHERE writing_log.date = ? AND (?2 IS NULL OR notes.project_id = ?2)",
+            rusqlite::params![date.to_string(), project_id],
+            |row| row.get(0),
+        )
     }
 
-    pub fn get_outgoing_links(&self, note_id: &str) -> SqlResult<Vec<Note>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT notes.id, notes.title, notes.content, notes.folder, notes.project_id,
-                    notes.properties, notes.created_at, notes.updated_at, notes.deleted_at
-             FROM notes
-             JOIN links ON notes.id = links.target_note_id
-             WHERE links.source_note_id = ? AND notes.deleted_at IS NULL
-             ORDER BY notes.updated_at DESC",
-        )?;
+    pub fn get_note_goal_progress(&self, note_id: &str, today: chrono::NaiveDate) -> SqlResult<Option<WritingGoalProgress>> {
+        let Some(note) = self.get_note(note_id)? else {
+            return Ok(None);
+        };
 
-        let notes = stmt.query_map([note_id], |row| {
-            Ok(Note {
-                id: row.get(0)?,
-                title: row.get(1)?,
-                content: row.get(2)?,
-                folder: row.get(3)?,
-                project_id: row.get(4)?,
-                properties: row.get(5)?,
-                created_at: row.get(6)?,
-                updated_at: row.get(7)?,
-                deleted_at: row.get(8)?,
-            })
-        })?;
+        let properties: HashMap<String, Property> = note.properties
+            .as_deref()
+            .and_then(|p| serde_json::from_str(p).ok())
+            .unwrap_or_default();
+        let word_goal = properties.get(NOTE_WORD_GOAL_PROPERTY).and_then(|p| p.value.as_f64()).map(|g| g as i64);
+        let deadline = properties.get(NOTE_DEADLINE_PROPERTY).and_then(|p| p.value.as_str()).map(String::from);
+        let (word_count, words_today): (i64, i64) = self.conn.query_row(
+            "SELECT word_count,
+                    (SELECT COALESCE(SUM(words_added), 0) FROM writing_log WHERE date = ? AND note_id = notes.id)
+             FROM notes WHERE id = ?",
+            rusqlite::params![today.to_string(), note_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
 
-        notes.collect()
+        Ok(Some(goal_progress(word_count, word_goal, deadline, words_today, today)))
     }
 
-    pub fn get_note_by_title_and_folder(&self, title: &str, folder: &str) -> SqlResult<Option<Note>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT id, title, content, folder, project_id, properties, created_at, updated_at, deleted_at
-             FROM notes WHERE title = ? AND folder = ? AND deleted_at IS NULL LIMIT 1"
+    pub fn get_project_goal_progress(&self, project_id: &str, today: chrono::NaiveDate) -> SqlResult<Option<WritingGoalProgress>> {
+        if self.get_project(project_id)?.is_none() {
+            return Ok(None);
+        }
+
+        let settings: JsonValue = self.get_project_settings(project_id)?
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default();
+        let word_goal = settings.get(PROJECT_WORD_GOAL_SETTING).and_then(JsonValue::as_f64).map(|g| g as i64);
+        let deadline = settings.get(PROJECT_DEADLINE_SETTING).and_then(JsonValue::as_str).map(String::from);
+        let word_count: i64 = self.conn.query_row(
+            "SELECT COALESCE(SUM(word_count), 0) FROM notes WHERE project_id = ? AND deleted_at IS NULL",
+            [project_id],
+            |row| row.get(0),
         )?;
-        let note = stmt.query_row([title, folder], |row| {
-            Ok(Note {
-                id: row.get(0)?,
-                title: row.get(1)?,
-                content: row.get(2)?,
-                folder: row.get(3)?,
-                project_id: row.get(4)?,
-                properties: row.get(5)?,
-                created_at: row.get(6)?,
-                updated_at: row.get(7)?,
-                deleted_at: row.get(8)?,
-            })
-        });
+        let words_today = self.words_written_on(today, Some(project_id))?;
 
-        match note {
-            Ok(n) => Ok(Some(n)),
-            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
-            Err(e) => Err(e),
-        }
+        Ok(Some(goal_progress(word_count, word_goal, deadline, words_today, today)))
     }
 
+    // Prose statistics
 
-    fn generate_tag_color(&self, name: &str) -> String {
-        let hash = name.chars().fold(0u32, |acc, c| {
-            c as u32 + ((acc << 5).wrapping_sub(acc))
-        });
-        let hue = hash % 360;
-        format!("hsl({}, 70%, 50%)", hue)
+    pub fn get_note_prose_stats(&self, note_id: &str) -> SqlResult<Option<ProseStats>> {
+        Ok(self.get_note(note_id)?.map(|note| readability::analyze(&note.content)))
     }
 
-    // Project CRUD operations
+    /// Statistics across a project's live notes
+    pub fn get_project_prose_stats(&self, project_id: &str) -> SqlResult<Option<ProseStats>> {
+        if self.get_project(project_id)?.is_none() {
+            return Ok(None);
+        }
 
-    pub fn create_project(
-        &self,
-        name: &str,
-        description: Option<&str>,
-        project_type: &str,
-        color: Option<&str>,
-        icon: Option<&str>,
-        settings: Option<&str>,
-    ) -> SqlResult<Project> {
-        self.conn.execute(
-            "INSERT INTO projects (name, description, type, color, icon, settings) VALUES (?, ?, ?, ?, ?, ?)",
-            rusqlite::params![name, description, project_type, color, icon, settings],
-        )?;
+        let contents: Vec<String> = self.conn
+            .prepare("SELECT content FROM notes WHERE project_id = ? AND deleted_at IS NULL ORDER BY created_at")?
+            .query_map([project_id], |row| row.get(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+        let contents: Vec<&str> = contents.iter().map(String::as_str).collect();
 
-        let project = self.conn.query_row(
-            "SELECT id, name, description, type, color, icon, settings, created_at, updated_at
-             FROM projects WHERE rowid = last_insert_rowid()",
-            [],
-            |row| {
-                Ok(Project {
-                    id: row.get(0)?,
-                    name: row.get(1)?,
-                    description: row.get(2)?,
-                    project_type: row.get(3)?,
-                    color: row.get(4)?,
-                    icon: row.get(5)?,
-                    settings: row.get(6)?,
-                    created_at: row.get(7)?,
-                    updated_at: row.get(8)?,
-                })
-            },
-        )?;
+        Ok(Some(readability::analyze_all(&contents)))
+    }
 
-        Ok(project)
+    // Focus session operations
+
+    fn focus_session_from_row(row: &rusqlite::Row) -> rusqlite::Result<FocusSession> {
+        Ok(FocusSession {
+            id: row.get(0)?,
+            note_id: row.get(1)?,
+            project_id: row.get(2)?,
+            planned_minutes: row.get(3)?,
+            status: row.get(4)?,
+            started_at: row.get(5)?,
+            ended_at: row.get(6)?,
+            duration_seconds: row.get(7)?,
+            words_written: row.get(8)?,
+            interruptions: row.get(9)?,
+            reflection: row.get(10)?,
+        })
     }
 
-    pub fn get_project(&self, id: &str) -> SqlResult<Option<Project>> {
+    pub fn get_focus_session(&self, id: &str) -> SqlResult<Option<FocusSession>> {
         let result = self.conn.query_row(
-            "SELECT id, name, description, type, color, icon, settings, created_at, updated_at
-             FROM projects WHERE id = ?",
+            &format!("SELECT {} FROM focus_sessions WHERE id = ?", FOCUS_SESSION_COLUMNS),
             [id],
-            |row| {
-                Ok(Project {
-                    id: row.get(0)?,
-                    name: row.get(1)?,
-                    description: row.get(2)?,
-                    project_type: row.get(3)?,
-                    color: row.get(4)?,
-                    icon: row.get(5)?,
-                    settings: row.get(6)?,
-                    created_at: row.get(7)?,
-                    updated_at: row.get(8)?,
-                })
-            },
+            Self::focus_session_from_row,
         );
 
         match result {
-            Ok(project) => Ok(Some(project)),
+            Ok(session) => Ok(Some(session)),
             Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
             Err(e) => Err(e),
         }
     }
 
-    pub fn list_projects(&self, project_type: Option<&str>) -> SqlResult<Vec<Project>> {
-        if let Some(pt) = project_type {
-            let mut stmt = self.conn.prepare(
-                "SELECT id, name, description, type, color, icon, settings, created_at, updated_at
-                 FROM projects WHERE type = ?
-                 ORDER BY name COLLATE NOCASE",
-            )?;
-
-            let projects = stmt.query_map([pt], |row| {
-                Ok(Project {
-                    id: row.get(0)?,
-                    name: row.get(1)?,
-                    description: row.get(2)?,
-                    project_type: row.get(3)?,
-                    color: row.get(4)?,
-                    icon: row.get(5)?,
-                    settings: row.get(6)?,
-                    created_at: row.get(7)?,
-                    updated_at: row.get(8)?,
-                })
-            })?;
-
-            projects.collect()
-        } else {
-            let mut stmt = self.conn.prepare(
-                "SELECT id, name, description, type, color, icon, settings, created_at, updated_at
-                 FROM projects
-                 ORDER BY name COLLATE NOCASE",
-            )?;
+    /// The running or paused session, if any
+    pub fn get_active_focus_session(&self) -> SqlResult<Option<FocusSession>> {
+        let result = self.conn.query_row(
+            &format!(
+                "SELECT {} FROM focus_sessions WHERE status != ? ORDER BY started_at DESC LIMIT 1",
+                FOCUS_SESSION_COLUMNS
+            ),
+            [FOCUS_COMPLETED],
+            Self::focus_session_from_row,
+        );
 
-            let projects = stmt.query_map([], |row| {
-                Ok(Project {
-                    id: row.get(0)?,
-                    name: row.get(1)?,
-                    description: row.get(2)?,
-                    project_type: row.get(3)?,
-                    color: row.get(4)?,
-                    icon: row.get(5)?,
-                    settings: row.get(6)?,
-                    created_at: row.get(7)?,
-                    updated_at: row.get(8)?,
-                })
-            })?;
+        match result {
+            Ok(session) => Ok(Some(session)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
 
-            projects.collect()
+    /// Words in the session's note, or across its project's notes
+    fn focus_word_count(&self, note_id: Option<&str>, project_id: Option<&str>) -> SqlResult<i64> {
+        match (note_id, project_id) {
+            (Some(note_id), _) => self.conn.query_row(
+                "SELECT COALESCE(SUM(word_count), 0) FROM notes WHERE id = ?",
+                [note_id],
+                |row| row.get(0),
+            ),
+            (None, Some(project_id)) => self.conn.query_row(
+                "SELECT COALESCE(SUM(word_count), 0) FROM notes WHERE project_id = ? AND deleted_at IS NULL",
+                [project_id],
+                |row| row.get(0),
+            ),
+            (None, None) => Ok(0),
         }
     }
 
-    pub fn update_project(
+    /// Start a session against a note (its project is recorded too) or a project.
+    /// Only one session can be active at a time.
+    pub fn start_focus_session(
         &self,
-        id: &str,
-        name: Option<&str>,
-        description: Option<&str>,
-        project_type: Option<&str>,
-        color: Option<&str>,
-        icon: Option<&str>,
-        settings: Option<&str>,
-    ) -> SqlResult<Option<Project>> {
-        let mut sql_parts = Vec::new();
-        let mut params: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
-
-        if let Some(n) = name {
-            sql_parts.push("name = ?");
-            params.push(Box::new(n.to_string()));
-        }
-        if let Some(d) = description {
-            sql_parts.push("description = ?");
-            params.push(Box::new(d.to_string()));
-        }
-        if let Some(pt) = project_type {
-            sql_parts.push("type = ?");
-            params.push(Box::new(pt.to_string()));
-        }
-        if let Some(c) = color {
-            sql_parts.push("color = ?");
-            params.push(Box::new(c.to_string()));
-        }
-        if let Some(i) = icon {
-            sql_parts.push("icon = ?");
-            params.push(Box::new(i.to_string()));
-        }
-        if let Some(s) = settings {
-            sql_parts.push("settings = ?");
-            params.push(Box::new(s.to_string()));
+        note_id: Option<&str>,
+        project_id: Option<&str>,
+        planned_minutes: Option<i64>,
+    ) -> SqlResult<FocusSession> {
+        if self.get_active_focus_session()?.is_some() {
+            return Err(invalid_input("A focus session is already in progress"));
         }
 
-        if sql_parts.is_empty() {
-            return self.get_project(id);
+        let project_id = match note_id {
+            Some(id) => match self.get_note(id)? {
+                Some(note) => project_id.map(String::from).or(note.project_id),
+                None => return Err(invalid_input(format!("Note not found: {}", id))),
+            },
+            None => project_id.map(String::from),
+        };
+        if let Some(id) = &project_id {
+            if self.get_project(id)?.is_none() {
+                return Err(invalid_input(format!("Project not found: {}", id)));
+            }
         }
 
-        sql_parts.push("updated_at = strftime('%s', 'now')");
-        params.push(Box::new(id.to_string()));
-
-        let sql = format!("UPDATE projects SET {} WHERE id = ?", sql_parts.join(", "));
-        self.conn.execute(&sql, params_from_iter(params))?;
+        let words_at_start = self.focus_word_count(note_id, project_id.as_deref())?;
+        self.conn.execute(
+            "INSERT INTO focus_sessions (note_id, project_id, planned_minutes, status, words_at_start)
+             VALUES (?, ?, ?, ?, ?)",
+            rusqlite::params![note_id, project_id, planned_minutes, FOCUS_RUNNING, words_at_start],
+        )?;
 
-        self.get_project(id)
+        self.conn.query_row(
+            &format!(
+                "SELECT {} FROM focus_sessions WHERE rowid = last_insert_rowid()",
+                FOCUS_SESSION_COLUMNS
+            ),
+            [],
+            Self::focus_session_from_row,
+        )
     }
 
-    pub fn delete_project(&self, id: &str) -> SqlResult<bool> {
-        // First, unassign all notes from this project
+    pub fn pause_focus_session(&self, id: &str) -> SqlResult<Option<FocusSession>> {
         self.conn.execute(
-            "UPDATE notes SET project_id = NULL WHERE project_id = ?",
-            [id],
+            "UPDATE focus_sessions SET status = ?, paused_at = strftime('%s', 'now')
+             WHERE id = ? AND status = ?",
+            [FOCUS_PAUSED, id, FOCUS_RUNNING],
         )?;
+        self.get_focus_session(id)
+    }
 
-        let changes = self.conn.execute("DELETE FROM projects WHERE id = ?", [id])?;
-        Ok(changes > 0)
+    pub fn resume_focus_session(&self, id: &str) -> SqlResult<Option<FocusSession>> {
+        self.conn.execute(
+            "UPDATE focus_sessions
+             SET status = ?, paused_seconds = paused_seconds + (strftime('%s', 'now') - paused_at), paused_at = NULL
+             WHERE id = ? AND status = ?",
+            [FOCUS_RUNNING, id, FOCUS_PAUSED],
+        )?;
+        self.get_focus_session(id)
     }
 
-    pub fn get_notes_by_project(&self, project_id: &str) -> SqlResult<Vec<Note>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT id, title, content, folder, project_id, properties, created_at, updated_at, deleted_at
-             FROM notes
-             WHERE project_id = ? AND deleted_at IS NULL
-             ORDER BY updated_at DESC",
+    pub fn record_focus_interruption(&self, id: &str) -> SqlResult<Option<FocusSession>> {
+        self.conn.execute(
+            "UPDATE focus_sessions SET interruptions = interruptions + 1 WHERE id = ? AND status != ?",
+            [id, FOCUS_COMPLETED],
         )?;
+        self.get_focus_session(id)
+    }
 
-        let notes = stmt.query_map([project_id], |row| {
-            Ok(Note {
-                id: row.get(0)?,
-                title: row.get(1)?,
-                content: row.get(2)?,
-                folder: row.get(3)?,
-                project_id: row.get(4)?,
-                properties: row.get(5)?,
-                created_at: row.get(6)?,
-                updated_at: row.get(7)?,
-                deleted_at: row.get(8)?,
-            })
-        })?;
+    /// Finish a session, fixing its duration and words written. Stopping a
+    /// completed session only updates the reflection.
+    pub fn stop_focus_session(&self, id: &str, reflection: Option<&str>) -> SqlResult<Option<FocusSession>> {
+        let Some(session) = self.get_focus_session(id)? else {
+            return Ok(None);
+        };
 
-        notes.collect()
-    }
+        if session.status == FOCUS_COMPLETED {
+            if reflection.is_some() {
+                self.conn.execute(
+                    "UPDATE focus_sessions SET reflection = ? WHERE id = ?",
+                    rusqlite::params![reflection, id],
+                )?;
+            }
+            return self.get_focus_session(id);
+        }
 
-    pub fn assign_note_to_project(&self, note_id: &str, project_id: Option<&str>) -> SqlResult<bool> {
-        let changes = self.conn.execute(
-            "UPDATE notes SET project_id = ?, updated_at = strftime('%s', 'now') WHERE id = ?",
-            rusqlite::params![project_id, note_id],
+        let words_now = self.focus_word_count(session.note_id.as_deref(), session.project_id.as_deref())?;
+        self.conn.execute(
+            "UPDATE focus_sessions
+             SET status = ?,
+                 ended_at = strftime('%s', 'now'),
+                 duration_seconds = MAX(0, ?),
+                 paused_seconds = paused_seconds + COALESCE(strftime('%s', 'now') - paused_at, 0),
+                 paused_at = NULL,
+                 words_written = MAX(0, ? - words_at_start),
+                 reflection = COALESCE(?, reflection)
+             WHERE id = ?",
+            rusqlite::params![FOCUS_COMPLETED, session.duration_seconds, words_now, reflection, id],
         )?;
-        Ok(changes > 0)
-    }
 
-    pub fn get_project_note_count(&self, project_id: &str) -> SqlResult<i64> {
-        self.conn.query_row(
-            "SELECT COUNT(*) FROM notes WHERE project_id = ? AND deleted_at IS NULL",
-            [project_id],
-            |row| row.get(0),
-        )
+        self.get_focus_session(id)
     }
 
-    // Project Settings operations
+    /// Completed sessions started in the week containing `date`, grouped by project
+    pub fn get_focus_week_summary(&self, date: chrono::NaiveDate) -> SqlResult<FocusWeekSummary> {
+        let start = Period::Weekly.start(date);
+        let end = Period::Weekly.end(date);
 
-    pub fn get_project_settings(&self, project_id: &str) -> SqlResult<Option<String>> {
-        let result = self.conn.query_row(
-            "SELECT settings FROM project_settings WHERE project_id = ?",
-            [project_id],
-            |row| row.get(0),
-        );
-        match result {
-            Ok(settings) => Ok(Some(settings)),
-            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
-            Err(e) => Err(e),
+        let mut stmt = self.conn.prepare(
+            "SELECT focus_sessions.project_id, projects.name,
+                    SUM(focus_sessions.duration_seconds), COUNT(*),
+                    SUM(focus_sessions.words_written), SUM(focus_sessions.interruptions)
+             FROM focus_sessions
+             LEFT JOIN projects ON projects.id = focus_sessions.project_id
+             WHERE focus_sessions.status = ?
+               AND focus_sessions.started_at >= ? AND focus_sessions.started_at < ?
+             GROUP BY focus_sessions.project_id
+             ORDER BY SUM(focus_sessions.duration_seconds) DESC",
+        )?;
+        let rows = stmt
+            .query_map(
+                rusqlite::params![FOCUS_COMPLETED, local_day_start(start), local_day_start(end + chrono::Days::new(1))],
+                |row| {
+                    Ok((
+                        FocusProjectTime {
+                            project_id: row.get(0)?,
+                            project_name: row.get(1)?,
+                            total_seconds: row.get(2)?,
+                            sessions: row.get(3)?,
+                            words_written: row.get(4)?,
+                        },
+                        row.get::<_, i64>(5)?,
+                    ))
+                },
+            )?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut summary = FocusWeekSummary {
+            start_date: start.to_string(),
+            end_date: end.to_string(),
+            total_seconds: 0,
+            sessions: 0,
+            words_written: 0,
+            interruptions: 0,
+            projects: Vec::with_capacity(rows.len()),
+        };
+        for (project, interruptions) in rows {
+            summary.total_seconds += project.total_seconds;
+            summary.sessions += project.sessions;
+            summary.words_written += project.words_written;
+            summary.interruptions += interruptions;
+            summary.projects.push(project);
         }
-    }
 
-    pub fn update_project_settings(&self, project_id: &str, settings: &str) -> SqlResult<()> {
-        self.conn.execute(
-            "INSERT INTO project_settings (project_id, settings) VALUES (?, ?)
-             ON CONFLICT(project_id) DO UPDATE SET settings = excluded.settings",
-            [project_id, settings],
-        )?;
-        Ok(())
+        Ok(summary)
     }
 
     // Chat history operations
@@ -1789,34 +6391,15 @@ What did you accomplish today?"#);
             .as_secs() as i64;
 
         // Export notes (excluding deleted)
-        let notes = self.list_notes(None)?;
+        let notes = self.list_notes(None, true)?;
 
         // Export projects
-        let mut stmt = self.conn.prepare("SELECT id, name, description, type, color, icon, settings, created_at, updated_at FROM projects")?;
-        let projects: Vec<Project> = stmt.query_map([], |row| {
-            Ok(Project {
-                id: row.get(0)?,
-                name: row.get(1)?,
-                description: row.get(2)?,
-                project_type: row.get(3)?,
-                color: row.get(4)?,
-                icon: row.get(5)?,
-                settings: row.get(6)?,
-                created_at: row.get(7)?,
-                updated_at: row.get(8)?,
-            })
-        })?.collect::<Result<Vec<_>, _>>()?;
+        let mut stmt = self.conn.prepare(&format!("SELECT {} FROM projects", PROJECT_COLUMNS))?;
+        let projects: Vec<Project> = stmt.query_map([], Self::project_from_row)?.collect::<Result<Vec<_>, _>>()?;
 
         // Export tags
-        let mut stmt = self.conn.prepare("SELECT id, name, color, created_at FROM tags")?;
-        let tags: Vec<Tag> = stmt.query_map([], |row| {
-            Ok(Tag {
-                id: row.get(0)?,
-                name: row.get(1)?,
-                color: row.get(2)?,
-                created_at: row.get(3)?,
-            })
-        })?.collect::<Result<Vec<_>, _>>()?;
+        let mut stmt = self.conn.prepare(&format!("SELECT {} FROM tags", TAG_COLUMNS))?;
+        let tags: Vec<Tag> = stmt.query_map([], Self::tag_from_row)?.collect::<Result<Vec<_>, _>>()?;
 
         // Export folders
         let mut stmt = self.conn.prepare("SELECT path, color, icon, sort_order FROM folders")?;
@@ -1839,14 +6422,21 @@ What did you accomplish today?"#);
         })?.collect::<Result<Vec<_>, _>>()?;
 
         // Export links
-        let mut stmt = self.conn.prepare("SELECT source_note_id, target_note_id FROM links")?;
+        let mut stmt = self.conn.prepare("SELECT source_note_id, target_note_id, target_title, kind, anchor, alias FROM links")?;
         let links: Vec<Link> = stmt.query_map([], |row| {
             Ok(Link {
                 source_note_id: row.get(0)?,
                 target_note_id: row.get(1)?,
+                target_title: row.get(2)?,
+                kind: row.get(3)?,
+                anchor: row.get(4)?,
+                alias: row.get(5)?,
             })
         })?.collect::<Result<Vec<_>, _>>()?;
 
+        let templates = self.list_templates()?;
+        let project_types = self.list_project_types()?;
+
         Ok(DatabaseBackup {
             version: "1.0".to_string(),
             timestamp,
@@ -1856,6 +6446,8 @@ What did you accomplish today?"#);
             folders,
             note_tags,
             links,
+            templates,
+            project_types,
         })
     }
 
@@ -1868,10 +6460,13 @@ What did you accomplish today?"#);
         // Clear existing data (except schema_version)
         tx.execute("DELETE FROM links", [])?;
         tx.execute("DELETE FROM note_tags", [])?;
+        tx.execute("DELETE FROM tasks", [])?;
         tx.execute("DELETE FROM notes_fts", [])?;
         tx.execute("DELETE FROM notes", [])?;
         tx.execute("DELETE FROM tags", [])?;
         tx.execute("DELETE FROM projects", [])?;
+        tx.execute("DELETE FROM templates", [])?;
+        tx.execute("DELETE FROM project_types WHERE builtin = 0", [])?;
         tx.execute("DELETE FROM folders WHERE path NOT IN ('inbox', 'notes', 'archive')", [])?;
 
         // Import folders
@@ -1882,27 +6477,66 @@ What did you accomplish today?"#);
             )?;
         }
 
+        // Import project types (built-ins are kept when the backup predates them)
+        for project_type in &backup.project_types {
+            self.save_project_type(project_type)?;
+        }
+
         // Import projects
         for project in backup.projects {
             tx.execute(
-                "INSERT INTO projects (id, name, description, type, color, settings, created_at, updated_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
-                rusqlite::params![&project.id, &project.name, &project.description, &project.project_type, &project.color, &project.settings, project.created_at, project.updated_at],
+                "INSERT INTO projects (id, name, description, type, color, icon, settings, created_at, updated_at, archived_at)
+                 VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                rusqlite::params![
+                    &project.id, &project.name, &project.description, &project.project_type, &project.color, &project.icon,
+                    &project.settings, project.created_at, project.updated_at, project.archived_at
+                ],
+            )?;
+        }
+
+        // Import templates
+        for template in &backup.templates {
+            tx.execute(
+                "INSERT INTO templates (id, name, description, content, properties, tags, created_at, updated_at)
+                 VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+                rusqlite::params![
+                    &template.id, &template.name, &template.description, &template.content, &template.properties,
+                    Self::template_tags_json(&template.tags)?, template.created_at, template.updated_at
+                ],
             )?;
         }
 
-        // Import tags
-        for tag in backup.tags {
+        // Import tags (parent overrides afterwards, once every tag exists)
+        for tag in &backup.tags {
+            tx.execute(
+                "INSERT INTO tags (id, name, color, created_at, description, icon, pinned, sort_order)
+                 VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+                rusqlite::params![&tag.id, &tag.name, &tag.color, tag.created_at, &tag.description, &tag.icon, tag.pinned, tag.sort_order],
+            )?;
+        }
+        for tag in backup.tags.iter().filter(|t| t.parent_id.is_some()) {
             tx.execute(
-                "INSERT INTO tags (id, name, color, created_at) VALUES (?, ?, ?, ?)",
-                rusqlite::params![&tag.id, &tag.name, &tag.color, tag.created_at],
+                "UPDATE tags SET parent_id = ? WHERE id = ?",
+                rusqlite::params![&tag.parent_id, &tag.id],
             )?;
         }
 
+        // Older backups don't record link titles, so recover them from the notes
+        let note_titles: HashMap<String, String> = backup.notes
+            .iter()
+            .map(|n| (n.id.clone(), n.title.clone()))
+            .collect();
+
         // Import notes
         for note in backup.notes {
             tx.execute(
-                "INSERT INTO notes (id, title, content, folder, project_id, properties, created_at, updated_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
-                rusqlite::params![&note.id, &note.title, &note.content, &note.folder, &note.project_id, &note.properties, note.created_at, note.updated_at],
+                "INSERT INTO notes (id, title, content, folder, project_id, properties, created_at, updated_at, word_count, pinned, favorite, archived_at)
+                 VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                rusqlite::params![
+                    &note.id, &note.title, &note.content, &note.folder, &note.project_id, &note.properties,
+                    note.created_at, note.updated_at, markdown::word_count(&note.content) as i64,
+                    note.pinned, note.favorite, note.archived_at
+                ],
             )?;
 
             // Rebuild FTS index
@@ -1910,6 +6544,8 @@ What did you accomplish today?"#);
                 "INSERT INTO notes_fts (note_id, title, content, properties) VALUES (?, ?, ?, ?)",
                 rusqlite::params![&note.id, &note.title, &note.content, note.properties.as_deref().unwrap_or("")],
             )?;
+
+            self.update_note_tasks(&note.id, &note.content)?;
         }
 
         // Import note_tags
@@ -1922,10 +6558,19 @@ What did you accomplish today?"#);
 
         // Import links
         for link in backup.links {
-            tx.execute(
-                "INSERT INTO links (source_note_id, target_note_id) VALUES (?, ?)",
-                rusqlite::params![&link.source_note_id, &link.target_note_id],
-            )?;
+            let target_title = if link.target_title.is_empty() {
+                link.target_note_id.as_ref().and_then(|id| note_titles.get(id)).cloned()
+            } else {
+                Some(link.target_title)
+            };
+
+            if let Some(target_title) = target_title {
+                tx.execute(
+                    "INSERT OR IGNORE INTO links (source_note_id, target_note_id, target_title, kind, anchor, alias)
+                     VALUES (?, ?, ?, ?, ?, ?)",
+                    rusqlite::params![&link.source_note_id, &link.target_note_id, &target_title, &link.kind, &link.anchor, &link.alias],
+                )?;
+            }
         }
 
         tx.commit()?;
@@ -0,0 +1,174 @@
+//! Knowledge graph: scope/filter types and graph metrics (degree, PageRank, communities)
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Which part of the vault to return
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum GraphScope {
+    Vault,
+    Project { id: String },
+    Tag { id: String },
+    /// The neighbourhood around one note
+    Note { id: String },
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GraphFilters {
+    /// Only include notes in these folders (or their subfolders)
+    #[serde(default)]
+    pub folders: Option<Vec<String>>,
+    /// Keep notes that have no edges inside the returned graph (default: true)
+    #[serde(default)]
+    pub include_orphans: Option<bool>,
+    /// Count `![[embeds]]` as edges (default: true)
+    #[serde(default)]
+    pub include_embeds: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GraphNode {
+    pub id: String,
+    pub title: String,
+    pub folder: String,
+    pub project_id: Option<String>,
+    pub in_degree: usize,
+    pub out_degree: usize,
+    pub degree: usize,
+    /// PageRank score; scores of all nodes sum to 1
+    pub centrality: f64,
+    /// Cluster id, numbered from 0 by descending cluster size
+    pub community: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GraphEdge {
+    pub source: String,
+    pub target: String,
+    /// Number of links from source to target
+    pub weight: usize,
+    /// "embed" when every link between the pair is an embed, otherwise "link"
+    pub kind: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Graph {
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+}
+
+const DAMPING: f64 = 0.85;
+const PAGERANK_ITERATIONS: usize = 100;
+const PAGERANK_TOLERANCE: f64 = 1e-9;
+const COMMUNITY_ITERATIONS: usize = 20;
+
+/// PageRank over directed edges `(source, target, weight)` between `n` nodes.
+/// Rank from nodes without outgoing edges is spread evenly over all nodes.
+pub fn pagerank(n: usize, edges: &[(usize, usize, f64)]) -> Vec<f64> {
+    if n == 0 {
+        return Vec::new();
+    }
+
+    let mut out_weight = vec![0.0; n];
+    for &(s, _, w) in edges {
+        out_weight[s] += w;
+    }
+
+    let base = 1.0 / n as f64;
+    let mut rank = vec![base; n];
+
+    for _ in 0..PAGERANK_ITERATIONS {
+        let dangling: f64 = (0..n).filter(|&i| out_weight[i] == 0.0).map(|i| rank[i]).sum();
+        let mut next = vec![(1.0 - DAMPING) * base + DAMPING * dangling * base; n];
+
+        for &(s, t, w) in edges {
+            next[t] += DAMPING * rank[s] * w / out_weight[s];
+        }
+
+        let delta: f64 = next.iter().zip(&rank).map(|(a, b)| (a - b).abs()).sum();
+        rank = next;
+        if delta < PAGERANK_TOLERANCE {
+            break;
+        }
+    }
+
+    rank
+}
+
+/// Cluster nodes with label propagation on the undirected, weighted graph.
+/// Deterministic: nodes are visited in index order and ties go to the lowest label.
+pub fn communities(n: usize, edges: &[(usize, usize, f64)]) -> Vec<usize> {
+    let mut neighbours: Vec<Vec<(usize, f64)>> = vec![Vec::new(); n];
+    for &(s, t, w) in edges {
+        if s != t {
+            neighbours[s].push((t, w));
+            neighbours[t].push((s, w));
+        }
+    }
+
+    let mut labels: Vec<usize> = (0..n).collect();
+    for _ in 0..COMMUNITY_ITERATIONS {
+        let mut changed = false;
+        for node in 0..n {
+            if neighbours[node].is_empty() {
+                continue;
+            }
+
+            let mut scores: HashMap<usize, f64> = HashMap::new();
+            for &(other, w) in &neighbours[node] {
+                *scores.entry(labels[other]).or_insert(0.0) += w;
+            }
+
+            let best = scores
+                .into_iter()
+                .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap().then(b.0.cmp(&a.0)))
+                .map(|(label, _)| label)
+                .unwrap_or(labels[node]);
+
+            if best != labels[node] {
+                labels[node] = best;
+                changed = true;
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+
+    // Renumber so the largest cluster is 0
+    let mut sizes: HashMap<usize, usize> = HashMap::new();
+    for &label in &labels {
+        *sizes.entry(label).or_insert(0) += 1;
+    }
+    let mut order: Vec<(usize, usize)> = sizes.into_iter().collect();
+    order.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    let renumber: HashMap<usize, usize> = order.iter().enumerate().map(|(i, (label, _))| (*label, i)).collect();
+
+    labels.iter().map(|label| renumber[label]).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pagerank_favours_linked_node() {
+        // 0 -> 2, 1 -> 2, 2 -> 0
+        let rank = pagerank(3, &[(0, 2, 1.0), (1, 2, 1.0), (2, 0, 1.0)]);
+        assert!((rank.iter().sum::<f64>() - 1.0).abs() < 1e-6);
+        assert!(rank[2] > rank[0] && rank[0] > rank[1]);
+    }
+
+    #[test]
+    fn test_communities_split_disconnected_groups() {
+        let edges = [(0, 1, 1.0), (1, 2, 1.0), (2, 0, 1.0), (3, 4, 1.0)];
+        let labels = communities(6, &edges);
+        assert_eq!(labels[0], labels[1]);
+        assert_eq!(labels[1], labels[2]);
+        assert_eq!(labels[3], labels[4]);
+        assert_ne!(labels[0], labels[3]);
+        assert_ne!(labels[5], labels[0]);
+        assert_eq!(labels[0], 0);
+    }
+}
@@ -2,6 +2,12 @@ mod database;
 mod commands;
 mod academic;
 mod terminal;
+mod markdown;
+mod graph;
+mod templates;
+mod periodic;
+mod readability;
+mod project_types;
 
 #[cfg(test)]
 mod tests;
@@ -68,25 +74,51 @@ pub fn run() {
       commands::create_note,
       commands::get_note,
       commands::list_notes,
+      commands::list_note_summaries,
       commands::update_note,
+      commands::move_note,
+      commands::move_notes,
+      commands::restore_note,
+      commands::bulk_update_notes,
       commands::delete_note,
       commands::search_notes,
+      commands::set_note_pinned,
+      commands::set_note_favorite,
+      commands::set_note_archived,
       commands::get_folders,
+      commands::create_folder,
+      commands::update_folder,
+      commands::rename_folder,
+      commands::move_folder,
+      commands::delete_folder,
       commands::get_all_tags,
       commands::create_tag,
       commands::rename_tag,
+      commands::update_tag,
+      commands::rename_tag_in_notes,
+      commands::merge_tags,
       commands::delete_tag,
       commands::add_tag_to_note,
       commands::remove_tag_from_note,
       commands::get_note_tags,
       commands::get_notes_by_tag,
       commands::filter_notes_by_tags,
+      commands::get_tag_tree,
       commands::get_tag_by_name,
       commands::get_tag,
       commands::update_note_tags,
       commands::update_note_links,
       commands::get_backlinks,
       commands::get_outgoing_links,
+      commands::get_unresolved_links,
+      commands::rename_note,
+      commands::get_note_links,
+      commands::get_link_preview,
+      commands::get_ambiguous_links,
+      commands::get_backlinks_with_context,
+      commands::get_unlinked_mentions,
+      commands::get_graph,
+      commands::get_vault_health,
       commands::run_claude,
       commands::run_gemini,
       commands::get_or_create_daily_note,
@@ -108,14 +140,63 @@ pub fn run() {
       commands::create_project,
       commands::get_project,
       commands::list_projects,
+      commands::set_project_archived,
+      commands::duplicate_project,
       commands::update_project,
       commands::delete_project,
       commands::get_notes_by_project,
       commands::assign_note_to_project,
       commands::get_project_note_count,
+      commands::get_project_stats,
+      commands::scaffold_project,
+      // Project types
+      commands::list_project_types,
+      commands::get_project_type,
+      commands::create_project_type,
+      commands::update_project_type,
+      commands::delete_project_type,
+      // Templates and periodic notes
+      commands::list_templates,
+      commands::get_template,
+      commands::create_template,
+      commands::update_template,
+      commands::delete_template,
+      commands::create_note_from_template,
+      commands::get_daily_note_template,
+      commands::set_daily_note_template,
+      commands::set_project_default_template,
+      commands::get_or_create_periodic_note,
+      commands::get_periodic_note,
+      commands::get_periodic_calendar,
+      commands::rollup_daily_note,
+      commands::get_daily_rollup_config,
+      commands::set_daily_rollup_config,
+      commands::get_periodic_configs,
+      commands::set_periodic_config,
+      // Tasks
+      commands::query_tasks,
+      commands::toggle_task,
+      // Writing progress
+      commands::get_writing_heatmap,
+      commands::get_writing_streak,
+      commands::get_note_goal_progress,
+      commands::get_project_goal_progress,
+      // Prose statistics
+      commands::get_prose_stats,
+      // Focus sessions
+      commands::start_focus_session,
+      commands::pause_focus_session,
+      commands::resume_focus_session,
+      commands::record_focus_interruption,
+      commands::stop_focus_session,
+      commands::get_active_focus_session,
+      commands::get_focus_week_summary,
       // Project settings
       commands::get_project_settings,
       commands::update_project_settings,
+      // Project bundles
+      commands::export_project_bundle,
+      commands::import_project_bundle,
       // Backup operations
       commands::create_backup,
       commands::restore_backup,
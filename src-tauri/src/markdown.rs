@@ -0,0 +1,928 @@
+//! Markdown helpers: wiki links, `#tags`, section extraction and link context
+//!
+//! Tag and link extraction runs on a real Markdown parse, so `#include` in a
+//! code block, `page#section` in a URL or `#ff0000` in inline code is ignored.
+
+use pulldown_cmark::{Event, LinkType, Options, Parser, Tag};
+use regex::Regex;
+use std::collections::HashMap;
+use std::ops::Range;
+
+lazy_static::lazy_static! {
+    static ref WIKI_LINK_RE: Regex = Regex::new(r"(!?)\[\[([^\[\]\n]+)\]\]").unwrap();
+    static ref HEADING_RE: Regex = Regex::new(r"^(#{1,6})\s+(.*?)\s*#*\s*$").unwrap();
+    static ref LIST_ITEM_RE: Regex = Regex::new(r"^\s*(?:[-*+]|\d+[.)])\s").unwrap();
+    // Hierarchical tags use `/` (e.g. #research/statistics/mediation); letters may be any script
+    static ref TAG_RE: Regex = Regex::new(r"#([\p{L}\p{M}\p{N}_/-]+)").unwrap();
+    static ref TAG_NAME_RE: Regex = Regex::new(r"^[\p{L}\p{M}\p{N}_-]+(?:/[\p{L}\p{M}\p{N}_-]+)*$").unwrap();
+    static ref HEX_COLOUR_RE: Regex = Regex::new(r"^(?:[0-9a-fA-F]{3,4}|[0-9a-fA-F]{6}|[0-9a-fA-F]{8})$").unwrap();
+    static ref BARE_URL_RE: Regex = Regex::new(r"(?i)\b(?:https?|ftp|file)://[^\s<>()\[\]]+|\bwww\.[^\s<>()\[\]]+").unwrap();
+    static ref FRONTMATTER_TAGS_RE: Regex = Regex::new(r"(?im)^tags\s*:[ \t]*(.*)$").unwrap();
+    static ref YAML_INLINE_ITEM_RE: Regex = Regex::new(r"[^,\s\[\]]+").unwrap();
+    static ref TASK_RE: Regex = Regex::new(r"^([ \t]*)[-*+][ \t]+\[(.)\][ \t]+(.*?)[ \t]*\r?$").unwrap();
+    static ref TASK_DUE_RE: Regex = Regex::new(r"(?:📅\s*|\bdue:\s*)(\d{4}-\d{2}-\d{2})").unwrap();
+    static ref SOURCE_LINK_RE: Regex = Regex::new(r"\(\[\[[^\[\]\n]+\]\]\)\s*$").unwrap();
+    static ref YAML_LIST_ITEM_RE: Regex = Regex::new(r"^[ \t]*-[ \t]+(.*?)[ \t]*$").unwrap();
+}
+
+/// Link kind as stored in `links.kind`
+pub const LINK_KIND_LINK: &str = "link";
+pub const LINK_KIND_EMBED: &str = "embed";
+
+/// A parsed `[[wiki link]]`
+///
+/// Supported forms: `[[Note]]`, `[[Note|alias]]`, `[[Note#Heading]]`,
+/// `[[Note^block-id]]` / `[[Note#^block-id]]`, and `![[Note]]` embeds.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WikiLink {
+    pub target: String,
+    /// `#Heading` or `^block-id` (marker included)
+    pub anchor: Option<String>,
+    pub alias: Option<String>,
+    /// `LINK_KIND_LINK` or `LINK_KIND_EMBED`
+    pub kind: &'static str,
+    /// Byte range of the whole link (including a leading `!`)
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Parse the inside of `[[...]]` into target, anchor and alias
+pub fn parse_link_text(inner: &str) -> (String, Option<String>, Option<String>) {
+    // `\|` is how aliases are escaped inside Markdown tables
+    let (target_part, alias) = match inner.find('|') {
+        Some(pos) => {
+            let alias = inner[pos + 1..].trim();
+            let target = inner[..pos].trim_end_matches('\\');
+            (target, if alias.is_empty() { None } else { Some(alias.to_string()) })
+        }
+        None => (inner, None),
+    };
+
+    let (target, anchor) = match target_part.find(['#', '^']) {
+        Some(pos) => {
+            let raw = target_part[pos..].trim();
+            // `#^id` is the Obsidian spelling of a block reference
+            let anchor = raw.strip_prefix("#^").map(|id| format!("^{}", id)).unwrap_or_else(|| raw.to_string());
+            let anchor = if anchor.len() > 1 { Some(anchor) } else { None };
+            (target_part[..pos].trim(), anchor)
+        }
+        None => (target_part.trim(), None),
+    };
+
+    (target.to_string(), anchor, alias)
+}
+
+/// Find all wiki links in `content`, skipping code, math and HTML.
+/// Same-note anchors (`[[#Heading]]`) are skipped too.
+pub fn parse_wiki_links(content: &str) -> Vec<WikiLink> {
+    let ignored = Masks::new(content).code;
+    WIKI_LINK_RE
+        .captures_iter(content)
+        .filter_map(|cap| {
+            let whole = cap.get(0)?;
+            if overlaps(&ignored, whole.start(), whole.end()) {
+                return None;
+            }
+            let (target, anchor, alias) = parse_link_text(&cap[2]);
+            if target.is_empty() {
+                return None;
+            }
+            Some(WikiLink {
+                target,
+                anchor,
+                alias,
+                kind: if &cap[1] == "!" { LINK_KIND_EMBED } else { LINK_KIND_LINK },
+                start: whole.start(),
+                end: whole.end(),
+            })
+        })
+        .collect()
+}
+
+/// Byte range of a parsed link's target inside `content` (`folder/Title` in `[[ folder/Title#A|b ]]`)
+pub fn link_target_range(content: &str, link: &WikiLink) -> Range<usize> {
+    let open = link.start + if link.kind == LINK_KIND_EMBED { 3 } else { 2 };
+    let inner = &content[open..link.end - 2];
+    let start = open + inner.len() - inner.trim_start().len();
+    start..start + link.target.len()
+}
+
+/// Add `#tag` to `content` unless it's already there: on the trailing line when
+/// that line holds only tags, otherwise as a new last paragraph
+pub fn append_tag(content: &str, tag: &str) -> String {
+    let has_tag = tag_spans(content).iter().any(|(s, e)| content[*s..*e].eq_ignore_ascii_case(tag));
+    if has_tag {
+        return content.to_string();
+    }
+
+    let trimmed = content.trim_end();
+    let last_line = trimmed.rsplit('\n').next().unwrap_or("");
+    let last_line_start = trimmed.len() - last_line.len();
+    let words = last_line.split_whitespace().count();
+    let line_tags = tag_spans(trimmed).iter().filter(|(s, _)| *s > last_line_start).count();
+    let tags_only = words > 0 && words == line_tags && last_line.split_whitespace().all(|w| w.starts_with('#'));
+
+    if trimmed.is_empty() {
+        format!("#{}", tag)
+    } else if tags_only {
+        format!("{} #{}", trimmed, tag)
+    } else {
+        format!("{}\n\n#{}", trimmed, tag)
+    }
+}
+
+/// Remove every `#tag` occurrence (exact tag, not its children) along with one preceding space,
+/// and its entry in a frontmatter `tags:` list
+pub fn remove_tag(content: &str, tag: &str) -> String {
+    let mut cuts: Vec<Range<usize>> = Masks::new(content)
+        .frontmatter
+        .map(|fm| frontmatter_tag_entries(content, fm))
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|entry| content[entry.name.clone()].eq_ignore_ascii_case(tag))
+        .map(|entry| entry.cut)
+        .collect();
+
+    for (start, end) in tag_spans(content) {
+        if !content[start..end].eq_ignore_ascii_case(tag) || !content[..start].ends_with('#') {
+            continue;
+        }
+        if cuts.iter().any(|cut| cut.start <= start && end <= cut.end) {
+            continue;
+        }
+        let hash = start - 1;
+        let cut = if content[..hash].ends_with(' ') { hash - 1 } else { hash };
+        cuts.push(cut..end);
+    }
+    cuts.sort_by_key(|cut| cut.start);
+
+    let mut result = String::with_capacity(content.len());
+    let mut last = 0;
+    for cut in cuts {
+        result.push_str(&content[last..cut.start.max(last)]);
+        last = last.max(cut.end);
+    }
+    result.push_str(&content[last..]);
+    result
+}
+
+/// Regions of a note that aren't prose
+struct Masks {
+    /// Fenced/indented/inline code, math, HTML, URLs and link destinations
+    code: Vec<Range<usize>>,
+    /// The YAML frontmatter block, fences included
+    frontmatter: Option<Range<usize>>,
+}
+
+impl Masks {
+    fn new(content: &str) -> Self {
+        let options = Options::ENABLE_MATH
+            | Options::ENABLE_YAML_STYLE_METADATA_BLOCKS
+            | Options::ENABLE_TABLES
+            | Options::ENABLE_STRIKETHROUGH;
+
+        let mut code = Vec::new();
+        let mut frontmatter = None;
+
+        for (event, range) in Parser::new_ext(content, options).into_offset_iter() {
+            match event {
+                Event::Start(Tag::MetadataBlock(_)) => frontmatter = Some(range),
+                Event::Start(Tag::CodeBlock(_)) | Event::Start(Tag::HtmlBlock) => code.push(range),
+                Event::Code(_) | Event::InlineMath(_) | Event::DisplayMath(_) | Event::Html(_) | Event::InlineHtml(_) => {
+                    code.push(range)
+                }
+                Event::Start(Tag::Link { link_type, .. }) | Event::Start(Tag::Image { link_type, .. }) => match link_type {
+                    LinkType::Autolink | LinkType::Email => code.push(range),
+                    // Only the `(destination)` part of `[text](destination)`
+                    _ => {
+                        if let Some(pos) = content[range.clone()].rfind("](") {
+                            code.push(range.start + pos + 2..range.end);
+                        }
+                    }
+                },
+                _ => {}
+            }
+        }
+
+        code.extend(BARE_URL_RE.find_iter(content).map(|m| m.range()));
+        Masks { code, frontmatter }
+    }
+}
+
+fn overlaps(ranges: &[Range<usize>], start: usize, end: usize) -> bool {
+    ranges.iter().any(|r| start < r.end && end > r.start)
+}
+
+/// Byte ranges of every `#tag` name in `content` (the `#` itself excluded),
+/// including entries of a frontmatter `tags:` list
+pub fn tag_spans(content: &str) -> Vec<(usize, usize)> {
+    let masks = Masks::new(content);
+    let mut ignored = masks.code;
+    ignored.extend(parse_wiki_links(content).iter().map(|l| l.start..l.end));
+    if let Some(frontmatter) = &masks.frontmatter {
+        ignored.push(frontmatter.clone());
+    }
+
+    let mut spans: Vec<(usize, usize)> = masks.frontmatter
+        .map(|fm| frontmatter_tag_entries(content, fm))
+        .unwrap_or_default()
+        .into_iter()
+        .map(|entry| (entry.name.start, entry.name.end))
+        .collect();
+
+    for cap in TAG_RE.captures_iter(content) {
+        let (Some(hash), Some(name)) = (cap.get(0), cap.get(1)) else { continue };
+        if overlaps(&ignored, hash.start(), hash.end()) {
+            continue;
+        }
+
+        // A tag starts a word: not `page#section`, `C#`, `&#123;` or `##Heading`
+        let preceded_by_word = content[..hash.start()]
+            .chars()
+            .next_back()
+            .is_some_and(|c| !c.is_whitespace() && !"([{,;\"'".contains(c));
+        if preceded_by_word {
+            continue;
+        }
+
+        let text = name.as_str().trim_end_matches('/');
+        if is_tag(text) {
+            spans.push((name.start(), name.start() + text.len()));
+        }
+    }
+
+    spans.sort();
+    spans
+}
+
+/// An entry of a frontmatter `tags:` list
+struct FrontmatterTag {
+    /// The tag name, without quotes or `#`
+    name: Range<usize>,
+    /// What to delete to drop the entry: its line in a block list, or the
+    /// item and one neighbouring comma in an inline list
+    cut: Range<usize>,
+}
+
+/// Entries of a frontmatter `tags:` key, inline (`tags: [a, b]`, `tags: a, b`) or as a YAML list
+fn frontmatter_tag_entries(content: &str, frontmatter: Range<usize>) -> Vec<FrontmatterTag> {
+    let block = &content[frontmatter.clone()];
+    let Some(cap) = FRONTMATTER_TAGS_RE.captures(block) else {
+        return Vec::new();
+    };
+    let value = cap.get(1).unwrap();
+    // (item start, item text, cut range), all relative to the block
+    let mut items: Vec<(usize, &str, Range<usize>)> = Vec::new();
+
+    if value.as_str().trim().is_empty() {
+        // Block list on the following lines
+        let mut offset = cap.get(0).unwrap().end() + 1;
+        for line in block.get(offset..).unwrap_or("").split('\n') {
+            let Some(item) = YAML_LIST_ITEM_RE.captures(line.trim_end_matches('\r')) else { break };
+            let item = item.get(1).unwrap();
+            let line_end = (offset + line.len() + 1).min(block.len());
+            items.push((offset + item.start(), item.as_str(), offset..line_end));
+            offset += line.len() + 1;
+        }
+    } else {
+        let inline = value.as_str();
+        let (inner, inner_start) = match (inline.trim().strip_prefix('['), inline.find('[')) {
+            (Some(rest), Some(open)) => (rest.trim_end().trim_end_matches(']'), value.start() + open + 1),
+            _ => (inline, value.start()),
+        };
+        for m in YAML_INLINE_ITEM_RE.find_iter(inner) {
+            let (start, end) = (inner_start + m.start(), inner_start + m.end());
+            let after = &block[end..];
+            let before = block[..start].trim_end_matches([' ', '\t']);
+            let cut = if let Some(rest) = after.trim_start_matches([' ', '\t']).strip_prefix(',') {
+                start..block.len() - rest.trim_start_matches([' ', '\t']).len()
+            } else if before.ends_with(',') {
+                before.len() - 1..end
+            } else {
+                start..end
+            };
+            items.push((start, m.as_str(), cut));
+        }
+    }
+
+    items
+        .into_iter()
+        .filter_map(|(start, raw, cut)| {
+            let trimmed = raw.trim_matches(|c| c == '"' || c == '\'');
+            let lead = raw.find(trimmed).unwrap_or(0) + usize::from(trimmed.starts_with('#'));
+            let name = trimmed.trim_start_matches('#');
+            is_tag(name).then(|| {
+                let start = frontmatter.start + start + lead;
+                FrontmatterTag {
+                    name: start..start + name.len(),
+                    cut: frontmatter.start + cut.start..frontmatter.start + cut.end,
+                }
+            })
+        })
+        .collect()
+}
+
+/// Tag text rules beyond the character set: at least one non-digit, and not a hex colour
+fn is_tag(name: &str) -> bool {
+    is_valid_tag_name(name)
+        && !name.chars().all(|c| c.is_ascii_digit() || c == '/')
+        && !(HEX_COLOUR_RE.is_match(name) && name.chars().any(|c| c.is_ascii_digit()))
+}
+
+/// Whether `name` can be written as a `#tag` (no `#`, no empty path segments)
+pub fn is_valid_tag_name(name: &str) -> bool {
+    TAG_NAME_RE.is_match(name)
+}
+
+/// If tag path `name` is `tag` or nested under it, the rest of the path (`""` or `/child`).
+/// Case is ignored, comparing lowercased text up to each `/` since case changes can alter byte lengths.
+pub fn strip_tag_prefix<'a>(name: &'a str, tag: &str) -> Option<&'a str> {
+    let tag = tag.to_lowercase();
+    name.match_indices('/')
+        .map(|(i, _)| i)
+        .chain([name.len()])
+        .find(|&end| name[..end].to_lowercase() == tag)
+        .map(|end| &name[end..])
+}
+
+/// Markdown lines paired with whether they sit inside a fenced code block
+fn lines_with_fence_state(content: &str) -> Vec<(&str, bool)> {
+    let mut in_fence = false;
+    content
+        .lines()
+        .map(|line| {
+            let trimmed = line.trim_start();
+            if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+                in_fence = !in_fence;
+                return (line, true);
+            }
+            (line, in_fence)
+        })
+        .collect()
+}
+
+fn heading_level(line: &str) -> Option<(usize, &str)> {
+    HEADING_RE.captures(line).map(|cap| {
+        let level = cap.get(1).map(|m| m.as_str().len()).unwrap_or(0);
+        (level, cap.get(2).map(|m| m.as_str()).unwrap_or(""))
+    })
+}
+
+/// Return the text an anchor refers to: the heading section for `#Heading`
+/// (up to the next heading of the same or higher level) or the block for
+/// `^block-id` (the list item or paragraph carrying the id, without the marker).
+pub fn extract_section(content: &str, anchor: &str) -> Option<String> {
+    let lines = lines_with_fence_state(content);
+
+    if let Some(block_id) = anchor.strip_prefix('^') {
+        let marker_re = Regex::new(&format!(r"\s\^{}\s*$", regex::escape(block_id))).ok()?;
+        let idx = lines.iter().position(|(line, in_fence)| !in_fence && marker_re.is_match(line))?;
+        let strip = |line: &str| marker_re.replace(line, "").trim_end().to_string();
+
+        if LIST_ITEM_RE.is_match(lines[idx].0) {
+            return Some(strip(lines[idx].0));
+        }
+
+        let mut start = idx;
+        while start > 0 && !lines[start - 1].0.trim().is_empty() {
+            start -= 1;
+        }
+        let mut block: Vec<String> = lines[start..idx].iter().map(|(l, _)| l.to_string()).collect();
+        block.push(strip(lines[idx].0));
+        return Some(block.join("\n"));
+    }
+
+    // Nested anchors (`#Chapter#Section`) point at the last heading
+    let wanted = anchor.trim_start_matches('#').rsplit('#').next()?.trim().to_lowercase();
+    if wanted.is_empty() {
+        return None;
+    }
+
+    let (start, level) = lines.iter().enumerate().find_map(|(i, (line, in_fence))| {
+        if *in_fence {
+            return None;
+        }
+        heading_level(line)
+            .filter(|(_, text)| text.trim().to_lowercase() == wanted)
+            .map(|(level, _)| (i, level))
+    })?;
+
+    let end = lines
+        .iter()
+        .enumerate()
+        .skip(start + 1)
+        .find(|(_, (line, in_fence))| !in_fence && heading_level(line).is_some_and(|(l, _)| l <= level))
+        .map(|(i, _)| i)
+        .unwrap_or(lines.len());
+
+    let section: Vec<&str> = lines[start..end].iter().map(|(l, _)| *l).collect();
+    Some(section.join("\n").trim_end().to_string())
+}
+
+/// Byte range of the block around `pos`: the list item or heading line it sits
+/// on, otherwise the surrounding paragraph (delimited by blank lines)
+pub fn context_range(content: &str, pos: usize) -> (usize, usize) {
+    let line_start = content[..pos].rfind('\n').map(|i| i + 1).unwrap_or(0);
+    let line_end = content[pos..].find('\n').map(|i| pos + i).unwrap_or(content.len());
+    let line = &content[line_start..line_end];
+    if LIST_ITEM_RE.is_match(line) || heading_level(line).is_some() {
+        return (line_start, line_end);
+    }
+
+    let is_break = |line: &str| line.trim().is_empty() || LIST_ITEM_RE.is_match(line) || heading_level(line).is_some();
+
+    let mut start = line_start;
+    while start > 0 {
+        let prev_start = content[..start - 1].rfind('\n').map(|i| i + 1).unwrap_or(0);
+        if is_break(&content[prev_start..start - 1]) {
+            break;
+        }
+        start = prev_start;
+    }
+
+    let mut end = line_end;
+    while end < content.len() {
+        let next_end = content[end + 1..].find('\n').map(|i| end + 1 + i).unwrap_or(content.len());
+        if is_break(&content[end + 1..next_end]) {
+            break;
+        }
+        end = next_end;
+    }
+
+    (start, end)
+}
+
+/// Byte ranges where `title` appears as plain text (case-insensitive, whole
+/// words), skipping wiki links, code and URLs
+pub fn find_mentions(content: &str, title: &str) -> Vec<(usize, usize)> {
+    let title = title.trim();
+    if title.is_empty() {
+        return Vec::new();
+    }
+
+    // Only demand a word boundary where the title itself starts/ends with a word character
+    let is_word = |c: Option<char>| c.is_some_and(|c| c.is_alphanumeric() || c == '_');
+    let pattern = format!(
+        "(?i){}{}{}",
+        if is_word(title.chars().next()) { r"\b" } else { "" },
+        regex::escape(title),
+        if is_word(title.chars().last()) { r"\b" } else { "" },
+    );
+    let Ok(re) = Regex::new(&pattern) else {
+        return Vec::new();
+    };
+
+    let mut ignored = Masks::new(content).code;
+    ignored.extend(parse_wiki_links(content).iter().map(|l| l.start..l.end));
+
+    re.find_iter(content)
+        .map(|m| (m.start(), m.end()))
+        .filter(|(start, end)| !overlaps(&ignored, *start, *end))
+        .collect()
+}
+
+/// A `- [ ]` checklist item outside code blocks
+#[derive(Debug, Clone, PartialEq)]
+pub struct TaskItem {
+    /// Zero-based line number
+    pub line: usize,
+    /// Byte offset of the status character between the brackets
+    pub status_offset: usize,
+    /// ' ' when open, 'x' when done, '>' when migrated to another note, ...
+    pub status: char,
+    pub indent: String,
+    pub text: String,
+}
+
+pub const TASK_OPEN: char = ' ';
+pub const TASK_MIGRATED: char = '>';
+
+/// Every checklist item in `content`, in order
+pub fn task_items(content: &str) -> Vec<TaskItem> {
+    let mut tasks = Vec::new();
+    let mut offset = 0;
+    let mut in_fence = false;
+
+    for (line_no, line) in content.split_inclusive('\n').enumerate() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+        } else if !in_fence {
+            // A checkbox with no text yet isn't a task
+            if let Some(cap) = TASK_RE.captures(line.trim_end_matches('\n')).filter(|cap| !cap[3].is_empty()) {
+                let status = cap.get(2).unwrap();
+                tasks.push(TaskItem {
+                    line: line_no,
+                    status_offset: offset + status.start(),
+                    status: status.as_str().chars().next().unwrap_or(TASK_OPEN),
+                    indent: cap[1].to_string(),
+                    text: cap[3].to_string(),
+                });
+            }
+        }
+        offset += line.len();
+    }
+
+    tasks
+}
+
+/// Obsidian Tasks priority markers, highest first
+pub const TASK_PRIORITIES: [(&str, &str); 5] = [
+    ("🔺", "highest"),
+    ("⏫", "high"),
+    ("🔼", "medium"),
+    ("🔽", "low"),
+    ("⏬", "lowest"),
+];
+
+/// Metadata written inline in a task's text
+#[derive(Debug, Clone, PartialEq)]
+pub struct TaskDetails {
+    /// The text with due date and priority markers removed
+    pub text: String,
+    /// `📅 2026-10-20` or `due:2026-10-20`
+    pub due: Option<chrono::NaiveDate>,
+    /// One of the names in `TASK_PRIORITIES`
+    pub priority: Option<&'static str>,
+    pub tags: Vec<String>,
+}
+
+pub fn task_details(text: &str) -> TaskDetails {
+    let due = TASK_DUE_RE
+        .captures(text)
+        .and_then(|cap| chrono::NaiveDate::parse_from_str(&cap[1], "%Y-%m-%d").ok());
+    let priority = TASK_PRIORITIES
+        .iter()
+        .find(|(marker, _)| text.contains(marker))
+        .map(|(_, name)| *name);
+
+    let mut tags: Vec<String> = Vec::new();
+    for (start, end) in tag_spans(text) {
+        let tag = &text[start..end];
+        if !tags.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+            tags.push(tag.to_string());
+        }
+    }
+
+    let mut clean = TASK_DUE_RE.replace_all(text, "").into_owned();
+    for (marker, _) in TASK_PRIORITIES {
+        clean = clean.replace(marker, "");
+    }
+    let clean = clean.split_whitespace().collect::<Vec<_>>().join(" ");
+
+    TaskDetails { text: clean, due, priority, tags }
+}
+
+/// Replace the status character of the given tasks with `status`
+pub fn set_task_status(content: &str, tasks: &[&TaskItem], status: char) -> String {
+    let mut result = content.to_string();
+    let mut offsets: Vec<(usize, usize)> = tasks
+        .iter()
+        .map(|t| (t.status_offset, t.status.len_utf8()))
+        .collect();
+    // Back to front so earlier offsets stay valid
+    offsets.sort_by_key(|o| std::cmp::Reverse(o.0));
+    for (offset, len) in offsets {
+        result.replace_range(offset..offset + len, status.encode_utf8(&mut [0; 4]));
+    }
+    result
+}
+
+/// Targets of inline `![](path)` images and `[text](path)` links that point at
+/// local files rather than URLs or anchors, with the target's byte range
+fn attachment_refs(content: &str) -> Vec<(Range<usize>, String)> {
+    Parser::new_ext(content, Options::empty())
+        .into_offset_iter()
+        .filter_map(|(event, range)| {
+            let dest = match event {
+                Event::Start(Tag::Image { link_type: LinkType::Inline, dest_url, .. })
+                | Event::Start(Tag::Link { link_type: LinkType::Inline, dest_url, .. }) => dest_url,
+                _ => return None,
+            };
+            let is_local = !dest.is_empty()
+                && !dest.starts_with('#')
+                && !dest.contains("://")
+                && !dest.starts_with("mailto:")
+                && !dest.starts_with("data:");
+            if !is_local {
+                return None;
+            }
+
+            // Escaped or percent-encoded targets don't appear verbatim and are skipped
+            let source = &content[range.clone()];
+            let after_text = source.rfind("](")? + 2;
+            let start = range.start + after_text + source[after_text..].find(dest.as_ref())?;
+            Some((start..start + dest.len(), dest.to_string()))
+        })
+        .collect()
+}
+
+/// Local files the note refers to, in order of first appearance
+pub fn attachment_paths(content: &str) -> Vec<String> {
+    let mut paths: Vec<String> = Vec::new();
+    for (_, path) in attachment_refs(content) {
+        if !paths.contains(&path) {
+            paths.push(path);
+        }
+    }
+    paths
+}
+
+/// Point attachment references at new locations; paths missing from `moved` are kept
+pub fn replace_attachment_paths(content: &str, moved: &HashMap<String, String>) -> String {
+    let mut result = content.to_string();
+    for (range, path) in attachment_refs(content).into_iter().rev() {
+        if let Some(new_path) = moved.get(&path) {
+            result.replace_range(range, new_path);
+        }
+    }
+    result
+}
+
+/// Whether text ends with a `([[Note]])` link, as carried-over tasks do
+pub fn ends_with_source_link(text: &str) -> bool {
+    SOURCE_LINK_RE.is_match(text)
+}
+
+/// Add `lines` at the end of the section under `heading` (a whole heading line,
+/// e.g. `## Tasks`), or as a new section at the end of the note if it has none.
+/// The result ends with a newline.
+pub fn append_to_section(content: &str, heading: &str, lines: &[String]) -> String {
+    let all = lines_with_fence_state(content);
+    let level = heading_level(heading).map_or(1, |(level, _)| level);
+    let Some(start) = all.iter().position(|(line, fenced)| !fenced && line.trim_end() == heading) else {
+        let trimmed = content.trim_end();
+        let separator = if trimmed.is_empty() { "" } else { "\n\n" };
+        return format!("{}{}{}\n\n{}\n", trimmed, separator, heading, lines.join("\n"));
+    };
+
+    let end = all[start + 1..]
+        .iter()
+        .position(|(line, fenced)| !fenced && heading_level(line).is_some_and(|(l, _)| l <= level))
+        .map_or(all.len(), |offset| start + 1 + offset);
+    let mut last = end;
+    while last > start + 1 && all[last - 1].0.trim().is_empty() {
+        last -= 1;
+    }
+
+    let mut result: Vec<&str> = all[..last].iter().map(|(line, _)| *line).collect();
+    if last == start + 1 {
+        result.push("");
+    }
+    result.extend(lines.iter().map(String::as_str));
+    if end < all.len() {
+        result.push("");
+        result.extend(all[end..].iter().map(|(line, _)| *line));
+    }
+    format!("{}\n", result.join("\n").trim_end())
+}
+
+/// The note's prose with frontmatter, code, math, HTML and URLs blanked out
+pub fn prose(content: &str) -> String {
+    let masks = Masks::new(content);
+    let mut text = content.to_string();
+    for range in masks.code.iter().chain(masks.frontmatter.iter()) {
+        // Masked ranges come from the parser, so they fall on char boundaries
+        text.replace_range(range.clone(), &" ".repeat(range.len()));
+    }
+    text
+}
+
+/// Words of prose: whitespace-separated tokens containing a letter or digit.
+/// Frontmatter, code, math and URLs don't count.
+pub fn word_count(content: &str) -> usize {
+    prose(content)
+        .split_whitespace()
+        .filter(|word| word.chars().any(char::is_alphanumeric))
+        .count()
+}
+
+/// Everything after the YAML frontmatter block (or the whole note without one)
+pub fn strip_frontmatter(content: &str) -> &str {
+    match Parser::new_ext(content, Options::ENABLE_YAML_STYLE_METADATA_BLOCKS).into_offset_iter().next() {
+        Some((Event::Start(Tag::MetadataBlock(_)), range)) => &content[range.end..],
+        _ => content,
+    }
+}
+
+/// A plain-text excerpt of at most `max_chars` characters: no frontmatter or code,
+/// heading and list markers dropped, wiki links shown as their text
+pub fn preview(content: &str, max_chars: usize) -> String {
+    let text = prose(content);
+    let text = WIKI_LINK_RE.replace_all(&text, |cap: &regex::Captures| {
+        let (target, _, alias) = parse_link_text(&cap[2]);
+        alias.unwrap_or(target)
+    });
+
+    let mut words = Vec::new();
+    for line in text.lines() {
+        let line = match heading_level(line) {
+            Some((_, heading)) => heading,
+            None => LIST_ITEM_RE.find(line).map_or(line, |m| &line[m.end()..]),
+        };
+        words.extend(line.split_whitespace().filter(|w| *w != ">"));
+    }
+
+    let joined = words.join(" ");
+    if joined.chars().count() <= max_chars {
+        return joined;
+    }
+    let cut: String = joined.chars().take(max_chars).collect();
+    let cut = match cut.rfind(' ') {
+        Some(pos) if pos > 0 => &cut[..pos],
+        _ => cut.as_str(),
+    };
+    format!("{}…", cut.trim_end_matches(|c: char| c.is_ascii_punctuation()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_wiki_link_forms() {
+        let links = parse_wiki_links("[[A]] [[B|bee]] [[C#Intro]] [[D^abc]] [[E#^def]] ![[F]] [[#Local]]");
+        let summary: Vec<_> = links
+            .iter()
+            .map(|l| (l.target.as_str(), l.anchor.as_deref(), l.alias.as_deref(), l.kind))
+            .collect();
+
+        assert_eq!(summary, vec![
+            ("A", None, None, LINK_KIND_LINK),
+            ("B", None, Some("bee"), LINK_KIND_LINK),
+            ("C", Some("#Intro"), None, LINK_KIND_LINK),
+            ("D", Some("^abc"), None, LINK_KIND_LINK),
+            ("E", Some("^def"), None, LINK_KIND_LINK),
+            ("F", None, None, LINK_KIND_EMBED),
+        ]);
+        assert_eq!(links[5].start, 49);
+    }
+
+    #[test]
+    fn test_extract_heading_section() {
+        let content = "# Title\n\n## Methods\nWe did things.\n```\n# not a heading\n```\n### Detail\nMore.\n## Results\nNumbers.";
+        assert_eq!(
+            extract_section(content, "#methods").unwrap(),
+            "## Methods\nWe did things.\n```\n# not a heading\n```\n### Detail\nMore."
+        );
+        assert_eq!(extract_section(content, "#Title#Results").unwrap(), "## Results\nNumbers.");
+        assert!(extract_section(content, "#Missing").is_none());
+    }
+
+    #[test]
+    fn test_extract_block() {
+        let content = "First line\nsecond line ^para1\n\n- item one\n- item two ^item2";
+        assert_eq!(extract_section(content, "^para1").unwrap(), "First line\nsecond line");
+        assert_eq!(extract_section(content, "^item2").unwrap(), "- item two");
+        assert!(extract_section(content, "^nope").is_none());
+    }
+
+    #[test]
+    fn test_context_range() {
+        let content = "Intro line\nsee [[Target]] here\n\n- item with [[Target]]\n- other";
+        let pos = content.find("[[Target]]").unwrap();
+        let (start, end) = context_range(content, pos);
+        assert_eq!(&content[start..end], "Intro line\nsee [[Target]] here");
+
+        let pos = content.rfind("[[Target]]").unwrap();
+        let (start, end) = context_range(content, pos);
+        assert_eq!(&content[start..end], "- item with [[Target]]");
+    }
+
+    #[test]
+    fn test_find_mentions() {
+        let content = "Deep Work is good. [[Deep Work]] again.\n```\ndeep work\n```\ndeep workers, deep work.";
+        let mentions: Vec<&str> = find_mentions(content, "Deep Work").iter().map(|(s, e)| &content[*s..*e]).collect();
+        assert_eq!(mentions, vec!["Deep Work", "deep work"]);
+        assert_eq!(find_mentions(content, "Deep Work")[1].0, content.rfind("deep work").unwrap());
+    }
+
+    #[test]
+    fn test_tag_spans() {
+        let content = "#research/stats and #todo, not a heading\n## Heading";
+        let tags: Vec<&str> = tag_spans(content).iter().map(|(s, e)| &content[*s..*e]).collect();
+        assert_eq!(tags, vec!["research/stats", "todo"]);
+        assert!(is_valid_tag_name("a/b-c_d"));
+        assert!(!is_valid_tag_name("a//b") && !is_valid_tag_name("/a") && !is_valid_tag_name("a b"));
+    }
+
+    #[test]
+    fn test_tags_skip_code_urls_and_headings() {
+        let content = "Real #todo and #café/menu and #统计.\n\
+            ```c\n#include <stdio.h>\n```\n\
+            Inline `#ff0000`, colour #ff0000, issue #123, C# code.\n\
+            See https://example.com/page#section and [docs](http://x.org/#anchor) and <https://a.b/#c>.\n\
+            ##NoSpace heading, $x #notmath$, <span>#html</span>, [[Note#Heading]]\n";
+        let tags: Vec<&str> = tag_spans(content).iter().map(|(s, e)| &content[*s..*e]).collect();
+        assert_eq!(tags, vec!["todo", "café/menu", "统计"]);
+    }
+
+    #[test]
+    fn test_frontmatter_tags() {
+        let inline = "---\ntitle: X\ntags: [alpha, \"#beta\", gamma/delta]\n---\nBody #body";
+        let tags: Vec<&str> = tag_spans(inline).iter().map(|(s, e)| &inline[*s..*e]).collect();
+        assert_eq!(tags, vec!["alpha", "beta", "gamma/delta", "body"]);
+
+        let list = "---\ntags:\n  - one\n  - 'two'\nother: 3\n---\ntext";
+        let tags: Vec<&str> = tag_spans(list).iter().map(|(s, e)| &list[*s..*e]).collect();
+        assert_eq!(tags, vec!["one", "two"]);
+    }
+
+    #[test]
+    fn test_wiki_links_skip_code() {
+        let links = parse_wiki_links("[[Real]] `[[Inline]]`\n```\n[[Fenced]]\n```\n");
+        assert_eq!(links.len(), 1);
+        assert_eq!(links[0].target, "Real");
+    }
+
+    #[test]
+    fn test_append_and_remove_tag() {
+        assert_eq!(append_tag("Body text\n", "todo"), "Body text\n\n#todo");
+        assert_eq!(append_tag("Body\n\n#a #b", "c"), "Body\n\n#a #b #c");
+        assert_eq!(append_tag("Has #todo inline", "todo"), "Has #todo inline");
+        assert_eq!(append_tag("", "x"), "#x");
+
+        assert_eq!(remove_tag("Body #todo here #todo/sub\n#todo", "todo"), "Body here #todo/sub\n");
+        assert_eq!(remove_tag("---\ntags: [todo]\n---\nBody #todo", "todo"), "---\ntags: []\n---\nBody");
+        assert_eq!(remove_tag("---\ntags: [a, \"#todo\", b]\n---\n", "todo"), "---\ntags: [a, b]\n---\n");
+        assert_eq!(remove_tag("---\ntags: a, todo\n---\n", "todo"), "---\ntags: a\n---\n");
+        assert_eq!(remove_tag("---\ntags:\n  - one\n  - '#todo'\ntitle: X\n---\n", "todo"), "---\ntags:\n  - one\ntitle: X\n---\n");
+    }
+
+    #[test]
+    fn test_word_count_skips_code_and_frontmatter() {
+        let content = "---\ntags: [a]\n---\n# Title\n\nOne two - three `inline code`.\n\n```\nlet x = 1;\n```\nSee https://example.com now";
+        assert_eq!(word_count(content), 6);
+        assert_eq!(word_count(""), 0);
+    }
+
+    #[test]
+    fn test_preview() {
+        let content = "---\ntitle: x\n---\n# Heading\n\n- See [[Other Note|the other]] and [[Plain]]\n\n```\ncode\n```\nEnd.";
+        assert_eq!(preview(content, 200), "Heading See the other and Plain End.");
+        assert_eq!(preview("alpha beta gamma", 12), "alpha beta…");
+        assert_eq!(strip_frontmatter("---\ntitle: x\n---\nBody"), "\nBody");
+        assert_eq!(strip_frontmatter("Body\n---\ntitle: x\n---\n"), "Body\n---\ntitle: x\n---\n");
+    }
+
+    #[test]
+    fn test_task_items_and_status() {
+        let content = "- [ ] Open\n  * [x] Done\n```\n- [ ] In code\n```\n- [>] Moved\n+ [ ] Último café\n- [] not a task\n- [ ] ";
+        let tasks = task_items(content);
+        let summary: Vec<_> = tasks.iter().map(|t| (t.line, t.status, t.indent.as_str(), t.text.as_str())).collect();
+        assert_eq!(summary, vec![
+            (0, ' ', "", "Open"),
+            (1, 'x', "  ", "Done"),
+            (5, '>', "", "Moved"),
+            (6, ' ', "", "Último café"),
+        ]);
+
+        let open: Vec<&TaskItem> = tasks.iter().filter(|t| t.status == TASK_OPEN).collect();
+        let marked = set_task_status(content, &open, TASK_MIGRATED);
+        assert!(marked.starts_with("- [>] Open\n"));
+        assert!(marked.contains("+ [>] Último café"));
+        assert!(marked.contains("- [ ] In code"));
+    }
+
+    #[test]
+    fn test_attachment_paths() {
+        let content = "![Plot](figures/plot.png) [data](/home/me/data.csv) [site](https://example.com)\n\
+                       [top](#intro) ![again](figures/plot.png) `![code](skip.png)`";
+        assert_eq!(attachment_paths(content), vec!["figures/plot.png", "/home/me/data.csv"]);
+
+        let moved = HashMap::from([("figures/plot.png".to_string(), "/bundle/1-plot.png".to_string())]);
+        assert_eq!(
+            replace_attachment_paths(content, &moved),
+            "![Plot](/bundle/1-plot.png) [data](/home/me/data.csv) [site](https://example.com)\n\
+             [top](#intro) ![again](/bundle/1-plot.png) `![code](skip.png)`"
+        );
+    }
+
+    #[test]
+    fn test_append_to_section() {
+        let lines = vec!["- b".to_string()];
+        assert_eq!(append_to_section("# Day\n", "## Tasks", &lines), "# Day\n\n## Tasks\n\n- b\n");
+        assert_eq!(
+            append_to_section("## Tasks\n\n- a\n\n## Notes\n\nText\n", "## Tasks", &lines),
+            "## Tasks\n\n- a\n- b\n\n## Notes\n\nText\n"
+        );
+        assert_eq!(append_to_section("## Tasks\n", "## Tasks", &lines), "## Tasks\n\n- b\n");
+        assert!(ends_with_source_link("Call bank ([[2025-06-10]])"));
+        assert!(!ends_with_source_link("Read [[Paper]] again"));
+    }
+
+    #[test]
+    fn test_task_details() {
+        let details = task_details("Submit draft 📅 2026-10-20 ⏫ #thesis/writing");
+        assert_eq!(details.text, "Submit draft #thesis/writing");
+        assert_eq!(details.due, chrono::NaiveDate::from_ymd_opt(2026, 10, 20));
+        assert_eq!(details.priority, Some("high"));
+        assert_eq!(details.tags, vec!["thesis/writing".to_string()]);
+
+        let details = task_details("Pay rent due:2026-11-01");
+        assert_eq!(details.text, "Pay rent");
+        assert_eq!(details.due, chrono::NaiveDate::from_ymd_opt(2026, 11, 1));
+        assert_eq!(details.priority, None);
+        assert_eq!(task_details("Bad date due:2026-13-45").due, None);
+    }
+}
@@ -0,0 +1,112 @@
+//! Periodic notes: daily, weekly, monthly, quarterly and yearly
+//!
+//! Every period is identified by its first day (weeks start on Monday, ISO style),
+//! which is what the database keys periodic notes on.
+
+use chrono::{Datelike, Days, Months, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Period {
+    Daily,
+    Weekly,
+    Monthly,
+    Quarterly,
+    Yearly,
+}
+
+impl Period {
+    pub const ALL: [Period; 5] = [Period::Daily, Period::Weekly, Period::Monthly, Period::Quarterly, Period::Yearly];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Period::Daily => "daily",
+            Period::Weekly => "weekly",
+            Period::Monthly => "monthly",
+            Period::Quarterly => "quarterly",
+            Period::Yearly => "yearly",
+        }
+    }
+
+    /// Folder used until the user configures another one
+    pub fn default_folder(self) -> &'static str {
+        self.as_str()
+    }
+
+    /// Title format (Moment-style tokens, see `templates::format_date`)
+    pub fn default_title_format(self) -> &'static str {
+        match self {
+            Period::Daily => "YYYY-MM-DD",
+            Period::Weekly => "GGGG-[W]ww",
+            Period::Monthly => "YYYY-MM",
+            Period::Quarterly => "YYYY-[Q]Q",
+            Period::Yearly => "YYYY",
+        }
+    }
+
+    /// First day of the period containing `date`
+    pub fn start(self, date: NaiveDate) -> NaiveDate {
+        match self {
+            Period::Daily => date,
+            Period::Weekly => date - Days::new(u64::from(date.weekday().num_days_from_monday())),
+            Period::Monthly => date.with_day(1).unwrap(),
+            Period::Quarterly => NaiveDate::from_ymd_opt(date.year(), date.month0() / 3 * 3 + 1, 1).unwrap(),
+            Period::Yearly => NaiveDate::from_ymd_opt(date.year(), 1, 1).unwrap(),
+        }
+    }
+
+    /// Last day of the period containing `date`
+    pub fn end(self, date: NaiveDate) -> NaiveDate {
+        self.shift(self.start(date), 1) - Days::new(1)
+    }
+
+    /// Start of the period `offset` periods away from the one containing `date`
+    pub fn shift(self, date: NaiveDate, offset: i32) -> NaiveDate {
+        let start = self.start(date);
+        let steps = offset.unsigned_abs();
+        let (days, months) = match self {
+            Period::Daily => (steps as u64, 0),
+            Period::Weekly => (steps as u64 * 7, 0),
+            Period::Monthly => (0, steps),
+            Period::Quarterly => (0, steps * 3),
+            Period::Yearly => (0, steps * 12),
+        };
+        let shifted = if offset >= 0 {
+            start.checked_add_days(Days::new(days)).and_then(|d| d.checked_add_months(Months::new(months)))
+        } else {
+            start.checked_sub_days(Days::new(days)).and_then(|d| d.checked_sub_months(Months::new(months)))
+        };
+        shifted.unwrap_or(start)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn test_period_bounds() {
+        let date = day(2025, 5, 15); // Thursday
+        assert_eq!(Period::Weekly.start(date), day(2025, 5, 12));
+        assert_eq!(Period::Weekly.end(date), day(2025, 5, 18));
+        assert_eq!(Period::Monthly.end(date), day(2025, 5, 31));
+        assert_eq!(Period::Quarterly.start(date), day(2025, 4, 1));
+        assert_eq!(Period::Quarterly.end(date), day(2025, 6, 30));
+        assert_eq!(Period::Yearly.end(date), day(2025, 12, 31));
+        assert_eq!(Period::Daily.end(date), date);
+    }
+
+    #[test]
+    fn test_period_shift() {
+        assert_eq!(Period::Daily.shift(day(2025, 3, 1), -1), day(2025, 2, 28));
+        assert_eq!(Period::Weekly.shift(day(2025, 1, 1), -1), day(2024, 12, 23));
+        assert_eq!(Period::Monthly.shift(day(2025, 1, 31), 1), day(2025, 2, 1));
+        assert_eq!(Period::Quarterly.shift(day(2025, 11, 5), 1), day(2026, 1, 1));
+        assert_eq!(Period::Yearly.shift(day(2025, 6, 1), -2), day(2023, 1, 1));
+    }
+}
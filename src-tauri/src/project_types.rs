@@ -0,0 +1,221 @@
+//! Project types: what a new project of each kind starts with
+//!
+//! Types live in the `project_types` table, so adding one is a data change.
+//! The built-in types below are seeded by migration 022 and can be edited but
+//! not deleted. Folder paths and starter notes are rendered as templates, so
+//! `{{project.name}}` and `{{date}}` work in both.
+
+use crate::academic::ExportFormat;
+use crate::database::PropertyType;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+
+pub const DEFAULT_PROJECT_TYPE: &str = "generic";
+
+/// Where a project's citations come from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BibliographyMode {
+    /// No citations
+    #[default]
+    None,
+    /// The app-wide bibliography file
+    Global,
+    /// The project's own `bibliographyPath` setting
+    Project,
+}
+
+impl BibliographyMode {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            BibliographyMode::None => "none",
+            BibliographyMode::Global => "global",
+            BibliographyMode::Project => "project",
+        }
+    }
+
+    pub fn parse(value: &str) -> Self {
+        match value {
+            "global" => BibliographyMode::Global,
+            "project" => BibliographyMode::Project,
+            _ => BibliographyMode::None,
+        }
+    }
+}
+
+/// Defaults for the export dialog
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportPreset {
+    pub format: ExportFormat,
+    /// Citation style, e.g. `apa`
+    pub csl: String,
+    #[serde(default)]
+    pub include_metadata: bool,
+    #[serde(default)]
+    pub process_equations: bool,
+}
+
+/// A note created with every new project of a type
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StarterNote {
+    pub title: String,
+    pub folder: String,
+    #[serde(default)]
+    pub content: String,
+}
+
+/// A property suggested for the project's notes; starter notes get the default
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectTypeProperty {
+    pub key: String,
+    #[serde(rename = "type")]
+    pub prop_type: PropertyType,
+    #[serde(default)]
+    pub default: Option<JsonValue>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectType {
+    /// Stored in `projects.type`, e.g. `research`
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub icon: Option<String>,
+    #[serde(default)]
+    pub color: Option<String>,
+    #[serde(default)]
+    pub folders: Vec<String>,
+    #[serde(default)]
+    pub starter_notes: Vec<StarterNote>,
+    #[serde(default)]
+    pub properties: Vec<ProjectTypeProperty>,
+    #[serde(default)]
+    pub export_preset: Option<ExportPreset>,
+    #[serde(default)]
+    pub bibliography: BibliographyMode,
+    #[serde(default)]
+    pub builtin: bool,
+    #[serde(default)]
+    pub sort_order: i64,
+}
+
+fn property(key: &str, prop_type: PropertyType, default: Option<JsonValue>) -> ProjectTypeProperty {
+    ProjectTypeProperty { key: key.to_string(), prop_type, default }
+}
+
+fn starter(title: &str, folder: &str, content: &str) -> StarterNote {
+    StarterNote { title: title.to_string(), folder: folder.to_string(), content: content.to_string() }
+}
+
+fn preset(format: ExportFormat, csl: &str, process_equations: bool) -> Option<ExportPreset> {
+    Some(ExportPreset { format, csl: csl.to_string(), include_metadata: true, process_equations })
+}
+
+/// The types that used to be fixed by the `projects.type` CHECK constraint
+pub fn builtin() -> Vec<ProjectType> {
+    let base = |id: &str, name: &str, description: &str, icon: &str, sort_order: i64| ProjectType {
+        id: id.to_string(),
+        name: name.to_string(),
+        description: Some(description.to_string()),
+        icon: Some(icon.to_string()),
+        color: None,
+        folders: Vec::new(),
+        starter_notes: Vec::new(),
+        properties: Vec::new(),
+        export_preset: None,
+        bibliography: BibliographyMode::None,
+        builtin: true,
+        sort_order,
+    };
+
+    vec![
+        ProjectType {
+            folders: vec![
+                "{{project.name}}/literature-notes".to_string(),
+                "{{project.name}}/manuscript".to_string(),
+                "{{project.name}}/analysis".to_string(),
+            ],
+            starter_notes: vec![
+                starter(
+                    "Manuscript Outline",
+                    "{{project.name}}/manuscript",
+                    "# {{project.name}}: Manuscript Outline\n\n## Abstract\n\n## Introduction\n\n## Methods\n\n## Results\n\n## Discussion\n\n## References\n",
+                ),
+                starter(
+                    "Literature Notes",
+                    "{{project.name}}/literature-notes",
+                    "# Literature Notes\n\nOne note per source, cited as `[@key]`.\n\n## To read\n\nAdd a `- [ ]` item for each source.\n",
+                ),
+            ],
+            properties: vec![
+                property("status", PropertyType::List, Some(JsonValue::from(vec!["draft"]))),
+                property("due", PropertyType::Date, None),
+                property("word_goal", PropertyType::Number, None),
+            ],
+            export_preset: preset(ExportFormat::Pdf, "apa", true),
+            bibliography: BibliographyMode::Project,
+            ..base("research", "Research", "Papers, theses and grant proposals", "Beaker", 0)
+        },
+        ProjectType {
+            folders: vec![
+                "{{project.name}}/lectures".to_string(),
+                "{{project.name}}/assignments".to_string(),
+            ],
+            starter_notes: vec![starter(
+                "Syllabus",
+                "{{project.name}}",
+                "# {{project.name}} Syllabus\n\n## Overview\n\n## Schedule\n\n| Week | Topic | Reading |\n|------|-------|---------|\n\n## Assessment\n",
+            )],
+            properties: vec![
+                property("week", PropertyType::Number, None),
+                property("course", PropertyType::Text, None),
+            ],
+            export_preset: preset(ExportFormat::Html, "apa", true),
+            bibliography: BibliographyMode::Global,
+            ..base("teaching", "Teaching", "Courses, lectures and assignments", "GraduationCap", 1)
+        },
+        ProjectType {
+            folders: vec!["{{project.name}}/vignettes".to_string(), "{{project.name}}/design".to_string()],
+            starter_notes: vec![starter(
+                "Package Roadmap",
+                "{{project.name}}",
+                "# {{project.name}} Roadmap\n\n## Functions\n\nList each exported function as a `- [ ]` item.\n\n## Documentation\n\n- [ ] README\n- [ ] Vignette\n\n## Release checklist\n\n- [ ] R CMD check\n- [ ] NEWS.md\n",
+            )],
+            export_preset: preset(ExportFormat::Html, "apa", false),
+            ..base("r-package", "R Package", "Developing and documenting an R package", "Package", 2)
+        },
+        ProjectType {
+            folders: vec!["{{project.name}}/scripts".to_string()],
+            ..base("r-dev", "R Development", "Analysis scripts and R experiments", "Code2", 3)
+        },
+        base("generic", "Generic", "Anything else", "Folder", 4),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_builtin_types() {
+        let types = builtin();
+        let ids: HashSet<&str> = types.iter().map(|t| t.id.as_str()).collect();
+        assert_eq!(ids, HashSet::from(["research", "teaching", "r-package", "r-dev", DEFAULT_PROJECT_TYPE]));
+
+        let research = types.iter().find(|t| t.id == "research").unwrap();
+        assert!(research.folders.iter().any(|f| f.ends_with("/literature-notes")));
+        assert!(research.starter_notes.iter().any(|n| n.title == "Manuscript Outline"));
+        assert_eq!(research.bibliography, BibliographyMode::Project);
+    }
+
+    #[test]
+    fn test_bibliography_mode_round_trip() {
+        for mode in [BibliographyMode::None, BibliographyMode::Global, BibliographyMode::Project] {
+            assert_eq!(BibliographyMode::parse(mode.as_str()), mode);
+        }
+        assert_eq!(BibliographyMode::parse("unknown"), BibliographyMode::None);
+    }
+}
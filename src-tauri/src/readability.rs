@@ -0,0 +1,394 @@
+//! Prose statistics and readability scores for notes
+//!
+//! Only body prose is analysed: frontmatter, code, math, HTML, URLs, Pandoc
+//! citations (`[@key, p. 3]`, `@key`), headings and table rows are left out.
+//! Syllables are estimated from vowel groups, so scores are approximate for
+//! unusual words, as in most readability tools.
+
+use crate::markdown;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+lazy_static::lazy_static! {
+    static ref BRACKET_CITATION_RE: Regex = Regex::new(r"\[[^\[\]\n]*@[^\[\]\n]*\]").unwrap();
+    static ref CITATION_RE: Regex = Regex::new(r"(^|[^\w@])-?@[\p{L}\p{N}_][\p{L}\p{N}_:.#$%&+?<>~/-]*").unwrap();
+    static ref WIKI_LINK_RE: Regex = Regex::new(r"!?\[\[(?:[^\[\]|\n]*\|)?([^\[\]\n]*)\]\]").unwrap();
+    static ref HEADING_RE: Regex = Regex::new(r"^#{1,6}(?:\s|$)").unwrap();
+    static ref LIST_ITEM_RE: Regex = Regex::new(r"^(?:[-*+]|\d+[.)])\s+(?:\[.\]\s+)?").unwrap();
+}
+
+/// Average adult silent reading speed
+const WORDS_PER_MINUTE: f64 = 238.0;
+const TOP_REPEATED_WORDS: usize = 10;
+
+const ABBREVIATIONS: [&str; 22] = [
+    "e.g", "i.e", "etc", "et al", "al", "vs", "cf", "viz", "approx", "dr", "mr", "mrs", "ms", "prof",
+    "fig", "figs", "eq", "eqs", "p", "pp", "no", "vol",
+];
+
+const BE_FORMS: [&str; 8] = ["am", "is", "are", "was", "were", "be", "been", "being"];
+
+const IRREGULAR_PARTICIPLES: [&str; 40] = [
+    "been", "born", "bought", "brought", "built", "caught", "chosen", "done", "drawn", "driven",
+    "eaten", "fallen", "felt", "found", "given", "gone", "grown", "held", "hidden", "kept",
+    "known", "laid", "led", "left", "lost", "made", "meant", "paid", "put", "read",
+    "said", "seen", "sent", "set", "shown", "sold", "taken", "taught", "thought", "written",
+];
+
+const FILLER_WORDS: [&str; 16] = [
+    "actually", "basically", "certainly", "clearly", "definitely", "essentially", "extremely", "just",
+    "literally", "obviously", "quite", "rather", "really", "simply", "somewhat", "very",
+];
+
+const FILLER_PHRASES: [&[&str]; 4] = [&["in", "order", "to"], &["kind", "of"], &["sort", "of"], &["a", "lot"]];
+
+const STOP_WORDS: [&str; 96] = [
+    "about", "above", "after", "again", "against", "all", "also", "and", "any", "are", "because",
+    "been", "before", "being", "below", "between", "both", "but", "can", "could", "did", "does",
+    "doing", "down", "during", "each", "few", "for", "from", "further", "had", "has", "have",
+    "having", "her", "here", "hers", "him", "his", "how", "into", "its", "itself", "more", "most",
+    "not", "now", "off", "once", "only", "other", "our", "out", "over", "own", "same", "she",
+    "should", "some", "such", "than", "that", "the", "their", "them", "then", "there", "these",
+    "they", "this", "those", "through", "too", "under", "until", "upon", "was", "were", "what",
+    "when", "where", "which", "while", "who", "whom", "why", "will", "with", "would", "you",
+    "your", "may", "might", "must", "one", "two",
+];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WordCount {
+    pub word: String,
+    pub count: usize,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ProseStats {
+    pub words: usize,
+    pub sentences: usize,
+    pub paragraphs: usize,
+    pub reading_time_seconds: u64,
+    pub average_sentence_length: f64,
+    /// Words in the longest sentence
+    pub longest_sentence: usize,
+    /// Letters and digits per word
+    pub average_word_length: f64,
+    /// Readability scores; `None` when there is no prose to score
+    pub flesch_reading_ease: Option<f64>,
+    pub flesch_kincaid_grade: Option<f64>,
+    pub gunning_fog: Option<f64>,
+    pub smog_index: Option<f64>,
+    pub coleman_liau_index: Option<f64>,
+    pub automated_readability_index: Option<f64>,
+    /// Sentences with a form of "to be" followed by a past participle
+    pub passive_sentences: usize,
+    /// Filler words and phrases, most frequent first
+    pub filler_words: Vec<WordCount>,
+    /// Most repeated words, function and filler words excluded
+    pub repeated_words: Vec<WordCount>,
+}
+
+/// Statistics for one note's Markdown
+pub fn analyze(content: &str) -> ProseStats {
+    analyze_all(&[content])
+}
+
+/// Statistics for several notes taken together, e.g. all notes in a project
+pub fn analyze_all(contents: &[&str]) -> ProseStats {
+    let mut paragraphs = Vec::new();
+    for content in contents {
+        paragraphs.extend(paragraphs_of(&plain_prose(content)));
+    }
+
+    let mut stats = ProseStats::default();
+    let mut syllables = 0;
+    let mut polysyllables = 0;
+    let mut characters = 0;
+    let mut frequencies: HashMap<String, usize> = HashMap::new();
+    let mut fillers: HashMap<String, usize> = HashMap::new();
+
+    for paragraph in &paragraphs {
+        let sentences: Vec<Vec<String>> = paragraph.iter().flat_map(|segment| sentences_of(segment)).collect();
+        if sentences.is_empty() {
+            continue;
+        }
+        stats.paragraphs += 1;
+
+        for sentence in &sentences {
+            stats.sentences += 1;
+            stats.words += sentence.len();
+            stats.longest_sentence = stats.longest_sentence.max(sentence.len());
+
+            let lower: Vec<String> = sentence.iter().map(|w| w.to_lowercase()).collect();
+            if is_passive(&lower) {
+                stats.passive_sentences += 1;
+            }
+            count_fillers(&lower, &mut fillers);
+
+            for word in &lower {
+                let count = syllable_count(word);
+                syllables += count;
+                if count >= 3 {
+                    polysyllables += 1;
+                }
+                characters += word.chars().filter(|c| c.is_alphanumeric()).count();
+                if word.chars().count() >= 3
+                    && word.chars().any(char::is_alphabetic)
+                    && !STOP_WORDS.contains(&word.as_str())
+                    && !FILLER_WORDS.contains(&word.as_str())
+                {
+                    *frequencies.entry(word.clone()).or_default() += 1;
+                }
+            }
+        }
+    }
+
+    stats.filler_words = ranked(fillers, 1, usize::MAX);
+    stats.repeated_words = ranked(frequencies, 2, TOP_REPEATED_WORDS);
+    if stats.words == 0 {
+        return stats;
+    }
+
+    let words = stats.words as f64;
+    let sentences = stats.sentences as f64;
+    let words_per_sentence = words / sentences;
+    let syllables_per_word = syllables as f64 / words;
+
+    stats.reading_time_seconds = (words / WORDS_PER_MINUTE * 60.0).ceil() as u64;
+    stats.average_sentence_length = round1(words_per_sentence);
+    stats.average_word_length = round1(characters as f64 / words);
+    stats.flesch_reading_ease = Some(round1(206.835 - 1.015 * words_per_sentence - 84.6 * syllables_per_word));
+    stats.flesch_kincaid_grade = Some(round1(0.39 * words_per_sentence + 11.8 * syllables_per_word - 15.59));
+    stats.gunning_fog = Some(round1(0.4 * (words_per_sentence + 100.0 * polysyllables as f64 / words)));
+    stats.smog_index = Some(round1(1.043 * (polysyllables as f64 * 30.0 / sentences).sqrt() + 3.1291));
+    stats.coleman_liau_index = Some(round1(
+        0.0588 * (characters as f64 / words * 100.0) - 0.296 * (sentences / words * 100.0) - 15.8,
+    ));
+    stats.automated_readability_index = Some(round1(
+        4.71 * (characters as f64 / words) + 0.5 * words_per_sentence - 21.43,
+    ));
+
+    stats
+}
+
+/// Body text with citations removed and wiki links reduced to their text
+fn plain_prose(content: &str) -> String {
+    let text = markdown::prose(content);
+    let text = BRACKET_CITATION_RE.replace_all(&text, " ");
+    let text = CITATION_RE.replace_all(&text, "$1");
+    WIKI_LINK_RE.replace_all(&text, "$1").into_owned()
+}
+
+/// Paragraphs as lists of segments; a segment (a list item, say) always ends a sentence
+fn paragraphs_of(text: &str) -> Vec<Vec<String>> {
+    let mut paragraphs = Vec::new();
+    let mut current: Vec<String> = Vec::new();
+
+    for line in text.lines() {
+        let mut line = line.trim();
+        while let Some(rest) = line.strip_prefix('>') {
+            line = rest.trim_start();
+        }
+
+        if line.is_empty() || HEADING_RE.is_match(line) || line.starts_with('|') {
+            if !current.is_empty() {
+                paragraphs.push(std::mem::take(&mut current));
+            }
+            continue;
+        }
+
+        match LIST_ITEM_RE.find(line) {
+            Some(marker) => current.push(line[marker.end()..].to_string()),
+            None => match current.last_mut() {
+                Some(segment) => {
+                    segment.push(' ');
+                    segment.push_str(line);
+                }
+                None => current.push(line.to_string()),
+            },
+        }
+    }
+    if !current.is_empty() {
+        paragraphs.push(current);
+    }
+
+    paragraphs
+}
+
+/// Sentences of a segment, each as its words with surrounding punctuation trimmed
+fn sentences_of(segment: &str) -> Vec<Vec<String>> {
+    let mut sentences = Vec::new();
+    let mut sentence = Vec::new();
+
+    for token in segment.split_whitespace() {
+        let word = token.trim_matches(|c: char| !c.is_alphanumeric());
+        if !word.is_empty() {
+            sentence.push(word.to_string());
+        }
+        if ends_sentence(token) && !sentence.is_empty() {
+            sentences.push(std::mem::take(&mut sentence));
+        }
+    }
+    if !sentence.is_empty() {
+        sentences.push(sentence);
+    }
+
+    sentences
+}
+
+fn ends_sentence(token: &str) -> bool {
+    let token = token.trim_end_matches(|c: char| "\"'”’»)]}*_".contains(c));
+    if token.ends_with(['!', '?', '…']) {
+        return true;
+    }
+    if !token.ends_with('.') {
+        return false;
+    }
+
+    // `e.g.`, `et al.`, `Fig.` and initials such as `J.` don't end a sentence
+    let core = token
+        .trim_start_matches(|c: char| !c.is_alphanumeric())
+        .trim_end_matches('.')
+        .to_lowercase();
+    let is_initial = core.chars().count() == 1 && core.chars().all(char::is_alphabetic);
+    !(is_initial || ABBREVIATIONS.contains(&core.as_str()))
+}
+
+fn is_passive(words: &[String]) -> bool {
+    words.iter().enumerate().any(|(i, word)| {
+        if !BE_FORMS.contains(&word.as_str()) {
+            return false;
+        }
+        let mut next = words.get(i + 1);
+        // "was quickly written"
+        if next.is_some_and(|w| w.ends_with("ly")) {
+            next = words.get(i + 2);
+        }
+        next.is_some_and(|w| is_participle(w))
+    })
+}
+
+fn is_participle(word: &str) -> bool {
+    (word.len() > 4 && word.ends_with("ed")) || IRREGULAR_PARTICIPLES.contains(&word)
+}
+
+fn count_fillers(words: &[String], counts: &mut HashMap<String, usize>) {
+    for (i, word) in words.iter().enumerate() {
+        if FILLER_WORDS.contains(&word.as_str()) {
+            *counts.entry(word.clone()).or_default() += 1;
+        }
+        for phrase in FILLER_PHRASES {
+            let matches = words.len() >= i + phrase.len()
+                && phrase.iter().zip(&words[i..]).all(|(p, w)| p == w);
+            if matches {
+                *counts.entry(phrase.join(" ")).or_default() += 1;
+            }
+        }
+    }
+}
+
+/// Estimated syllables: vowel groups, less a silent final `e` or `-ed`
+fn syllable_count(word: &str) -> usize {
+    let letters: Vec<char> = word.chars().filter(char::is_ascii_alphabetic).collect();
+    if letters.is_empty() {
+        return 1;
+    }
+
+    let is_vowel = |c: char| "aeiouy".contains(c);
+    let mut count = 0;
+    let mut previous_vowel = false;
+    for &c in &letters {
+        let vowel = is_vowel(c);
+        if vowel && !previous_vowel {
+            count += 1;
+        }
+        previous_vowel = vowel;
+    }
+
+    let n = letters.len();
+    if n > 2 && count > 1 {
+        let (last, before) = (letters[n - 1], letters[n - 2]);
+        let silent_e = last == 'e' && before != 'l' && !is_vowel(before);
+        let silent_ed = before == 'e' && last == 'd' && n > 3 && !"td".contains(letters[n - 3]);
+        if silent_e || silent_ed {
+            count -= 1;
+        }
+    }
+
+    count.max(1)
+}
+
+fn ranked(counts: HashMap<String, usize>, min_count: usize, limit: usize) -> Vec<WordCount> {
+    let mut ranked: Vec<WordCount> = counts
+        .into_iter()
+        .filter(|(_, count)| *count >= min_count)
+        .map(|(word, count)| WordCount { word, count })
+        .collect();
+    ranked.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.word.cmp(&b.word)));
+    ranked.truncate(limit);
+    ranked
+}
+
+fn round1(value: f64) -> f64 {
+    (value * 10.0).round() / 10.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counts_skip_code_math_citations_and_frontmatter() {
+        let content = "---\ntitle: Draft\n---\n# Introduction\n\nThe model was fitted to the data [@smith2020, p. 4]. \
+            It converged quickly, as @jones2019 predicted!\n\n```r\nfit <- lm(y ~ x)\n```\n\n\
+            Inline $x^2$ math and `code` are ignored. See e.g. Fig. 2 for details.\n\n- First item\n- Second item";
+        let stats = analyze(content);
+
+        assert_eq!(stats.paragraphs, 3);
+        assert_eq!(stats.sentences, 6);
+        // 7 + 5 + 5 + 6 + 2 + 2
+        assert_eq!(stats.words, 27);
+        assert_eq!(stats.longest_sentence, 7);
+        // "was fitted", "are ignored"
+        assert_eq!(stats.passive_sentences, 2);
+        assert_eq!(stats.reading_time_seconds, 7);
+        assert!(stats.flesch_reading_ease.is_some());
+    }
+
+    #[test]
+    fn test_readability_scores() {
+        let stats = analyze("The cat sat on the mat. The dog ran to the park.");
+        assert_eq!(stats.sentences, 2);
+        assert_eq!(stats.average_sentence_length, 6.0);
+        // All one-syllable words
+        assert_eq!(stats.flesch_reading_ease, Some(116.1));
+        assert_eq!(stats.gunning_fog, Some(2.4));
+
+        let dense = analyze("Heterogeneous methodological considerations necessitate comprehensive evaluation.");
+        assert!(dense.flesch_reading_ease.unwrap() < 0.0);
+        assert!(dense.flesch_kincaid_grade.unwrap() > 15.0);
+
+        assert_eq!(analyze("```\ncode only\n```").flesch_reading_ease, None);
+    }
+
+    #[test]
+    fn test_syllable_count() {
+        for (word, expected) in [("cat", 1), ("make", 1), ("table", 2), ("wanted", 2), ("walked", 1), ("readability", 5), ("rhythm", 1), ("42", 1)] {
+            assert_eq!(syllable_count(word), expected, "{}", word);
+        }
+    }
+
+    #[test]
+    fn test_fillers_and_repeated_words() {
+        let stats = analyze(
+            "We really just want to test mediation. In order to test mediation we basically need a lot of data. \
+             Mediation analysis is really kind of hard.",
+        );
+        assert_eq!(stats.filler_words[0], WordCount { word: "really".into(), count: 2 });
+        let fillers: Vec<&str> = stats.filler_words.iter().map(|f| f.word.as_str()).collect();
+        assert_eq!(fillers, vec!["really", "a lot", "basically", "in order to", "just", "kind of"]);
+
+        assert_eq!(stats.repeated_words[0], WordCount { word: "mediation".into(), count: 3 });
+        assert_eq!(stats.repeated_words[1], WordCount { word: "test".into(), count: 2 });
+        assert_eq!(stats.repeated_words.len(), 2);
+    }
+}
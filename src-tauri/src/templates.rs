@@ -0,0 +1,179 @@
+//! Note templates: `{{placeholder}}` substitution for new notes
+//!
+//! Supported placeholders:
+//! - `{{title}}`, `{{folder}}`, `{{project.name}}`
+//! - `{{date}}` / `{{date:FORMAT}}` - the note's date (default `YYYY-MM-DD`)
+//! - `{{time}}` / `{{time:FORMAT}}` - the current time (default `HH:mm`)
+//! - `{{cursor}}` - removed; its position is returned so the editor can place the caret
+//!
+//! Formats use Moment-style tokens (`YYYY`, `Q`, `MMMM`, `DD`, `dddd`, `HH`, `GGGG`, `ww`, ...);
+//! text in `[brackets]` is copied as is. Unknown placeholders are left untouched.
+
+use chrono::{Datelike, NaiveDate, NaiveDateTime, Timelike};
+use regex::Regex;
+
+lazy_static::lazy_static! {
+    static ref PLACEHOLDER_RE: Regex = Regex::new(r"\{\{\s*([A-Za-z_.]+)(?::([^}]*))?\s*\}\}").unwrap();
+}
+
+const DEFAULT_DATE_FORMAT: &str = "YYYY-MM-DD";
+const DEFAULT_TIME_FORMAT: &str = "HH:mm";
+
+/// Longest tokens first so `YYYY` wins over `YY`
+const TOKENS: [&str; 22] = [
+    "YYYY", "YY", "GGGG", "Q", "MMMM", "MMM", "MM", "M", "DD", "D", "dddd", "ddd",
+    "HH", "H", "hh", "h", "mm", "ss", "A", "a", "ww", "w",
+];
+
+/// Values available to placeholders
+pub struct TemplateContext<'a> {
+    pub title: &'a str,
+    pub folder: &'a str,
+    pub project_name: Option<&'a str>,
+    /// The note's date, e.g. the day of a daily note
+    pub date: NaiveDate,
+    pub now: NaiveDateTime,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rendered {
+    pub content: String,
+    /// Char offset of the first `{{cursor}}`, if any
+    pub cursor: Option<usize>,
+}
+
+/// Substitute every placeholder in `template`
+pub fn render(template: &str, ctx: &TemplateContext) -> Rendered {
+    let mut content = String::with_capacity(template.len());
+    let mut cursor = None;
+    let mut last = 0;
+
+    for cap in PLACEHOLDER_RE.captures_iter(template) {
+        let whole = cap.get(0).unwrap();
+        content.push_str(&template[last..whole.start()]);
+        last = whole.end();
+
+        let format = cap.get(2).map(|m| m.as_str().trim()).filter(|f| !f.is_empty());
+        match cap[1].to_ascii_lowercase().as_str() {
+            "title" => content.push_str(ctx.title),
+            "folder" => content.push_str(ctx.folder),
+            "project.name" => content.push_str(ctx.project_name.unwrap_or("")),
+            "date" => {
+                let at = ctx.date.and_time(ctx.now.time());
+                content.push_str(&format_date(at, format.unwrap_or(DEFAULT_DATE_FORMAT)));
+            }
+            "time" => content.push_str(&format_date(ctx.now, format.unwrap_or(DEFAULT_TIME_FORMAT))),
+            "cursor" => {
+                if cursor.is_none() {
+                    cursor = Some(content.chars().count());
+                }
+            }
+            _ => content.push_str(whole.as_str()),
+        }
+    }
+    content.push_str(&template[last..]);
+
+    Rendered { content, cursor }
+}
+
+/// Format `at` with Moment-style tokens
+pub fn format_date(at: NaiveDateTime, format: &str) -> String {
+    let mut out = String::new();
+    let mut rest = format;
+
+    while !rest.is_empty() {
+        if let Some(literal) = rest.strip_prefix('[') {
+            let end = literal.find(']').unwrap_or(literal.len());
+            out.push_str(&literal[..end]);
+            rest = literal.get(end + 1..).unwrap_or("");
+            continue;
+        }
+
+        match TOKENS.iter().find(|token| rest.starts_with(*token)) {
+            Some(token) => {
+                out.push_str(&format_token(at, token));
+                rest = &rest[token.len()..];
+            }
+            None => {
+                let c = rest.chars().next().unwrap();
+                out.push(c);
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+    }
+
+    out
+}
+
+fn format_token(at: NaiveDateTime, token: &str) -> String {
+    let hour12 = match at.hour() % 12 {
+        0 => 12,
+        h => h,
+    };
+    match token {
+        "YYYY" => format!("{:04}", at.year()),
+        "YY" => format!("{:02}", at.year() % 100),
+        // ISO week-numbering year, for titles like `2025-W01`
+        "GGGG" => format!("{:04}", at.iso_week().year()),
+        "Q" => (at.month0() / 3 + 1).to_string(),
+        "MMMM" => at.format("%B").to_string(),
+        "MMM" => at.format("%b").to_string(),
+        "MM" => format!("{:02}", at.month()),
+        "M" => at.month().to_string(),
+        "DD" => format!("{:02}", at.day()),
+        "D" => at.day().to_string(),
+        "dddd" => at.format("%A").to_string(),
+        "ddd" => at.format("%a").to_string(),
+        "HH" => format!("{:02}", at.hour()),
+        "H" => at.hour().to_string(),
+        "hh" => format!("{:02}", hour12),
+        "h" => hour12.to_string(),
+        "mm" => format!("{:02}", at.minute()),
+        "ss" => format!("{:02}", at.second()),
+        "A" => if at.hour() < 12 { "AM" } else { "PM" }.to_string(),
+        "a" => if at.hour() < 12 { "am" } else { "pm" }.to_string(),
+        "ww" => format!("{:02}", at.iso_week().week()),
+        "w" => at.iso_week().week().to_string(),
+        _ => token.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context() -> TemplateContext<'static> {
+        let date = NaiveDate::from_ymd_opt(2025, 3, 7).unwrap();
+        TemplateContext {
+            title: "Weekly sync",
+            folder: "meetings",
+            project_name: Some("Thesis"),
+            date,
+            now: date.and_hms_opt(14, 5, 9).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_render_placeholders() {
+        let rendered = render(
+            "# {{title}} ({{project.name}})\n{{date}} · {{date:dddd, MMMM D}} · {{time}} · {{ date:[Week] ww }}\n\n{{cursor}}{{unknown}}",
+            &context(),
+        );
+        assert_eq!(
+            rendered.content,
+            "# Weekly sync (Thesis)\n2025-03-07 · Friday, March 7 · 14:05 · Week 10\n\n{{unknown}}"
+        );
+        assert_eq!(rendered.cursor, Some(rendered.content.chars().count() - "{{unknown}}".len()));
+    }
+
+    #[test]
+    fn test_format_date_tokens() {
+        let at = context().now;
+        assert_eq!(format_date(at, "YY/M/D h:mm:ss A"), "25/3/7 2:05:09 PM");
+        assert_eq!(format_date(at, "ddd D MMM YYYY"), "Fri 7 Mar 2025");
+        assert_eq!(format_date(at, "YYYY-[Q]Q"), "2025-Q1");
+
+        let new_year = NaiveDate::from_ymd_opt(2024, 12, 30).unwrap().and_hms_opt(0, 0, 0).unwrap();
+        assert_eq!(format_date(new_year, "GGGG-[W]ww"), "2025-W01");
+    }
+}
@@ -0,0 +1,141 @@
+// Bulk note operation tests
+// Tests for applying several operations to many notes in one transaction
+
+use crate::database::{BulkNoteOperation, PropertyType};
+use serde_json::json;
+use super::setup_test_db;
+
+#[test]
+fn test_bulk_move_tag_and_assign() {
+    let (db, _temp_dir) = setup_test_db();
+    let project = db.create_project("Thesis", None, "research", None, None, None).unwrap();
+    let a = db.create_note("A", "First note #draft", "inbox", None, None).unwrap();
+    let b = db.create_note("B", "Second note", "inbox", None, None).unwrap();
+
+    let results = db.bulk_update_notes(
+        &[a.id.clone(), b.id.clone()],
+        &[
+            BulkNoteOperation::MoveFolder { folder: "research/papers".to_string() },
+            BulkNoteOperation::AssignProject { project_id: Some(project.id.clone()) },
+            BulkNoteOperation::AddTag { tag: "reviewed".to_string() },
+            BulkNoteOperation::RemoveTag { tag: "draft".to_string() },
+        ],
+    ).unwrap();
+    assert!(results.iter().all(|r| r.success));
+
+    for id in [&a.id, &b.id] {
+        let note = db.get_note(id).unwrap().unwrap();
+        assert_eq!(note.folder, "research/papers");
+        assert_eq!(note.project_id.as_deref(), Some(project.id.as_str()));
+        assert!(note.content.contains("#reviewed"));
+        assert!(!note.content.contains("#draft"));
+        let tags: Vec<String> = db.get_note_tags(id).unwrap().into_iter().map(|t| t.name).collect();
+        assert_eq!(tags, vec!["reviewed".to_string()]);
+    }
+    assert!(db.get_folders().unwrap().iter().any(|f| f.path == "research/papers"));
+}
+
+#[test]
+fn test_bulk_remove_frontmatter_tag() {
+    let (db, _temp_dir) = setup_test_db();
+    let note = db.create_note("A", "---\ntags: [\"#draft\", stats]\n---\nBody", "inbox", None, None).unwrap();
+
+    let results = db.bulk_update_notes(
+        std::slice::from_ref(&note.id),
+        &[BulkNoteOperation::RemoveTag { tag: "draft".to_string() }],
+    ).unwrap();
+    assert!(results[0].success);
+
+    assert_eq!(db.get_note(&note.id).unwrap().unwrap().content, "---\ntags: [stats]\n---\nBody");
+    let tags: Vec<String> = db.get_note_tags(&note.id).unwrap().into_iter().map(|t| t.name).collect();
+    assert_eq!(tags, vec!["stats".to_string()]);
+}
+
+#[test]
+fn test_bulk_reports_missing_notes_per_note() {
+    let (db, _temp_dir) = setup_test_db();
+    let a = db.create_note("A", "Body", "inbox", None, None).unwrap();
+
+    let results = db.bulk_update_notes(
+        &[a.id.clone(), "missing".to_string()],
+        &[BulkNoteOperation::Trash],
+    ).unwrap();
+    assert!(results[0].success);
+    assert!(!results[1].success);
+    assert!(results[1].error.as_deref().unwrap().contains("Note not found"));
+    assert!(db.get_note(&a.id).unwrap().is_none());
+
+    let results = db.bulk_update_notes(std::slice::from_ref(&a.id), &[BulkNoteOperation::Restore]).unwrap();
+    assert!(results[0].success);
+    assert!(db.get_note(&a.id).unwrap().is_some());
+}
+
+#[test]
+fn test_bulk_failed_note_rolls_back_its_changes() {
+    let (db, _temp_dir) = setup_test_db();
+    let locked = db.create_note(
+        "Locked",
+        "Body",
+        "inbox",
+        None,
+        Some(r#"{"created":{"key":"created","type":"date","value":"2024-01-01","readonly":true}}"#),
+    ).unwrap();
+    let open = db.create_note("Open", "Body", "inbox", None, None).unwrap();
+
+    let results = db.bulk_update_notes(
+        &[locked.id.clone(), open.id.clone()],
+        &[
+            BulkNoteOperation::MoveFolder { folder: "archive".to_string() },
+            BulkNoteOperation::SetProperty {
+                key: "created".to_string(),
+                property_type: PropertyType::Date,
+                value: json!("2025-02-02"),
+            },
+        ],
+    ).unwrap();
+
+    assert!(!results[0].success);
+    assert!(results[0].error.as_deref().unwrap().contains("read-only"));
+    assert_eq!(db.get_note(&locked.id).unwrap().unwrap().folder, "inbox");
+
+    assert!(results[1].success);
+    let open = db.get_note(&open.id).unwrap().unwrap();
+    assert_eq!(open.folder, "archive");
+    assert!(open.properties.unwrap().contains("2025-02-02"));
+
+    // A null value removes the property
+    db.bulk_update_notes(
+        std::slice::from_ref(&open.id),
+        &[BulkNoteOperation::SetProperty {
+            key: "created".to_string(),
+            property_type: PropertyType::Date,
+            value: serde_json::Value::Null,
+        }],
+    ).unwrap();
+    assert!(!db.get_note(&open.id).unwrap().unwrap().properties.unwrap().contains("created"));
+
+    // ...but not a read-only one
+    let results = db.bulk_update_notes(
+        std::slice::from_ref(&locked.id),
+        &[BulkNoteOperation::SetProperty {
+            key: "created".to_string(),
+            property_type: PropertyType::Date,
+            value: serde_json::Value::Null,
+        }],
+    ).unwrap();
+    assert!(!results[0].success);
+    assert!(db.get_note(&locked.id).unwrap().unwrap().properties.unwrap().contains("2024-01-01"));
+}
+
+#[test]
+fn test_bulk_rejects_unknown_project_up_front() {
+    let (db, _temp_dir) = setup_test_db();
+    let a = db.create_note("A", "Body", "inbox", None, None).unwrap();
+
+    let result = db.bulk_update_notes(
+        std::slice::from_ref(&a.id),
+        &[BulkNoteOperation::AssignProject { project_id: Some("nope".to_string()) }],
+    );
+    assert!(result.is_err());
+    assert!(db.bulk_update_notes(&[a.id], &[BulkNoteOperation::AddTag { tag: "bad tag".to_string() }]).is_err());
+}
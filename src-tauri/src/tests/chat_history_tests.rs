@@ -33,14 +33,23 @@ mod chat_history_tests {
                 properties TEXT,
                 created_at INTEGER DEFAULT (strftime('%s', 'now')),
                 updated_at INTEGER DEFAULT (strftime('%s', 'now')),
-                deleted_at INTEGER
+                deleted_at INTEGER,
+                word_count INTEGER NOT NULL DEFAULT 0,
+                pinned INTEGER NOT NULL DEFAULT 0,
+                favorite INTEGER NOT NULL DEFAULT 0,
+                archived_at INTEGER
             );
 
             CREATE TABLE IF NOT EXISTS tags (
                 id TEXT PRIMARY KEY DEFAULT (lower(hex(randomblob(16)))),
                 name TEXT UNIQUE NOT NULL,
                 color TEXT DEFAULT '#3b82f6',
-                created_at INTEGER DEFAULT (strftime('%s', 'now'))
+                created_at INTEGER DEFAULT (strftime('%s', 'now')),
+                description TEXT,
+                icon TEXT,
+                pinned INTEGER NOT NULL DEFAULT 0,
+                sort_order INTEGER NOT NULL DEFAULT 0,
+                parent_id TEXT REFERENCES tags(id) ON DELETE SET NULL
             );
 
             CREATE TABLE IF NOT EXISTS note_tags (
@@ -52,11 +61,39 @@ mod chat_history_tests {
             );
 
             CREATE TABLE IF NOT EXISTS links (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
                 source_note_id TEXT NOT NULL,
-                target_note_id TEXT NOT NULL,
-                PRIMARY KEY (source_note_id, target_note_id),
+                target_note_id TEXT,
+                target_title TEXT NOT NULL,
+                kind TEXT NOT NULL DEFAULT 'link',
+                anchor TEXT,
+                alias TEXT,
                 FOREIGN KEY (source_note_id) REFERENCES notes(id) ON DELETE CASCADE,
-                FOREIGN KEY (target_note_id) REFERENCES notes(id) ON DELETE CASCADE
+                FOREIGN KEY (target_note_id) REFERENCES notes(id) ON DELETE SET NULL
+            );
+
+            CREATE UNIQUE INDEX IF NOT EXISTS idx_links_unique ON links(
+                source_note_id, target_title, kind, COALESCE(anchor, ''), COALESCE(alias, '')
+            );
+
+            CREATE TABLE IF NOT EXISTS tasks (
+                note_id TEXT NOT NULL,
+                line INTEGER NOT NULL,
+                text TEXT NOT NULL,
+                status TEXT NOT NULL DEFAULT ' ',
+                checked INTEGER NOT NULL DEFAULT 0,
+                due_date TEXT,
+                priority TEXT,
+                tags TEXT NOT NULL DEFAULT '[]',
+                PRIMARY KEY (note_id, line)
+            );
+
+            CREATE TABLE IF NOT EXISTS writing_log (
+                date TEXT NOT NULL,
+                note_id TEXT NOT NULL,
+                words_added INTEGER NOT NULL DEFAULT 0,
+                words_removed INTEGER NOT NULL DEFAULT 0,
+                PRIMARY KEY (date, note_id)
             );
 
             CREATE TABLE IF NOT EXISTS projects (
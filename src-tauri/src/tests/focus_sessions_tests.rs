@@ -0,0 +1,99 @@
+// Focus session tests
+// Tests for starting, pausing and stopping focus sessions and the weekly summary
+
+use crate::database::Database;
+use chrono::{Days, Local};
+use super::setup_test_db;
+
+/// Move a session's timestamps into the past instead of sleeping
+fn shift_back(db: &Database, id: &str, seconds: i64) {
+    db.conn.execute(
+        "UPDATE focus_sessions SET started_at = started_at - ?1, paused_at = paused_at - ?1 WHERE id = ?2",
+        rusqlite::params![seconds, id],
+    ).unwrap();
+}
+
+#[test]
+fn test_focus_session_lifecycle() {
+    let (db, _temp_dir) = setup_test_db();
+    let project = db.create_project("Grant", None, "research", None, None, None).unwrap();
+    let note = db.create_note("Aims", "one two", "notes", Some(&project.id), None).unwrap();
+
+    let session = db.start_focus_session(Some(&note.id), None, Some(25)).unwrap();
+    assert_eq!(session.status, "running");
+    assert_eq!(session.project_id.as_deref(), Some(project.id.as_str()));
+    assert_eq!(session.planned_minutes, Some(25));
+    assert!(db.start_focus_session(None, Some(&project.id), None).is_err());
+
+    // 20 minutes in, pause for 5, then resume
+    shift_back(&db, &session.id, 1200);
+    let paused = db.pause_focus_session(&session.id).unwrap().unwrap();
+    assert_eq!(paused.status, "paused");
+    shift_back(&db, &session.id, 300);
+    let resumed = db.resume_focus_session(&session.id).unwrap().unwrap();
+    assert_eq!(resumed.status, "running");
+    assert!((1199..=1202).contains(&resumed.duration_seconds));
+
+    db.record_focus_interruption(&session.id).unwrap();
+    db.record_focus_interruption(&session.id).unwrap();
+    db.update_note(&note.id, None, Some("one two three four five"), None).unwrap();
+
+    let done = db.stop_focus_session(&session.id, Some("Good flow after coffee")).unwrap().unwrap();
+    assert_eq!(done.status, "completed");
+    assert!(done.ended_at.is_some());
+    assert!((1199..=1202).contains(&done.duration_seconds));
+    assert_eq!(done.words_written, 3);
+    assert_eq!(done.interruptions, 2);
+    assert_eq!(done.reflection.as_deref(), Some("Good flow after coffee"));
+    assert!(db.get_active_focus_session().unwrap().is_none());
+
+    // Stopping again keeps the numbers and only touches the reflection
+    let again = db.stop_focus_session(&session.id, Some("Edited later")).unwrap().unwrap();
+    assert_eq!(again.duration_seconds, done.duration_seconds);
+    assert_eq!(again.reflection.as_deref(), Some("Edited later"));
+}
+
+#[test]
+fn test_stopping_a_paused_session() {
+    let (db, _temp_dir) = setup_test_db();
+    let session = db.start_focus_session(None, None, None).unwrap();
+    shift_back(&db, &session.id, 600);
+    db.pause_focus_session(&session.id).unwrap();
+    shift_back(&db, &session.id, 900);
+
+    assert_eq!(db.get_active_focus_session().unwrap().unwrap().id, session.id);
+    let done = db.stop_focus_session(&session.id, None).unwrap().unwrap();
+    assert!((599..=602).contains(&done.duration_seconds));
+    assert_eq!(done.words_written, 0);
+
+    assert!(db.start_focus_session(Some("missing"), None, None).is_err());
+    assert!(db.stop_focus_session("missing", None).unwrap().is_none());
+}
+
+#[test]
+fn test_focus_week_summary_by_project() {
+    let (db, _temp_dir) = setup_test_db();
+    let grant = db.create_project("Grant", None, "research", None, None, None).unwrap();
+    let course = db.create_project("Course", None, "teaching", None, None, None).unwrap();
+
+    for (project_id, seconds) in [(Some(&grant.id), 1500), (Some(&grant.id), 900), (Some(&course.id), 600), (None, 300)] {
+        let session = db.start_focus_session(None, project_id.map(|p| p.as_str()), None).unwrap();
+        shift_back(&db, &session.id, seconds);
+        db.stop_focus_session(&session.id, None).unwrap();
+    }
+    // Still running, so not counted yet
+    db.start_focus_session(None, Some(&course.id), None).unwrap();
+
+    let today = Local::now().date_naive();
+    let summary = db.get_focus_week_summary(today).unwrap();
+    assert_eq!(summary.sessions, 4);
+    assert!((3300..=3310).contains(&summary.total_seconds));
+    assert_eq!(summary.projects.len(), 3);
+    assert_eq!(summary.projects[0].project_name.as_deref(), Some("Grant"));
+    assert_eq!(summary.projects[0].sessions, 2);
+    assert_eq!(summary.projects[2].project_id, None);
+
+    let last_week = db.get_focus_week_summary(today - Days::new(7)).unwrap();
+    assert_eq!(last_week.sessions, 0);
+    assert!(last_week.projects.is_empty());
+}
@@ -0,0 +1,105 @@
+// Folder tests
+// Tests for nested folder CRUD and moving notes between folders
+
+use crate::database::{Database, FolderUpdate};
+use super::setup_test_db;
+
+fn folder_paths(db: &Database) -> Vec<String> {
+    db.get_folders().unwrap().into_iter().map(|f| f.path).collect()
+}
+
+#[test]
+fn test_create_folder_with_parents() {
+    let (db, _temp_dir) = setup_test_db();
+
+    let folder = db.create_folder("/research/papers/ ", Some("#46A758"), None).unwrap();
+    assert_eq!(folder.path, "research/papers");
+    assert_eq!(folder.color.as_deref(), Some("#46A758"));
+
+    let paths = folder_paths(&db);
+    assert!(paths.contains(&"research".to_string()));
+    assert!(db.create_folder("research/papers", None, None).is_err());
+    assert!(db.create_folder("research//x", None, None).is_err());
+
+    let updated = db.update_folder("research/", &FolderUpdate { icon: Some("📚".into()), ..Default::default() }).unwrap().unwrap();
+    assert_eq!(updated.icon.as_deref(), Some("📚"));
+}
+
+#[test]
+fn test_rename_folder_moves_descendants() {
+    let (db, _temp_dir) = setup_test_db();
+
+    db.create_folder("research/papers/drafts", None, None).unwrap();
+    db.create_folder("researchers", None, None).unwrap();
+    let top = db.create_note("Top", "", "research", None, None).unwrap();
+    let deep = db.create_note("Deep", "", "research/papers/drafts", None, None).unwrap();
+    let other = db.create_note("Other", "", "researchers", None, None).unwrap();
+
+    db.rename_folder("research", "work/studies").unwrap().unwrap();
+
+    assert_eq!(db.get_note(&top.id).unwrap().unwrap().folder, "work/studies");
+    assert_eq!(db.get_note(&deep.id).unwrap().unwrap().folder, "work/studies/papers/drafts");
+    assert_eq!(db.get_note(&other.id).unwrap().unwrap().folder, "researchers");
+
+    let paths = folder_paths(&db);
+    assert!(paths.contains(&"work".to_string()));
+    assert!(paths.contains(&"work/studies/papers/drafts".to_string()));
+    assert!(!paths.iter().any(|p| p.starts_with("research/") || p == "research"));
+
+    assert!(db.rename_folder("work/studies", "work/studies/inner").is_err());
+    assert!(db.rename_folder("inbox", "elsewhere").is_err());
+    assert!(db.rename_folder("/inbox/", "elsewhere").is_err());
+    db.create_folder("daily", None, None).unwrap();
+    assert!(db.rename_folder("daily", "journal").is_err());
+
+    // Moved notes count as updated
+    db.conn.execute("UPDATE notes SET updated_at = 0", []).unwrap();
+    db.rename_folder("work/studies/", "work/study").unwrap().unwrap();
+    assert!(db.get_note(&deep.id).unwrap().unwrap().updated_at > 0);
+    assert_eq!(db.get_note(&other.id).unwrap().unwrap().updated_at, 0);
+
+    db.move_folder("work/study/papers", None).unwrap().unwrap();
+    assert_eq!(db.get_note(&deep.id).unwrap().unwrap().folder, "papers/drafts");
+
+    // A trailing slash still keeps the folder's own name
+    db.move_folder("papers/drafts/", Some("work")).unwrap().unwrap();
+    assert_eq!(db.get_note(&deep.id).unwrap().unwrap().folder, "work/drafts");
+}
+
+#[test]
+fn test_delete_folder_refuses_or_moves_to_inbox() {
+    let (db, _temp_dir) = setup_test_db();
+
+    db.create_folder("projects/old", None, None).unwrap();
+    let note = db.create_note("Stale", "", "projects/old", None, None).unwrap();
+
+    assert!(db.delete_folder("projects", false).is_err());
+    assert!(db.delete_folder("projects", true).unwrap());
+    assert_eq!(db.get_note(&note.id).unwrap().unwrap().folder, "inbox");
+    assert!(!folder_paths(&db).iter().any(|p| p.starts_with("projects")));
+    assert!(db.delete_folder("inbox", true).is_err());
+}
+
+#[test]
+fn test_move_notes() {
+    let (db, _temp_dir) = setup_test_db();
+
+    let a = db.create_note("A", "", "inbox", None, None).unwrap();
+    let b = db.create_note("B", "", "inbox", None, None).unwrap();
+
+    let moved = db.move_note(&a.id, "reading/").unwrap().unwrap();
+    assert_eq!(moved.folder, "reading");
+    assert!(folder_paths(&db).contains(&"reading".to_string()));
+
+    assert_eq!(db.move_notes(&[a.id.clone(), b.id.clone(), "missing".into()], "archive").unwrap(), 2);
+    assert_eq!(db.get_note(&b.id).unwrap().unwrap().folder, "archive");
+    assert!(db.move_note("missing", "inbox").unwrap().is_none());
+    assert!(db.move_note("missing", "nowhere/deep").unwrap().is_none());
+    assert!(!folder_paths(&db).iter().any(|p| p.starts_with("nowhere")));
+
+    // An edit with a bad folder is rolled back with the move
+    assert!(db.update_and_move_note(&b.id, None, Some("edited"), None, Some("../x")).is_err());
+    assert_eq!(db.get_note(&b.id).unwrap().unwrap().content, "");
+    let edited = db.update_and_move_note(&b.id, Some("B2"), None, None, Some("reading")).unwrap().unwrap();
+    assert_eq!((edited.title.as_str(), edited.folder.as_str()), ("B2", "reading"));
+}
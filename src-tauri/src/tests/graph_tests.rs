@@ -0,0 +1,77 @@
+// Knowledge graph tests
+// Tests for scoped graph queries, neighbourhood depth and node metrics
+
+use crate::graph::{GraphFilters, GraphScope};
+use super::setup_test_db;
+
+#[test]
+fn test_note_neighbourhood_depth() {
+    let (db, _temp_dir) = setup_test_db();
+
+    // Chain: A -> B -> C -> D
+    let d = db.create_note("Graph D", "", "inbox", None, None).unwrap();
+    let c = db.create_note("Graph C", "[[Graph D]]", "inbox", None, None).unwrap();
+    let b = db.create_note("Graph B", "[[Graph C]] and again [[Graph C|see C]]", "inbox", None, None).unwrap();
+    let a = db.create_note("Graph A", "![[Graph B]]", "inbox", None, None).unwrap();
+
+    let scope = GraphScope::Note { id: b.id.clone() };
+    let graph = db.get_graph(&scope, None, &GraphFilters::default()).unwrap();
+    let mut ids: Vec<&str> = graph.nodes.iter().map(|n| n.id.as_str()).collect();
+    ids.sort();
+    let mut expected = vec![a.id.as_str(), b.id.as_str(), c.id.as_str()];
+    expected.sort();
+    assert_eq!(ids, expected);
+
+    let b_to_c = graph.edges.iter().find(|e| e.source == b.id && e.target == c.id).unwrap();
+    assert_eq!(b_to_c.weight, 2);
+    assert_eq!(b_to_c.kind, "link");
+    let a_to_b = graph.edges.iter().find(|e| e.source == a.id).unwrap();
+    assert_eq!(a_to_b.kind, "embed");
+
+    let node_b = graph.nodes.iter().find(|n| n.id == b.id).unwrap();
+    assert_eq!((node_b.in_degree, node_b.out_degree, node_b.degree), (1, 1, 2));
+
+    let deeper = db.get_graph(&scope, Some(2), &GraphFilters::default()).unwrap();
+    assert!(deeper.nodes.iter().any(|n| n.id == d.id));
+
+    let without_embeds = GraphFilters { include_embeds: Some(false), ..Default::default() };
+    let graph = db.get_graph(&scope, None, &without_embeds).unwrap();
+    assert!(graph.edges.iter().all(|e| e.source != a.id));
+    assert!(graph.nodes.iter().all(|n| n.id != a.id));
+}
+
+#[test]
+fn test_project_and_tag_scopes() {
+    let (db, _temp_dir) = setup_test_db();
+
+    let project = db.create_project("Graph Project", None, "generic", None, None, None).unwrap();
+    let hub = db.create_note("Hub", "", "inbox", Some(&project.id), None).unwrap();
+    let spoke_one = db.create_note("Spoke One", "[[Hub]]", "inbox", Some(&project.id), None).unwrap();
+    let spoke_two = db.create_note("Spoke Two", "[[Hub]]", "inbox", Some(&project.id), None).unwrap();
+    let loner = db.create_note("Loner", "", "inbox", Some(&project.id), None).unwrap();
+
+    let scope = GraphScope::Project { id: project.id.clone() };
+    let graph = db.get_graph(&scope, None, &GraphFilters::default()).unwrap();
+    assert_eq!(graph.nodes.len(), 4);
+    assert_eq!(graph.edges.len(), 2);
+
+    let total: f64 = graph.nodes.iter().map(|n| n.centrality).sum();
+    assert!((total - 1.0).abs() < 1e-6);
+    let hub_node = graph.nodes.iter().find(|n| n.id == hub.id).unwrap();
+    assert!(graph.nodes.iter().all(|n| n.centrality <= hub_node.centrality));
+    assert_eq!(hub_node.community, 0);
+    let loner_node = graph.nodes.iter().find(|n| n.id == loner.id).unwrap();
+    assert_ne!(loner_node.community, hub_node.community);
+
+    let no_orphans = GraphFilters { include_orphans: Some(false), ..Default::default() };
+    let graph = db.get_graph(&scope, None, &no_orphans).unwrap();
+    assert!(graph.nodes.iter().all(|n| n.id != loner.id));
+
+    db.add_tag_to_note(&spoke_one.id, "graph-tag").unwrap();
+    db.add_tag_to_note(&spoke_two.id, "graph-tag").unwrap();
+    let tag = db.get_tag_by_name("graph-tag").unwrap().unwrap();
+    let scope = GraphScope::Tag { id: tag.id };
+    let graph = db.get_graph(&scope, Some(1), &GraphFilters::default()).unwrap();
+    assert_eq!(graph.nodes.len(), 3);
+    assert!(graph.nodes.iter().any(|n| n.id == hub.id));
+}
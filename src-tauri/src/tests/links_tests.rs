@@ -0,0 +1,257 @@
+// Wiki link tests
+// Tests for Migration 011 and unresolved link tracking
+
+use crate::database::Database;
+use super::setup_test_db;
+
+fn link_target(db: &Database, source_id: &str, target_title: &str) -> Option<String> {
+    db.conn.query_row(
+        "SELECT target_note_id FROM links WHERE source_note_id = ? AND target_title = ?",
+        [source_id, target_title],
+        |row| row.get(0),
+    ).expect("Link row should exist")
+}
+
+#[test]
+fn test_unresolved_link_is_stored() {
+    let (db, _temp_dir) = setup_test_db();
+
+    let source = db.create_note("Source", "See [[Not Yet Written]]", "inbox", None, None).unwrap();
+
+    assert_eq!(link_target(&db, &source.id, "Not Yet Written"), None);
+
+    let unresolved = db.get_unresolved_links(Some(&source.id)).unwrap();
+    assert_eq!(unresolved.len(), 1);
+    assert_eq!(unresolved[0].target_title, "Not Yet Written");
+    assert_eq!(unresolved[0].source_title, "Source");
+}
+
+#[test]
+fn test_creating_target_resolves_pending_links() {
+    let (db, _temp_dir) = setup_test_db();
+
+    let source = db.create_note("Source", "See [[Later]]", "inbox", None, None).unwrap();
+    let target = db.create_note("Later", "", "inbox", None, None).unwrap();
+
+    assert_eq!(link_target(&db, &source.id, "Later"), Some(target.id.clone()));
+    assert!(db.get_unresolved_links(Some(&source.id)).unwrap().is_empty());
+
+    let backlinks = db.get_backlinks(&target.id).unwrap();
+    assert_eq!(backlinks.len(), 1);
+    assert_eq!(backlinks[0].id, source.id);
+}
+
+#[test]
+fn test_title_change_relinks() {
+    let (db, _temp_dir) = setup_test_db();
+
+    let target = db.create_note("Draft", "", "inbox", None, None).unwrap();
+    let old_ref = db.create_note("Old Ref", "[[Draft]]", "inbox", None, None).unwrap();
+    let new_ref = db.create_note("New Ref", "[[Final]]", "inbox", None, None).unwrap();
+
+    db.update_note(&target.id, Some("Final"), None, None).unwrap();
+
+    // [[Draft]] no longer matches anything, [[Final]] now does
+    assert_eq!(link_target(&db, &old_ref.id, "Draft"), None);
+    assert_eq!(link_target(&db, &new_ref.id, "Final"), Some(target.id));
+}
+
+#[test]
+fn test_unresolved_links_survive_backup_roundtrip() {
+    let (db, _temp_dir) = setup_test_db();
+
+    let source = db.create_note("Source", "[[Missing]] and [[Source]]", "inbox", None, None).unwrap();
+    let backup = db.export_backup().unwrap();
+    db.import_backup(backup).unwrap();
+
+    assert_eq!(link_target(&db, &source.id, "Missing"), None);
+    assert_eq!(link_target(&db, &source.id, "Source"), Some(source.id.clone()));
+}
+
+#[test]
+fn test_rename_note_rewrites_links() {
+    let (db, _temp_dir) = setup_test_db();
+
+    let target = db.create_note("Old Title", "", "inbox", None, None).unwrap();
+    let source = db.create_note(
+        "Source",
+        "[[Old Title]], [[Old Title|alias]], [[ inbox/Old Title#Methods]] and ![[Old Title]] but not [[Old Titles]]\n\n```\nliteral [[Old Title]]\n```\n`[[Old Title]]`",
+        "inbox", None, None,
+    ).unwrap();
+
+    let result = db.rename_note(&target.id, "New Title", false).unwrap().unwrap();
+    assert_eq!(result.modified_notes.len(), 1);
+    assert_eq!(result.modified_notes[0].note_id, source.id);
+    assert_eq!(result.modified_notes[0].occurrences, 4);
+
+    let source = db.get_note(&source.id).unwrap().unwrap();
+    assert_eq!(
+        source.content,
+        "[[New Title]], [[New Title|alias]], [[ inbox/New Title#Methods]] and ![[New Title]] but not [[Old Titles]]\n\n```\nliteral [[Old Title]]\n```\n`[[Old Title]]`"
+    );
+    assert_eq!(db.get_note(&target.id).unwrap().unwrap().title, "New Title");
+    assert_eq!(link_target(&db, &source.id, "New Title"), Some(target.id));
+}
+
+#[test]
+fn test_rename_note_dry_run_changes_nothing() {
+    let (db, _temp_dir) = setup_test_db();
+
+    let target = db.create_note("Old Title", "", "inbox", None, None).unwrap();
+    let source = db.create_note("Source", "[[Old Title]]", "inbox", None, None).unwrap();
+
+    let result = db.rename_note(&target.id, "New Title", true).unwrap().unwrap();
+    assert!(result.dry_run);
+    assert_eq!(result.modified_notes.len(), 1);
+
+    assert_eq!(db.get_note(&source.id).unwrap().unwrap().content, "[[Old Title]]");
+    assert_eq!(db.get_note(&target.id).unwrap().unwrap().title, "Old Title");
+}
+
+#[test]
+fn test_link_kinds_anchors_and_aliases() {
+    let (db, _temp_dir) = setup_test_db();
+
+    let target = db.create_note("Paper", "## Methods\nSurvey.\n\nKey claim ^claim1", "inbox", None, None).unwrap();
+    let source = db.create_note(
+        "Source",
+        "[[Paper|the paper]] [[Paper#Methods]] ![[Paper^claim1]]",
+        "inbox", None, None,
+    ).unwrap();
+
+    let links = db.get_note_links(&source.id).unwrap();
+    assert_eq!(links.len(), 3);
+    assert!(links.iter().all(|l| l.target_title == "Paper" && l.target_note_id.as_deref() == Some(target.id.as_str())));
+    assert_eq!(links[0].alias.as_deref(), Some("the paper"));
+    assert_eq!(links[1].anchor.as_deref(), Some("#Methods"));
+    assert_eq!(links[2].kind, "embed");
+    assert_eq!(links[2].anchor.as_deref(), Some("^claim1"));
+
+    // Several links from the same note still count as one backlink
+    assert_eq!(db.get_backlinks(&target.id).unwrap().len(), 1);
+
+    let preview = db.get_link_preview(&target.id, Some("#Methods")).unwrap().unwrap();
+    assert_eq!(preview.content, "## Methods\nSurvey.\n\nKey claim ^claim1");
+    let block = db.get_link_preview(&target.id, Some("^claim1")).unwrap().unwrap();
+    assert_eq!(block.content, "Key claim");
+    assert!(db.get_link_preview(&target.id, Some("#Nowhere")).unwrap().is_none());
+}
+
+#[test]
+fn test_ambiguous_title_prefers_same_project() {
+    let (db, _temp_dir) = setup_test_db();
+
+    let thesis = db.create_project("Thesis", None, "research", None, None, None).unwrap();
+    let course = db.create_project("Course", None, "teaching", None, None, None).unwrap();
+
+    let course_meeting = db.create_note("Meeting", "", "inbox", Some(&course.id), None).unwrap();
+    let thesis_meeting = db.create_note("Meeting", "", "inbox", Some(&thesis.id), None).unwrap();
+    let source = db.create_note("Plan", "[[Meeting]]", "inbox", Some(&thesis.id), None).unwrap();
+
+    assert_eq!(link_target(&db, &source.id, "Meeting"), Some(thesis_meeting.id.clone()));
+
+    let ambiguous = db.get_ambiguous_links().unwrap();
+    assert_eq!(ambiguous.len(), 1);
+    assert_eq!(ambiguous[0].source_note_id, source.id);
+    assert_eq!(ambiguous[0].candidates.len(), 2);
+    assert!(ambiguous[0].candidates.iter().any(|c| c.id == course_meeting.id));
+}
+
+#[test]
+fn test_path_qualified_links() {
+    let (db, _temp_dir) = setup_test_db();
+
+    let thesis = db.create_project("Thesis", None, "research", None, None, None).unwrap();
+    let by_folder = db.create_note("Meeting", "", "teaching/stats", None, None).unwrap();
+    let by_project = db.create_note("Meeting", "", "inbox", Some(&thesis.id), None).unwrap();
+
+    let source = db.create_note(
+        "Index",
+        "[[stats/Meeting]] [[projects/Thesis/Meeting]] [[nowhere/Meeting]]",
+        "inbox", None, None,
+    ).unwrap();
+
+    assert_eq!(link_target(&db, &source.id, "stats/Meeting"), Some(by_folder.id.clone()));
+    assert_eq!(link_target(&db, &source.id, "projects/Thesis/Meeting"), Some(by_project.id.clone()));
+    assert_eq!(link_target(&db, &source.id, "nowhere/Meeting"), None);
+
+    // Qualified links to the renamed note follow it; others are left alone
+    db.rename_note(&by_folder.id, "Seminar", false).unwrap();
+    let source = db.get_note(&source.id).unwrap().unwrap();
+    assert_eq!(source.content, "[[stats/Seminar]] [[projects/Thesis/Meeting]] [[nowhere/Meeting]]");
+    assert_eq!(link_target(&db, &source.id, "stats/Seminar"), Some(by_folder.id));
+}
+
+#[test]
+fn test_backlinks_with_context_and_unlinked_mentions() {
+    let (db, _temp_dir) = setup_test_db();
+
+    let target = db.create_note("Deep Work", "", "inbox", None, None).unwrap();
+    let source = db.create_note(
+        "Reading Notes",
+        "Über notes\nsee [[Deep Work|the book]] today\n\n- also ![[Deep Work#Rules]]\n- unrelated",
+        "inbox",
+        None,
+        None,
+    ).unwrap();
+    let mentioner = db.create_note("Journal", "Tried deep work this morning.", "inbox", None, None).unwrap();
+
+    let backlinks = db.get_backlinks_with_context(&target.id).unwrap();
+    assert_eq!(backlinks.len(), 1);
+    assert_eq!(backlinks[0].note_id, source.id);
+
+    let mentions = &backlinks[0].mentions;
+    assert_eq!(mentions.len(), 2);
+    assert_eq!(mentions[0].context, "Über notes\nsee [[Deep Work|the book]] today");
+    assert_eq!(mentions[0].line, 2);
+    // Offsets count characters, so "Ü" counts once
+    assert_eq!((mentions[0].start, mentions[0].end), (15, 37));
+    assert_eq!(mentions[0].alias.as_deref(), Some("the book"));
+    assert_eq!(mentions[1].context, "- also ![[Deep Work#Rules]]");
+    assert_eq!(mentions[1].kind, "embed");
+    assert_eq!(mentions[1].anchor.as_deref(), Some("#Rules"));
+
+    let unlinked = db.get_unlinked_mentions(&target.id).unwrap();
+    assert_eq!(unlinked.len(), 1);
+    assert_eq!(unlinked[0].note_id, mentioner.id);
+    assert_eq!(unlinked[0].mentions[0].kind, "mention");
+    assert_eq!((unlinked[0].mentions[0].start, unlinked[0].mentions[0].end), (6, 15));
+}
+
+#[test]
+fn test_unlinked_mentions_fold_non_ascii_case() {
+    let (db, _temp_dir) = setup_test_db();
+
+    let target = db.create_note("Éclair recipe", "", "inbox", None, None).unwrap();
+    let mentioner = db.create_note("Baking", "Try the ÉCLAIR RECIPE next", "inbox", None, None).unwrap();
+    db.create_note("Other", "éclair recipes are different", "inbox", None, None).unwrap();
+
+    let unlinked = db.get_unlinked_mentions(&target.id).unwrap();
+    assert_eq!(unlinked.len(), 1);
+    assert_eq!(unlinked[0].note_id, mentioner.id);
+
+    assert!(db.get_unlinked_mentions("missing").unwrap().is_empty());
+    db.delete_note(&target.id).unwrap();
+    assert!(db.get_unlinked_mentions(&target.id).unwrap().is_empty());
+}
+
+#[test]
+fn test_rename_note_leaves_links_to_namesakes() {
+    let (db, _temp_dir) = setup_test_db();
+
+    let thesis = db.create_project("Thesis", None, "research", None, None, None).unwrap();
+    let copy = db.create_project("Thesis copy", None, "research", None, None, None).unwrap();
+    db.create_note("Outline", "", "inbox", Some(&thesis.id), None).unwrap();
+    let copied = db.create_note("Outline", "", "inbox", Some(&copy.id), None).unwrap();
+    let original_source = db.create_note("Plan", "[[Outline]]", "inbox", Some(&thesis.id), None).unwrap();
+    let copy_source = db.create_note("Plan", "[[Outline]]", "inbox", Some(&copy.id), None).unwrap();
+
+    let result = db.rename_note(&copied.id, "Structure", false).unwrap().unwrap();
+    assert_eq!(result.modified_notes.len(), 1);
+    assert_eq!(db.get_note(&copy_source.id).unwrap().unwrap().content, "[[Structure]]");
+    assert_eq!(db.get_note(&original_source.id).unwrap().unwrap().content, "[[Outline]]");
+
+    // A title already used in the same project is refused
+    assert!(db.rename_note(&copied.id, "plan", false).is_err());
+    assert_eq!(db.get_note(&copied.id).unwrap().unwrap().title, "Structure");
+}
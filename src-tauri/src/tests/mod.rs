@@ -1,2 +1,31 @@
 // Test module for Scribe
 mod chat_history_tests;
+mod links_tests;
+mod graph_tests;
+mod vault_health_tests;
+mod tags_tests;
+mod folders_tests;
+mod bulk_tests;
+mod note_list_tests;
+mod note_states_tests;
+mod templates_tests;
+mod periodic_tests;
+mod rollup_tests;
+mod tasks_tests;
+mod writing_progress_tests;
+mod focus_sessions_tests;
+mod readability_tests;
+mod project_stats_tests;
+mod project_types_tests;
+mod project_bundles_tests;
+
+use crate::database::Database;
+use tempfile::TempDir;
+
+/// A fresh database in a temp directory; keep the `TempDir` alive for the whole test
+fn setup_test_db() -> (Database, TempDir) {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let db = Database::new_with_path(temp_dir.path().join("test.db"))
+        .expect("Failed to initialize test database");
+    (db, temp_dir)
+}
@@ -0,0 +1,105 @@
+// Note listing tests
+// Tests for paginated, sortable note summaries
+
+use crate::database::{Database, NoteSort, NoteSortField, NoteSummaryFilter};
+use super::setup_test_db;
+
+/// Walk every page and return the titles in order
+fn all_titles(db: &Database, filter: &NoteSummaryFilter, sort: &NoteSort, limit: usize) -> Vec<String> {
+    let mut titles = Vec::new();
+    let mut cursor: Option<String> = None;
+    loop {
+        let page = db.list_note_summaries(filter, sort, cursor.as_deref(), Some(limit)).unwrap();
+        assert!(page.notes.len() <= limit);
+        titles.extend(page.notes.into_iter().map(|n| n.title));
+        match page.next_cursor {
+            Some(next) => cursor = Some(next),
+            None => break,
+        }
+    }
+    titles
+}
+
+#[test]
+fn test_summaries_include_metadata() {
+    let (db, _temp_dir) = setup_test_db();
+    let project = db.create_project("Thesis", None, "research", None, None, None).unwrap();
+    let note = db.create_note("Draft", "# Intro\n\nThree little words #wip #alpha", "inbox", Some(&project.id), None).unwrap();
+
+    let page = db.list_note_summaries(&NoteSummaryFilter::default(), &NoteSort::default(), None, None).unwrap();
+    let summary = page.notes.iter().find(|n| n.id == note.id).unwrap();
+    assert_eq!(summary.word_count, 6);
+    assert_eq!(summary.project_name.as_deref(), Some("Thesis"));
+    assert_eq!(summary.tags, vec!["alpha".to_string(), "wip".to_string()]);
+    assert_eq!(summary.preview, "Intro Three little words #wip #alpha");
+
+    db.update_note(&note.id, None, Some("Just two"), None).unwrap();
+    let page = db.list_note_summaries(&NoteSummaryFilter::default(), &NoteSort::default(), None, None).unwrap();
+    assert_eq!(page.notes.iter().find(|n| n.id == note.id).unwrap().word_count, 2);
+
+    // Frontmatter longer than the preview source is still left out
+    let long = format!("---\nabstract: {}\n---\nBody text", "x ".repeat(1500));
+    db.update_note(&note.id, None, Some(&long), None).unwrap();
+    let page = db.list_note_summaries(&NoteSummaryFilter::default(), &NoteSort::default(), None, None).unwrap();
+    assert_eq!(page.notes.iter().find(|n| n.id == note.id).unwrap().preview, "Body text");
+}
+
+#[test]
+fn test_title_pages_cover_every_note_once() {
+    let (db, _temp_dir) = setup_test_db();
+    for title in ["delta", "Alpha", "charlie", "Bravo", "echo", "alpha"] {
+        db.create_note(title, "", "work", None, None).unwrap();
+    }
+    let filter = NoteSummaryFilter { folder: Some("work".to_string()), ..Default::default() };
+    let sort = NoteSort { field: NoteSortField::Title, descending: None };
+
+    let titles = all_titles(&db, &filter, &sort, 2);
+    assert_eq!(titles.len(), 6);
+    let lowered: Vec<String> = titles.iter().map(|t| t.to_lowercase()).collect();
+    assert_eq!(lowered, vec!["alpha", "alpha", "bravo", "charlie", "delta", "echo"]);
+
+    let sort = NoteSort { field: NoteSortField::Title, descending: Some(true) };
+    let lowered: Vec<String> = all_titles(&db, &filter, &sort, 4).iter().map(|t| t.to_lowercase()).collect();
+    assert_eq!(lowered, vec!["echo", "delta", "charlie", "bravo", "alpha", "alpha"]);
+}
+
+#[test]
+fn test_sort_by_word_count_and_property() {
+    let (db, _temp_dir) = setup_test_db();
+    let prop = |n: i64| format!(r#"{{"rank":{{"key":"rank","type":"number","value":{}}}}}"#, n);
+    db.create_note("one", "a", "work", None, Some(&prop(3))).unwrap();
+    db.create_note("three", "a b c", "work", None, Some(&prop(1))).unwrap();
+    db.create_note("two", "a b", "work", None, None).unwrap();
+    db.create_note("none", "", "work", None, Some(&prop(2))).unwrap();
+    let filter = NoteSummaryFilter { folder: Some("work".to_string()), ..Default::default() };
+
+    let sort = NoteSort { field: NoteSortField::WordCount, descending: None };
+    assert_eq!(all_titles(&db, &filter, &sort, 1), vec!["three", "two", "one", "none"]);
+
+    let sort = NoteSort { field: NoteSortField::Property { key: "rank".to_string() }, descending: None };
+    assert_eq!(all_titles(&db, &filter, &sort, 1), vec!["three", "none", "one", "two"]);
+    let sort = NoteSort { field: NoteSortField::Property { key: "rank".to_string() }, descending: Some(true) };
+    assert_eq!(all_titles(&db, &filter, &sort, 3), vec!["one", "none", "three", "two"]);
+}
+
+#[test]
+fn test_filters() {
+    let (db, _temp_dir) = setup_test_db();
+    db.create_note("Top", "#paper #read", "research", None, None).unwrap();
+    db.create_note("Nested", "#paper", "research/2024", None, None).unwrap();
+    db.create_note("Other", "#paper #read", "inbox", None, None).unwrap();
+    let paper = db.get_tag_by_name("paper").unwrap().unwrap();
+    let read = db.get_tag_by_name("read").unwrap().unwrap();
+    let sort = NoteSort { field: NoteSortField::Title, descending: None };
+
+    let filter = NoteSummaryFilter { folder: Some("research".to_string()), ..Default::default() };
+    assert_eq!(all_titles(&db, &filter, &sort, 10), vec!["Top"]);
+
+    let filter = NoteSummaryFilter { folder: Some("research".to_string()), include_subfolders: true, ..Default::default() };
+    assert_eq!(all_titles(&db, &filter, &sort, 10), vec!["Nested", "Top"]);
+
+    let filter = NoteSummaryFilter { tag_ids: vec![paper.id, read.id], ..Default::default() };
+    assert_eq!(all_titles(&db, &filter, &sort, 10), vec!["Other", "Top"]);
+
+    assert!(db.list_note_summaries(&filter, &sort, Some("garbage"), None).is_err());
+}
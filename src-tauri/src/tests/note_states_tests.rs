@@ -0,0 +1,98 @@
+// Note state tests
+// Tests for pinned, favourite and archived notes in listings, search and backups
+
+use crate::database::{NoteSort, NoteSortField, NoteStateFilter, NoteSummaryFilter};
+use super::setup_test_db;
+
+fn titles<'a>(notes: impl IntoIterator<Item = &'a String>) -> Vec<&'a str> {
+    notes.into_iter().map(|t| t.as_str()).collect()
+}
+
+#[test]
+fn test_archived_notes_hidden_from_listings_but_searchable() {
+    let (db, _temp_dir) = setup_test_db();
+    let kept = db.create_note("Kept", "quokka facts", "work", None, None).unwrap();
+    let old = db.create_note("Old", "quokka history", "work", None, None).unwrap();
+
+    let archived = db.set_note_archived(&old.id, true).unwrap().unwrap();
+    assert!(archived.archived_at.is_some());
+
+    let listed: Vec<String> = db.list_notes(Some("work"), false).unwrap().into_iter().map(|n| n.title).collect();
+    assert_eq!(titles(&listed), vec!["Kept"]);
+    assert_eq!(db.list_notes(Some("work"), true).unwrap().len(), 2);
+
+    assert_eq!(db.search_notes("quokka", &NoteStateFilter::default()).unwrap().len(), 2);
+    let active = NoteStateFilter { archived: Some(false), ..Default::default() };
+    let found = db.search_notes("quokka", &active).unwrap();
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].id, kept.id);
+
+    let sort = NoteSort { field: NoteSortField::Title, descending: None };
+    let filter = NoteSummaryFilter { folder: Some("work".to_string()), ..Default::default() };
+    let page = db.list_note_summaries(&filter, &sort, None, None).unwrap();
+    assert_eq!(page.notes.len(), 1);
+
+    let only_archived = NoteSummaryFilter {
+        state: NoteStateFilter { archived: Some(true), ..Default::default() },
+        ..filter
+    };
+    let page = db.list_note_summaries(&only_archived, &sort, None, None).unwrap();
+    assert_eq!(page.notes.len(), 1);
+    assert_eq!(page.notes[0].id, old.id);
+
+    db.set_note_archived(&old.id, false).unwrap();
+    assert_eq!(db.list_notes(Some("work"), false).unwrap().len(), 2);
+}
+
+#[test]
+fn test_pinned_notes_lead_every_page() {
+    let (db, _temp_dir) = setup_test_db();
+    for title in ["a", "b", "c", "d", "e"] {
+        db.create_note(title, "", "work", None, None).unwrap();
+    }
+    let d = db.list_notes(Some("work"), false).unwrap().into_iter().find(|n| n.title == "d").unwrap();
+    let b = db.list_notes(Some("work"), false).unwrap().into_iter().find(|n| n.title == "b").unwrap();
+    db.set_note_pinned(&d.id, true).unwrap();
+    db.set_note_pinned(&b.id, true).unwrap();
+    db.set_note_favorite(&d.id, true).unwrap();
+
+    assert!(db.list_notes(Some("work"), false).unwrap()[..2].iter().all(|n| n.pinned));
+
+    let filter = NoteSummaryFilter { folder: Some("work".to_string()), ..Default::default() };
+    let sort = NoteSort { field: NoteSortField::Title, descending: None };
+    let mut seen = Vec::new();
+    let mut cursor: Option<String> = None;
+    loop {
+        let page = db.list_note_summaries(&filter, &sort, cursor.as_deref(), Some(2)).unwrap();
+        seen.extend(page.notes.into_iter().map(|n| n.title));
+        cursor = page.next_cursor;
+        if cursor.is_none() {
+            break;
+        }
+    }
+    assert_eq!(titles(&seen), vec!["b", "d", "a", "c", "e"]);
+
+    let favourites = NoteSummaryFilter {
+        state: NoteStateFilter { favorite: Some(true), ..Default::default() },
+        ..filter
+    };
+    let page = db.list_note_summaries(&favourites, &sort, None, None).unwrap();
+    assert_eq!(page.notes.len(), 1);
+    assert!(page.notes[0].favorite && page.notes[0].pinned);
+}
+
+#[test]
+fn test_note_states_survive_backup() {
+    let (db, _temp_dir) = setup_test_db();
+    let note = db.create_note("Keeper", "body", "inbox", None, None).unwrap();
+    db.set_note_pinned(&note.id, true).unwrap();
+    db.set_note_favorite(&note.id, true).unwrap();
+    db.set_note_archived(&note.id, true).unwrap();
+
+    let backup = db.export_backup().unwrap();
+    let (restored, _restored_dir) = setup_test_db();
+    restored.import_backup(backup).unwrap();
+
+    let note = restored.get_note(&note.id).unwrap().unwrap();
+    assert!(note.pinned && note.favorite && note.archived_at.is_some());
+}
@@ -0,0 +1,93 @@
+// Periodic note tests
+// Tests for daily/weekly/monthly notes, navigation and the calendar query
+
+use crate::database::PeriodicConfig;
+use crate::periodic::Period;
+use chrono::NaiveDate;
+use super::setup_test_db;
+
+fn day(y: i32, m: u32, d: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(y, m, d).unwrap()
+}
+
+#[test]
+fn test_weekly_note_is_shared_by_the_whole_week() {
+    let (db, _temp_dir) = setup_test_db();
+
+    let monday = db.get_or_create_periodic_note(Period::Weekly, day(2025, 1, 6)).unwrap();
+    let sunday = db.get_or_create_periodic_note(Period::Weekly, day(2025, 1, 12)).unwrap();
+    assert_eq!(monday.id, sunday.id);
+    assert_eq!(monday.title, "2025-W02");
+    assert_eq!(monday.folder, "weekly");
+    assert_eq!(monday.content, "# 2025-W02\n\n");
+
+    let quarter = db.get_or_create_periodic_note(Period::Quarterly, day(2025, 8, 20)).unwrap();
+    assert_eq!(quarter.title, "2025-Q3");
+}
+
+#[test]
+fn test_configured_folder_format_and_template() {
+    let (db, _temp_dir) = setup_test_db();
+    let template = db.create_template("Month", "# {{date:MMMM YYYY}}\n\n## Goals\n", None, None, &[]).unwrap();
+
+    let config = db.set_periodic_config(&PeriodicConfig {
+        period: Period::Monthly,
+        folder: "journal/months/".to_string(),
+        title_format: "MMMM YYYY".to_string(),
+        template_id: Some(template.id.clone()),
+    }).unwrap();
+    assert_eq!(config.folder, "journal/months");
+
+    let note = db.get_or_create_periodic_note(Period::Monthly, day(2025, 4, 17)).unwrap();
+    assert_eq!(note.title, "April 2025");
+    assert_eq!(note.folder, "journal/months");
+    assert_eq!(note.content, "# April 2025\n\n## Goals\n");
+    assert!(db.get_folders().unwrap().iter().any(|f| f.path == "journal/months"));
+
+    // The daily template is shared with set_daily_note_template
+    let mut daily = db.get_periodic_config(Period::Daily).unwrap();
+    daily.template_id = Some(template.id.clone());
+    db.set_periodic_config(&daily).unwrap();
+    assert_eq!(db.get_daily_note_template().unwrap().unwrap().id, template.id);
+
+    let mut bad = config.clone();
+    bad.template_id = Some("missing".to_string());
+    assert!(db.set_periodic_config(&bad).is_err());
+}
+
+#[test]
+fn test_daily_notes_adopt_existing_titles() {
+    let (db, _temp_dir) = setup_test_db();
+    let legacy = db.create_note("2025-02-10", "old daily", "daily", None, None).unwrap();
+
+    let daily = db.get_or_create_daily_note("2025-02-10").unwrap();
+    assert_eq!(daily.id, legacy.id);
+    assert!(db.get_or_create_daily_note("10/02/2025").is_err());
+
+    // Renaming the note doesn't lose the link to its day
+    db.update_note(&daily.id, Some("Monday notes"), None, None).unwrap();
+    assert_eq!(db.get_or_create_daily_note("2025-02-10").unwrap().id, legacy.id);
+}
+
+#[test]
+fn test_navigation_and_calendar() {
+    let (db, _temp_dir) = setup_test_db();
+    for d in [3, 5, 28] {
+        db.get_or_create_periodic_note(Period::Daily, day(2025, 3, d)).unwrap();
+    }
+    db.get_or_create_periodic_note(Period::Daily, day(2025, 4, 1)).unwrap();
+
+    let previous = db.get_periodic_note(Period::Daily, day(2025, 3, 6), -1).unwrap();
+    assert_eq!(previous.start_date, "2025-03-05");
+    assert!(previous.note.is_some());
+    let next = db.get_periodic_note(Period::Daily, day(2025, 3, 6), 1).unwrap();
+    assert!(next.note.is_none());
+    assert_eq!(next.title, "2025-03-07");
+
+    let month = db.get_periodic_note(Period::Monthly, day(2025, 1, 31), 1).unwrap();
+    assert_eq!((month.start_date.as_str(), month.end_date.as_str()), ("2025-02-01", "2025-02-28"));
+
+    let calendar = db.get_periodic_calendar(Period::Daily, day(2025, 3, 1), day(2025, 3, 31)).unwrap();
+    let dates: Vec<&str> = calendar.iter().map(|e| e.date.as_str()).collect();
+    assert_eq!(dates, vec!["2025-03-03", "2025-03-05", "2025-03-28"]);
+}
@@ -0,0 +1,169 @@
+// Project archive, duplicate and bundle tests
+// Tests for moving whole projects between vaults with new ids
+
+use crate::database::Database;
+use std::collections::HashMap;
+use super::setup_test_db;
+
+/// A project with two linked notes, a hand-added tag, a chat and an image
+fn setup_project(db: &Database) -> String {
+    let project = db.create_project("Thesis", Some("PhD work"), "research", Some("#ff0000"), None, None).unwrap();
+    db.update_project_settings(&project.id, r#"{"citationStyle":"apa"}"#).unwrap();
+    db.create_folder("Thesis/manuscript", Some("#00ff00"), None).unwrap();
+
+    let outline = db.create_note(
+        "Outline",
+        "See [[Methods]] #thesis/draft\n\n![Plot](/data/figures/plot.png)",
+        "Thesis/manuscript",
+        Some(&project.id),
+        None,
+    ).unwrap();
+    let methods = db.create_note("Methods", "Sampling plan", "Thesis", Some(&project.id), None).unwrap();
+    db.add_tag_to_note(&methods.id, "stats").unwrap();
+
+    let session = db.get_or_create_chat_session(&outline.id).unwrap();
+    db.save_chat_message(&session, "user", "Summarise the outline", 100).unwrap();
+    db.save_chat_message(&session, "assistant", "It has one section", 101).unwrap();
+
+    project.id
+}
+
+#[test]
+fn test_archive_project() {
+    let (db, _temp_dir) = setup_test_db();
+    let project_id = setup_project(&db);
+
+    let listed = |include_archived: bool| -> bool {
+        db.list_projects(None, include_archived).unwrap().iter().any(|p| p.id == project_id)
+    };
+
+    let archived = db.set_project_archived(&project_id, true).unwrap().unwrap();
+    assert!(archived.archived_at.is_some());
+    assert!(!listed(false));
+    assert!(listed(true));
+    assert_eq!(db.get_project_note_count(&project_id).unwrap(), 2);
+
+    let restored = db.set_project_archived(&project_id, false).unwrap().unwrap();
+    assert!(restored.archived_at.is_none());
+    assert!(listed(false));
+    assert!(db.set_project_archived("missing", true).unwrap().is_none());
+}
+
+#[test]
+fn test_export_project_bundle() {
+    let (db, _temp_dir) = setup_test_db();
+    let project_id = setup_project(&db);
+    db.create_note("Unrelated", "[[Methods]] ![x](/other.png)", "inbox", None, None).unwrap();
+
+    let bundle = db.export_project_bundle(&project_id).unwrap().unwrap();
+    assert_eq!(bundle.project.name, "Thesis");
+    assert_eq!(bundle.settings.as_deref(), Some(r#"{"citationStyle":"apa"}"#));
+    assert!(bundle.project_type.is_none());
+    assert_eq!(bundle.notes.len(), 2);
+    assert_eq!(bundle.links.len(), 1);
+    assert_eq!(bundle.chat_sessions.len(), 1);
+    assert_eq!(bundle.chat_sessions[0].messages.len(), 2);
+
+    let tags: Vec<&str> = bundle.tags.iter().map(|t| t.name.as_str()).collect();
+    assert_eq!(tags, vec!["stats", "thesis/draft"]);
+
+    let folders: Vec<&str> = bundle.folders.iter().map(|f| f.path.as_str()).collect();
+    assert!(folders.contains(&"Thesis"));
+    assert!(folders.contains(&"Thesis/manuscript"));
+    assert!(!folders.contains(&"inbox"));
+
+    assert_eq!(bundle.attachments.len(), 1);
+    assert_eq!(bundle.attachments[0].path, "/data/figures/plot.png");
+    assert_eq!(bundle.attachments[0].file, "1-plot.png");
+
+    assert!(db.export_project_bundle("missing").unwrap().is_none());
+}
+
+#[test]
+fn test_import_project_bundle_remaps_ids() {
+    let (db, _temp_dir) = setup_test_db();
+    let project_id = setup_project(&db);
+    let bundle = db.export_project_bundle(&project_id).unwrap().unwrap();
+
+    let (other, _other_dir) = setup_test_db();
+    let attachments = HashMap::from([("/data/figures/plot.png".to_string(), "/vault/attachments/1-plot.png".to_string())]);
+    let imported = other.import_project_bundle(&bundle, None, &attachments).unwrap();
+
+    assert_ne!(imported.id, project_id);
+    assert_eq!(imported.name, "Thesis");
+    assert_eq!(imported.description.as_deref(), Some("PhD work"));
+    assert_eq!(other.get_project_settings(&imported.id).unwrap(), bundle.settings);
+
+    let notes = other.get_notes_by_project(&imported.id).unwrap();
+    assert_eq!(notes.len(), 2);
+    assert!(notes.iter().all(|n| bundle.notes.iter().all(|b| b.id != n.id)));
+
+    let outline = notes.iter().find(|n| n.title == "Outline").unwrap();
+    let methods = notes.iter().find(|n| n.title == "Methods").unwrap();
+    assert!(outline.content.contains("![Plot](/vault/attachments/1-plot.png)"));
+
+    // The link points at the imported Methods note, and the hand-added tag came along
+    let links = other.get_outgoing_links(&outline.id).unwrap();
+    assert_eq!(links.len(), 1);
+    assert_eq!(links[0].id, methods.id);
+    let tags: Vec<String> = other.get_note_tags(&methods.id).unwrap().into_iter().map(|t| t.name).collect();
+    assert_eq!(tags, vec!["stats"]);
+
+    let session = other.get_or_create_chat_session(&outline.id).unwrap();
+    let messages = other.load_chat_session(&session).unwrap();
+    assert_eq!(messages.len(), 2);
+    assert_eq!(messages[0]["content"], "Summarise the outline");
+
+    // A second import doesn't collide with the first
+    let again = other.import_project_bundle(&bundle, None, &HashMap::new()).unwrap();
+    assert_eq!(again.name, "Thesis (2)");
+    let folders: Vec<String> = other.get_notes_by_project(&again.id).unwrap().into_iter().map(|n| n.folder).collect();
+    assert!(folders.contains(&"Thesis (2)/manuscript".to_string()));
+}
+
+#[test]
+fn test_import_project_bundle_custom_type() {
+    let (db, _temp_dir) = setup_test_db();
+    let project_id = setup_project(&db);
+    let mut bundle = db.export_project_bundle(&project_id).unwrap().unwrap();
+    bundle.project.project_type = "lab-notebook".to_string();
+
+    // Unknown type without a definition falls back to generic
+    let (other, _other_dir) = setup_test_db();
+    let imported = other.import_project_bundle(&bundle, Some("Lab"), &HashMap::new()).unwrap();
+    assert_eq!(imported.project_type, "generic");
+    assert_eq!(imported.name, "Lab");
+}
+
+#[test]
+fn test_duplicate_project() {
+    let (db, _temp_dir) = setup_test_db();
+    let project_id = setup_project(&db);
+
+    let copy = db.duplicate_project(&project_id, None, true).unwrap().unwrap();
+    assert_eq!(copy.name, "Thesis (copy)");
+    assert_eq!(copy.project_type, "research");
+    assert_eq!(copy.color.as_deref(), Some("#ff0000"));
+
+    let notes = db.get_notes_by_project(&copy.id).unwrap();
+    assert_eq!(notes.len(), 2);
+    let outline = notes.iter().find(|n| n.title == "Outline").unwrap();
+    assert_eq!(outline.folder, "Thesis (copy)/manuscript");
+    let methods = notes.iter().find(|n| n.title == "Methods").unwrap();
+    assert_eq!(db.get_outgoing_links(&outline.id).unwrap()[0].id, methods.id);
+
+    // Chat history stays with the original
+    let session = db.get_or_create_chat_session(&outline.id).unwrap();
+    assert!(db.load_chat_session(&session).unwrap().is_empty());
+
+    // The original is untouched
+    assert_eq!(db.get_notes_by_project(&project_id).unwrap().len(), 2);
+
+    let template = db.duplicate_project(&project_id, Some("Next paper"), false).unwrap().unwrap();
+    assert!(db.get_notes_by_project(&template.id).unwrap().is_empty());
+    assert_eq!(db.get_project_settings(&template.id).unwrap().as_deref(), Some(r#"{"citationStyle":"apa"}"#));
+    let folder = db.get_folders().unwrap().into_iter().find(|f| f.path == "Next paper/manuscript").unwrap();
+    assert_eq!(folder.color.as_deref(), Some("#00ff00"));
+
+    assert!(db.duplicate_project("missing", None, true).unwrap().is_none());
+}
@@ -0,0 +1,53 @@
+// Project statistics tests
+// Tests for the project dashboard overview
+
+use chrono::{Days, Local};
+use super::setup_test_db;
+
+#[test]
+fn test_project_stats_overview() {
+    let (db, _temp_dir) = setup_test_db();
+    let today = Local::now().date_naive();
+    let project = db.create_project("Thesis", None, "research", None, None, None).unwrap();
+    let other = db.create_project("Other", None, "generic", None, None, None).unwrap();
+
+    db.create_note("Glossary", "terms", "notes", Some(&other.id), None).unwrap();
+    let methods = db.create_note("Methods", "", "notes", Some(&project.id), None).unwrap();
+    db.update_note(&methods.id, None, Some("Sample of #stats and #r\n- [ ] Check power\n- [x] Pick model"), None).unwrap();
+    db.create_note("Results", "See [[Methods]] and [[Glossary]] #stats", "notes", Some(&project.id), None).unwrap();
+    let trashed = db.create_note("Scrap", "#stats words words [[Methods]]", "notes", Some(&project.id), None).unwrap();
+    db.delete_note(&trashed.id).unwrap();
+
+    // An older session, outside the 7-day window
+    db.conn.execute(
+        "INSERT INTO writing_log (date, note_id, words_added) VALUES (?, ?, 100)",
+        rusqlite::params![(today - Days::new(10)).to_string(), methods.id],
+    ).unwrap();
+
+    let stats = db.get_project_stats(&project.id, today).unwrap().unwrap();
+    assert_eq!(stats.note_count, 2);
+    assert_eq!(stats.total_words, 15);
+    assert_eq!(stats.words_added_7_days, 19);
+    assert_eq!(stats.words_added_30_days, 119);
+
+    let tags: Vec<(&str, i64)> = stats.tags.iter().map(|t| (t.name.as_str(), t.note_count)).collect();
+    assert_eq!(tags, vec![("stats", 2), ("r", 1)]);
+
+    assert_eq!(stats.link_count, 2);
+    assert_eq!(stats.internal_link_count, 1);
+    assert_eq!(stats.link_density, 1.0);
+    assert_eq!(stats.open_tasks, 1);
+    assert_eq!(stats.completed_tasks, 1);
+    assert!(stats.last_activity.is_some());
+
+    let timeline: Vec<(String, i64, i64)> = stats.timeline.iter().map(|d| (d.date.clone(), d.notes_created, d.words_added)).collect();
+    assert_eq!(timeline, vec![
+        ((today - Days::new(10)).to_string(), 0, 100),
+        (today.to_string(), 2, 19),
+    ]);
+
+    assert_eq!(stats.recent_notes.len(), 2);
+    assert!(stats.recent_notes.iter().all(|n| n.id != trashed.id));
+
+    assert!(db.get_project_stats("missing", today).unwrap().is_none());
+}
@@ -0,0 +1,176 @@
+// Project type tests
+// Tests for type definitions, folder scaffolds and starter notes
+
+use crate::database::Database;
+use crate::project_types::{BibliographyMode, ProjectType, StarterNote};
+use chrono::NaiveDate;
+use super::setup_test_db;
+use tempfile::TempDir;
+
+fn date() -> NaiveDate {
+    NaiveDate::from_ymd_opt(2026, 3, 2).unwrap()
+}
+
+fn custom_type(id: &str) -> ProjectType {
+    ProjectType {
+        id: id.to_string(),
+        name: "Grant".to_string(),
+        description: None,
+        icon: None,
+        color: None,
+        folders: vec!["{{project.name}}/budget".to_string()],
+        starter_notes: vec![StarterNote {
+            title: "Aims".to_string(),
+            folder: "{{project.name}}".to_string(),
+            content: "# {{project.name}} aims ({{date}})".to_string(),
+        }],
+        properties: Vec::new(),
+        export_preset: None,
+        bibliography: BibliographyMode::None,
+        builtin: false,
+        sort_order: 10,
+    }
+}
+
+#[test]
+fn test_builtin_types_seeded() {
+    let (db, _temp_dir) = setup_test_db();
+
+    let ids: Vec<String> = db.list_project_types().unwrap().into_iter().map(|t| t.id).collect();
+    assert_eq!(ids, vec!["research", "teaching", "r-package", "r-dev", "generic"]);
+
+    let research = db.get_project_type("research").unwrap().unwrap();
+    assert!(research.builtin);
+    assert_eq!(research.bibliography, BibliographyMode::Project);
+    assert_eq!(research.export_preset.unwrap().csl, "apa");
+}
+
+#[test]
+fn test_research_project_scaffold() {
+    let (db, _temp_dir) = setup_test_db();
+    let project = db.create_project("Thesis", None, "research", None, None, None).unwrap();
+
+    let scaffold = db.scaffold_project(&project.id, date()).unwrap().unwrap();
+    assert!(scaffold.folders.contains(&"Thesis/literature-notes".to_string()));
+
+    let folders: Vec<String> = db.get_folders().unwrap().into_iter().map(|f| f.path).collect();
+    assert!(folders.contains(&"Thesis/literature-notes".to_string()));
+    assert!(folders.contains(&"Thesis/manuscript".to_string()));
+
+    let outline = scaffold.notes.iter().find(|n| n.title == "Manuscript Outline").unwrap();
+    assert_eq!(outline.folder, "Thesis/manuscript");
+    assert_eq!(outline.project_id.as_deref(), Some(project.id.as_str()));
+    assert!(outline.content.starts_with("# Thesis: Manuscript Outline"));
+    assert!(outline.properties.as_deref().unwrap().contains("draft"));
+
+    let settings = db.get_project_settings(&project.id).unwrap().unwrap();
+    assert!(settings.contains("\"citationStyle\":\"apa\""));
+    assert_eq!(db.get_project_stats(&project.id, date()).unwrap().unwrap().open_tasks, 0);
+
+    // Running it again doesn't duplicate the starter notes
+    let again = db.scaffold_project(&project.id, date()).unwrap().unwrap();
+    assert!(again.notes.is_empty());
+    assert_eq!(db.get_notes_by_project(&project.id).unwrap().len(), 2);
+}
+
+#[test]
+fn test_scaffolded_project_folder_names() {
+    let (db, _temp_dir) = setup_test_db();
+
+    let project = db.create_scaffolded_project(r"Q1\Q2", None, "research", None, None, None).unwrap();
+    let folders: Vec<String> = db.get_folders().unwrap().into_iter().map(|f| f.path).collect();
+    assert!(folders.contains(&"Q1-Q2/literature-notes".to_string()));
+    assert_eq!(db.get_notes_by_project(&project.id).unwrap().len(), 2);
+
+    let dots = db.create_scaffolded_project("..", None, "research", None, None, None).unwrap();
+    let notes = db.get_notes_by_project(&dots.id).unwrap();
+    assert!(notes.iter().all(|n| n.folder.starts_with("project/")));
+
+    // A scaffold that fails takes the new project with it
+    let mut broken = custom_type("broken");
+    broken.folders = vec!["{{project.name}}/../escape".to_string()];
+    db.create_project_type(&broken).unwrap();
+    let before = db.list_projects(None, true).unwrap().len();
+    assert!(db.create_scaffolded_project("Grant", None, "broken", None, None, None).is_err());
+    assert_eq!(db.list_projects(None, true).unwrap().len(), before);
+}
+
+#[test]
+fn test_custom_project_type() {
+    let (db, _temp_dir) = setup_test_db();
+
+    assert!(db.create_project("X", None, "grant", None, None, None).is_err());
+    assert!(db.create_project_type(&custom_type("Not A Slug")).is_err());
+
+    let created = db.create_project_type(&ProjectType { builtin: true, ..custom_type("grant") }).unwrap();
+    assert!(!created.builtin);
+    assert!(db.create_project_type(&custom_type("grant")).is_err());
+
+    let project = db.create_project("NSF/2026", None, "grant", None, None, None).unwrap();
+    let scaffold = db.scaffold_project(&project.id, date()).unwrap().unwrap();
+    assert_eq!(scaffold.folders, vec!["NSF-2026/budget"]);
+    assert_eq!(scaffold.notes[0].folder, "NSF-2026");
+    assert_eq!(scaffold.notes[0].content, "# NSF/2026 aims (2026-03-02)");
+
+    let renamed = db.update_project_type("grant", &ProjectType { name: "Grant Proposal".to_string(), ..custom_type("other") })
+        .unwrap()
+        .unwrap();
+    assert_eq!(renamed.id, "grant");
+    assert_eq!(db.get_project_type("grant").unwrap().unwrap().name, "Grant Proposal");
+    assert!(db.update_project_type("missing", &custom_type("missing")).unwrap().is_none());
+
+    assert!(db.update_project(&project.id, None, None, Some("missing"), None, None, None).is_err());
+}
+
+#[test]
+fn test_delete_project_type() {
+    let (db, _temp_dir) = setup_test_db();
+    db.create_project_type(&custom_type("grant")).unwrap();
+    let project = db.create_project("NSF", None, "grant", None, None, None).unwrap();
+
+    assert!(db.delete_project_type("research").is_err());
+    assert!(db.delete_project_type("grant").is_err());
+
+    db.delete_project(&project.id).unwrap();
+    assert!(db.delete_project_type("grant").unwrap());
+    assert!(!db.delete_project_type("grant").unwrap());
+}
+
+#[test]
+fn test_project_types_backup_round_trip() {
+    let (db, _temp_dir) = setup_test_db();
+    db.create_project_type(&custom_type("grant")).unwrap();
+    db.create_project("NSF", None, "grant", None, None, None).unwrap();
+    let backup = db.export_backup().unwrap();
+
+    let (restored, _restored_dir) = setup_test_db();
+    restored.import_backup(backup).unwrap();
+
+    let grant = restored.get_project_type("grant").unwrap().unwrap();
+    assert_eq!(grant.starter_notes[0].title, "Aims");
+    assert_eq!(restored.list_projects(Some("grant"), false).unwrap().len(), 1);
+    assert!(restored.get_project_type("research").unwrap().is_some());
+}
+
+#[test]
+fn test_migration_022_keeps_project_references() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let path = temp_dir.path().join("test.db");
+    let project_id = {
+        let db = Database::new_with_path(&path).unwrap();
+        let project = db.create_project("Thesis", None, "research", None, None, None).unwrap();
+        db.create_note("Draft", "", "inbox", Some(&project.id), None).unwrap();
+        db.update_project_settings(&project.id, r#"{"wordGoal":1000}"#).unwrap();
+
+        // Roll back to just before migration 022 so reopening runs it again
+        db.conn.execute("DELETE FROM schema_version WHERE version >= 22", []).unwrap();
+        project.id
+    };
+
+    let db = Database::new_with_path(&path).unwrap();
+    let notes = db.get_notes_by_project(&project_id).unwrap();
+    assert_eq!(notes.len(), 1);
+    assert_eq!(db.get_project_settings(&project_id).unwrap().as_deref(), Some(r#"{"wordGoal":1000}"#));
+    let foreign_keys: bool = db.conn.query_row("PRAGMA foreign_keys", [], |row| row.get(0)).unwrap();
+    assert!(foreign_keys);
+}
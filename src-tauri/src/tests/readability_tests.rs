@@ -0,0 +1,31 @@
+// Prose statistics tests
+// Tests for note and project readability statistics
+
+use super::setup_test_db;
+
+#[test]
+fn test_note_and_project_prose_stats() {
+    let (db, _temp_dir) = setup_test_db();
+    let project = db.create_project("Paper", None, "research", None, None, None).unwrap();
+    let intro = db.create_note(
+        "Intro",
+        "---\nstatus: draft\n---\nMediation is common. It is frequently tested.",
+        "notes",
+        Some(&project.id),
+        None,
+    ).unwrap();
+    db.create_note("Methods", "We fit the mediation model.\n\nWe report effects.", "notes", Some(&project.id), None).unwrap();
+    let trashed = db.create_note("Old", "Deleted words here.", "notes", Some(&project.id), None).unwrap();
+    db.delete_note(&trashed.id).unwrap();
+
+    let note = db.get_note_prose_stats(&intro.id).unwrap().unwrap();
+    assert_eq!((note.words, note.sentences, note.paragraphs), (7, 2, 1));
+    assert_eq!(note.passive_sentences, 1);
+
+    let all = db.get_project_prose_stats(&project.id).unwrap().unwrap();
+    assert_eq!((all.words, all.sentences, all.paragraphs), (15, 4, 3));
+    assert_eq!(all.repeated_words[0].word, "mediation");
+
+    assert!(db.get_note_prose_stats("missing").unwrap().is_none());
+    assert!(db.get_project_prose_stats("missing").unwrap().is_none());
+}
@@ -0,0 +1,82 @@
+// Daily rollup tests
+// Tests for carrying unfinished tasks and yesterday's notes into the daily note
+
+use crate::database::DailyRollupConfig;
+use crate::periodic::Period;
+use chrono::{Days, Local};
+use super::setup_test_db;
+
+#[test]
+fn test_todays_daily_note_carries_open_tasks() {
+    let (db, _temp_dir) = setup_test_db();
+    let today = Local::now().date_naive();
+    let two_days_ago = today - Days::new(2);
+
+    let old = db.get_or_create_periodic_note(Period::Daily, today - Days::new(10)).unwrap();
+    db.update_note(&old.id, None, Some("- [ ] Too old"), None).unwrap();
+    let earlier = db.get_or_create_periodic_note(Period::Daily, two_days_ago).unwrap();
+    db.update_note(&earlier.id, None, Some("- [ ] Call bank\n- [x] Done already\n- [ ] Email Sam"), None).unwrap();
+
+    let daily = db.get_or_create_daily_note(&today.to_string()).unwrap();
+    let title = two_days_ago.to_string();
+    assert!(daily.content.contains(&format!("## Carried over\n\n- [ ] Call bank ([[{0}]])\n- [ ] Email Sam ([[{0}]])", title)));
+    assert!(!daily.content.contains("Too old"));
+
+    let earlier = db.get_note(&earlier.id).unwrap().unwrap();
+    assert_eq!(earlier.content, "- [>] Call bank\n- [x] Done already\n- [>] Email Sam");
+
+    // Running again carries nothing new
+    let rollup = db.rollup_daily_note(today).unwrap();
+    assert_eq!(rollup.carried_tasks, 0);
+    assert_eq!(db.get_note(&daily.id).unwrap().unwrap().content.matches("Call bank").count(), 1);
+}
+
+#[test]
+fn test_rollup_keeps_original_source_and_heading() {
+    let (db, _temp_dir) = setup_test_db();
+    db.set_daily_rollup_config(&DailyRollupConfig { enabled: false, ..Default::default() }).unwrap();
+    let d1 = chrono::NaiveDate::from_ymd_opt(2025, 6, 9).unwrap();
+    let d2 = d1 + Days::new(1);
+    let d3 = d2 + Days::new(1);
+
+    let first = db.get_or_create_periodic_note(Period::Daily, d1).unwrap();
+    db.update_note(&first.id, None, Some("- [ ] Call bank"), None).unwrap();
+    db.rollup_daily_note(d2).unwrap();
+    let third = db.rollup_daily_note(d3).unwrap();
+
+    let content = db.get_note(&third.note_id).unwrap().unwrap().content;
+    assert!(content.contains("- [ ] Call bank ([[2025-06-09]])\n"));
+    assert!(!content.contains("([[2025-06-10]])"));
+
+    // New sources on a later run go under the existing heading
+    let second = db.get_or_create_periodic_note(Period::Daily, d2).unwrap();
+    db.update_note(&second.id, None, Some("- [ ] Pay rent"), None).unwrap();
+    db.rollup_daily_note(d3).unwrap();
+    let content = db.get_note(&third.note_id).unwrap().unwrap().content;
+    assert_eq!(content.matches("## Carried over").count(), 1);
+    assert!(content.contains("- [ ] Call bank ([[2025-06-09]])\n- [ ] Pay rent ([[2025-06-10]])"));
+}
+
+#[test]
+fn test_rollup_links_notes_edited_yesterday() {
+    let (db, _temp_dir) = setup_test_db();
+    db.set_daily_rollup_config(&DailyRollupConfig { enabled: false, ..Default::default() }).unwrap();
+    let date = chrono::NaiveDate::from_ymd_opt(2025, 6, 11).unwrap();
+
+    let edited = db.create_note("Draft chapter", "", "inbox", None, None).unwrap();
+    let untouched = db.create_note("Untouched", "", "inbox", None, None).unwrap();
+    let yesterday_noon = chrono::NaiveDate::from_ymd_opt(2025, 6, 10).unwrap()
+        .and_hms_opt(12, 0, 0).unwrap()
+        .and_local_timezone(Local).earliest().unwrap()
+        .timestamp();
+    db.conn.execute("UPDATE notes SET updated_at = ? WHERE id = ?", rusqlite::params![yesterday_noon, &edited.id]).unwrap();
+    db.conn.execute("UPDATE notes SET updated_at = ? WHERE id = ?", rusqlite::params![yesterday_noon - 86_400, &untouched.id]).unwrap();
+
+    let rollup = db.rollup_daily_note(date).unwrap();
+    assert_eq!(rollup.linked_notes, 1);
+    let note = db.get_note(&rollup.note_id).unwrap().unwrap();
+    assert!(note.content.ends_with("## Yesterday\n\n- [[Draft chapter]]\n"));
+
+    let again = db.rollup_daily_note(date).unwrap();
+    assert_eq!(again.linked_notes, 0);
+}
@@ -0,0 +1,236 @@
+// Tag tests
+// Tests for hierarchical tags: tree rollups and descendant queries
+
+use crate::database::Database;
+use super::setup_test_db;
+
+fn tag_id(db: &Database, name: &str) -> String {
+    db.get_tag_by_name(name).unwrap().expect("Tag should exist").id
+}
+
+#[test]
+fn test_tag_tree_rollup_counts() {
+    let (db, _temp_dir) = setup_test_db();
+
+    db.create_note("A", "#research/statistics/mediation", "inbox", None, None).unwrap();
+    db.create_note("B", "#research/statistics #research/writing", "inbox", None, None).unwrap();
+    db.create_note("C", "#research", "inbox", None, None).unwrap();
+
+    let tree = db.get_tag_tree().unwrap();
+    let research = tree.iter().find(|n| n.path == "research").unwrap();
+    assert_eq!(research.direct_count, 1);
+    // B has two research tags but is counted once
+    assert_eq!(research.total_count, 3);
+    assert_eq!(research.children.iter().map(|c| c.name.as_str()).collect::<Vec<_>>(), vec!["statistics", "writing"]);
+
+    let statistics = &research.children[0];
+    assert_eq!((statistics.direct_count, statistics.total_count), (1, 2));
+    assert_eq!(statistics.children[0].path, "research/statistics/mediation");
+    assert!(statistics.children[0].id.is_some());
+}
+
+#[test]
+fn test_tag_tree_implied_parent() {
+    let (db, _temp_dir) = setup_test_db();
+
+    db.create_note("A", "#teaching/stat101", "inbox", None, None).unwrap();
+
+    let tree = db.get_tag_tree().unwrap();
+    let teaching = tree.iter().find(|n| n.path == "teaching").unwrap();
+    assert!(teaching.id.is_none());
+    assert_eq!((teaching.direct_count, teaching.total_count), (0, 1));
+}
+
+#[test]
+fn test_notes_by_tag_include_descendants() {
+    let (db, _temp_dir) = setup_test_db();
+
+    let a = db.create_note("A", "#research/statistics", "inbox", None, None).unwrap();
+    let b = db.create_note("B", "#research #urgent", "inbox", None, None).unwrap();
+    let c = db.create_note("C", "#researcher #urgent", "inbox", None, None).unwrap();
+    db.create_note("D", "#research/writing #urgent", "inbox", None, None).unwrap();
+
+    let research = tag_id(&db, "research");
+    let urgent = tag_id(&db, "urgent");
+
+    assert_eq!(db.get_notes_by_tag(&research, false).unwrap().len(), 1);
+    let all: Vec<String> = db.get_notes_by_tag(&research, true).unwrap().into_iter().map(|n| n.id).collect();
+    assert_eq!(all.len(), 3);
+    assert!(all.contains(&a.id) && all.contains(&b.id) && !all.contains(&c.id));
+
+    let both = db.filter_notes_by_tags(vec![research.clone(), urgent.clone()], true, false).unwrap();
+    assert_eq!(both.len(), 1);
+    let both = db.filter_notes_by_tags(vec![research.clone(), urgent.clone()], true, true).unwrap();
+    assert_eq!(both.len(), 2);
+    let either = db.filter_notes_by_tags(vec![research, urgent], false, true).unwrap();
+    assert_eq!(either.len(), 4);
+
+    // Children are matched with Unicode case folding
+    let cafe = db.create_note("E", "#café", "inbox", None, None).unwrap();
+    let child = db.create_note("F", "#CAFÉ/menu", "inbox", None, None).unwrap();
+    let ids: Vec<String> = db.get_notes_by_tag(&tag_id(&db, "café"), true).unwrap().into_iter().map(|n| n.id).collect();
+    assert!(ids.contains(&cafe.id) && ids.contains(&child.id));
+}
+
+#[test]
+fn test_rename_tag_rewrites_notes_and_children() {
+    let (db, _temp_dir) = setup_test_db();
+
+    let a = db.create_note("A", "#proj and #proj/alpha and #project", "inbox", None, None).unwrap();
+    let b = db.create_note("B", "Only #Proj/beta here", "inbox", None, None).unwrap();
+    let proj = tag_id(&db, "proj");
+
+    let preview = db.rename_tag_in_notes(&proj, "work", true).unwrap().unwrap();
+    assert!(preview.dry_run);
+    assert_eq!(preview.tags.len(), 3);
+    assert_eq!(preview.modified_notes.iter().map(|n| n.occurrences).sum::<usize>(), 3);
+    assert_eq!(db.get_note(&a.id).unwrap().unwrap().content, "#proj and #proj/alpha and #project");
+
+    db.rename_tag_in_notes(&proj, "work", false).unwrap().unwrap();
+    assert_eq!(db.get_note(&a.id).unwrap().unwrap().content, "#work and #work/alpha and #project");
+    assert_eq!(db.get_note(&b.id).unwrap().unwrap().content, "Only #work/beta here");
+    assert!(db.get_tag_by_name("proj/alpha").unwrap().is_none());
+    assert_eq!(tag_id(&db, "work"), proj);
+
+    // Re-syncing from content must not bring the old tag back
+    let content = db.get_note(&a.id).unwrap().unwrap().content;
+    db.update_note_tags(&a.id, &content).unwrap();
+    assert!(db.get_tag_by_name("proj").unwrap().is_none());
+
+    let err = db.rename_tag_in_notes(&proj, "bad name", false).unwrap_err();
+    assert!(err.to_string().contains("Invalid tag name"));
+}
+
+#[test]
+fn test_rename_tag_matches_non_ascii_case() {
+    let (db, _temp_dir) = setup_test_db();
+
+    let note = db.create_note("Plans", "#Été/plans and #été", "inbox", None, None).unwrap();
+    assert!(db.rename_tag(&tag_id(&db, "été"), "summer").unwrap());
+
+    assert_eq!(db.get_note(&note.id).unwrap().unwrap().content, "#summer/plans and #summer");
+    assert!(db.get_tag_by_name("summer/plans").unwrap().is_some());
+    assert!(!db.rename_tag("missing", "winter").unwrap());
+}
+
+#[test]
+fn test_merge_tags() {
+    let (db, _temp_dir) = setup_test_db();
+
+    let a = db.create_note("A", "#stats #statistics", "inbox", None, None).unwrap();
+    let b = db.create_note("B", "#stat #stat/bayes", "inbox", None, None).unwrap();
+    db.create_note("C", "#statistics/bayes", "inbox", None, None).unwrap();
+
+    let target = tag_id(&db, "statistics");
+    let sources = vec![tag_id(&db, "stats"), tag_id(&db, "stat")];
+    let bayes = tag_id(&db, "statistics/bayes");
+    let stat_bayes = tag_id(&db, "stat/bayes");
+
+    let result = db.merge_tags(&sources, &target, false).unwrap().unwrap();
+    let merged: Vec<_> = result.tags.iter().filter(|t| t.merged_into.is_some()).map(|t| t.old_name.as_str()).collect();
+    assert_eq!(merged, vec!["stat", "stat/bayes", "stats"]);
+
+    assert_eq!(db.get_note(&a.id).unwrap().unwrap().content, "#statistics #statistics");
+    assert_eq!(db.get_note(&b.id).unwrap().unwrap().content, "#statistics #statistics/bayes");
+    assert!(db.get_tag(&sources[0]).unwrap().is_none());
+    assert!(db.get_tag(&stat_bayes).unwrap().is_none());
+
+    let tags: Vec<String> = db.get_note_tags(&a.id).unwrap().into_iter().map(|t| t.id).collect();
+    assert_eq!(tags, vec![target.clone()]);
+    assert_eq!(db.get_notes_by_tag(&bayes, false).unwrap().len(), 2);
+}
+
+#[test]
+fn test_update_note_tags_ignores_code_and_reads_frontmatter() {
+    let (db, _temp_dir) = setup_test_db();
+
+    let content = "---\ntags: [draft]\n---\n#café notes\n```r\nplot(col = \"#ff0000\")\n# comment\n```\n";
+    let note = db.create_note("Tagged", content, "inbox", None, None).unwrap();
+
+    let names: Vec<String> = db.get_note_tags(&note.id).unwrap().into_iter().map(|t| t.name).collect();
+    assert_eq!(names, vec!["café", "draft"]);
+
+    // Renaming a frontmatter tag rewrites the frontmatter entry
+    db.rename_tag_in_notes(&tag_id(&db, "draft"), "final", false).unwrap();
+    assert!(db.get_note(&note.id).unwrap().unwrap().content.starts_with("---\ntags: [final]\n---"));
+}
+
+#[test]
+fn test_update_tag_metadata_and_parent_override() {
+    let (db, _temp_dir) = setup_test_db();
+
+    db.create_note("A", "#stats #research/methods", "inbox", None, None).unwrap();
+    let stats = tag_id(&db, "stats");
+    let research = db.create_tag("research", None).unwrap().id;
+
+    let color = db.get_tag(&stats).unwrap().unwrap().color.unwrap();
+    assert!(color.starts_with('#') && color.len() == 7);
+
+    let updates = crate::database::TagUpdate {
+        description: Some("Quantitative methods".to_string()),
+        icon: Some("📊".to_string()),
+        pinned: Some(true),
+        sort_order: Some(2),
+        parent_id: Some(research.clone()),
+        ..Default::default()
+    };
+    let tag = db.update_tag(&stats, &updates).unwrap().unwrap();
+    assert_eq!(tag.description.as_deref(), Some("Quantitative methods"));
+    assert!(tag.pinned);
+    assert_eq!(tag.parent_id.as_deref(), Some(research.as_str()));
+
+    // The override moves #stats under #research, ahead of unpinned siblings
+    let tree = db.get_tag_tree().unwrap();
+    let research_node = tree.iter().find(|n| n.path == "research").unwrap();
+    assert_eq!(research_node.children[0].path, "stats");
+    assert_eq!(research_node.children[0].icon.as_deref(), Some("📊"));
+    assert_eq!(research_node.total_count, 1);
+    assert!(tree.iter().all(|n| n.path != "stats"));
+
+    // Cycles are rejected; an empty string clears a field
+    let cycle = crate::database::TagUpdate { parent_id: Some(stats.clone()), ..Default::default() };
+    assert!(db.update_tag(&research, &cycle).is_err());
+    assert!(db.update_tag(&stats, &crate::database::TagUpdate { parent_id: Some(stats.clone()), ..Default::default() }).is_err());
+    let cleared = db.update_tag(&stats, &crate::database::TagUpdate { description: Some(String::new()), ..Default::default() }).unwrap().unwrap();
+    assert!(cleared.description.is_none());
+
+    // Metadata survives a backup round trip
+    let backup = db.export_backup().unwrap();
+    db.import_backup(backup).unwrap();
+    let restored = db.get_tag(&stats).unwrap().unwrap();
+    assert!(restored.pinned);
+    assert_eq!(restored.sort_order, 2);
+    assert_eq!(restored.icon.as_deref(), Some("📊"));
+}
+
+#[test]
+fn test_update_tag_rename_into_existing_keeps_other_fields() {
+    let (db, _temp_dir) = setup_test_db();
+
+    let note = db.create_note("A", "#stats and #statistics", "inbox", None, None).unwrap();
+    let stats = tag_id(&db, "stats");
+    let statistics = tag_id(&db, "statistics");
+
+    // A failed parent check rolls the rename back too
+    let bad_parent = crate::database::TagUpdate {
+        name: Some("statistics".to_string()),
+        parent_id: Some(statistics.clone()),
+        ..Default::default()
+    };
+    assert!(db.update_tag(&stats, &bad_parent).is_err());
+    assert!(db.get_tag(&stats).unwrap().is_some());
+    assert_eq!(db.get_note(&note.id).unwrap().unwrap().content, "#stats and #statistics");
+
+    let updates = crate::database::TagUpdate {
+        name: Some("statistics".to_string()),
+        color: Some("#123456".to_string()),
+        icon: Some("📊".to_string()),
+        ..Default::default()
+    };
+    let merged = db.update_tag(&stats, &updates).unwrap().unwrap();
+    assert_eq!(merged.id, statistics);
+    assert_eq!(merged.color.as_deref(), Some("#123456"));
+    assert_eq!(merged.icon.as_deref(), Some("📊"));
+    assert!(db.get_tag(&stats).unwrap().is_none());
+    assert_eq!(db.get_note(&note.id).unwrap().unwrap().content, "#statistics and #statistics");
+}
@@ -0,0 +1,133 @@
+// Task index tests
+// Tests for indexing checklist items, querying them and toggling them in place
+
+use crate::database::{Database, TaskQuery};
+use tempfile::TempDir;
+
+fn setup_test_db() -> (Database, TempDir) {
+    let (db, temp_dir) = super::setup_test_db();
+    // The welcome notes come with a checklist of their own
+    db.conn.execute_batch("DELETE FROM tasks; DELETE FROM notes;").unwrap();
+    (db, temp_dir)
+}
+
+#[test]
+fn test_tasks_are_indexed_on_create_and_update() {
+    let (db, _temp_dir) = setup_test_db();
+    let note = db.create_note(
+        "Plan",
+        "# Plan\n\n- [ ] Draft intro 📅 2026-10-20 ⏫ #thesis\n- [x] Outline\n```\n- [ ] not a task\n```\n",
+        "inbox",
+        None,
+        None,
+    ).unwrap();
+
+    let all = db.query_tasks(&TaskQuery { include_done: true, ..Default::default() }).unwrap();
+    assert_eq!(all.len(), 2);
+
+    let open = db.query_tasks(&TaskQuery::default()).unwrap();
+    assert_eq!(open.len(), 1);
+    let task = &open[0];
+    assert_eq!(task.note_id, note.id);
+    assert_eq!(task.note_title, "Plan");
+    assert_eq!(task.line, 2);
+    assert_eq!(task.text, "Draft intro #thesis");
+    assert_eq!(task.due_date.as_deref(), Some("2026-10-20"));
+    assert_eq!(task.priority.as_deref(), Some("high"));
+    assert_eq!(task.tags, vec!["thesis".to_string()]);
+    assert!(!task.checked);
+
+    db.update_note(&note.id, None, Some("- [ ] Something else"), None).unwrap();
+    let open = db.query_tasks(&TaskQuery::default()).unwrap();
+    assert_eq!(open.len(), 1);
+    assert_eq!(open[0].text, "Something else");
+    assert_eq!(open[0].due_date, None);
+}
+
+#[test]
+fn test_query_tasks_by_due_date_project_and_tag() {
+    let (db, _temp_dir) = setup_test_db();
+    let project = db.create_project("Thesis", None, "research", None, None, None).unwrap();
+    let chapter = db.create_note(
+        "Chapter 1",
+        "- [ ] Revise methods due:2026-11-02\n- [ ] Fix figures 📅 2026-10-19 🔽\n- [ ] Someday",
+        "notes",
+        Some(&project.id),
+        None,
+    ).unwrap();
+    db.create_note(
+        "Errands",
+        "- [ ] Pay rent 📅 2026-10-19 🔺\n- [ ] Book trip #travel/europe",
+        "inbox",
+        None,
+        None,
+    ).unwrap();
+    db.create_note("Reading", "#thesis\n\n- [ ] Read Baron & Kenny", "inbox", None, None).unwrap();
+
+    // Soonest due first, higher priority first on the same day, undated last
+    let texts: Vec<String> = db.query_tasks(&TaskQuery::default()).unwrap().into_iter().map(|t| t.text).collect();
+    assert_eq!(texts[..3], ["Pay rent", "Fix figures", "Revise methods"]);
+    assert_eq!(texts.len(), 6);
+    assert_eq!(texts[5], "Read Baron & Kenny");
+
+    let due_soon = db.query_tasks(&TaskQuery { due_to: Some("2026-10-31".into()), ..Default::default() }).unwrap();
+    assert_eq!(due_soon.len(), 2);
+
+    let later = db.query_tasks(&TaskQuery { due_from: Some("2026-10-20".into()), ..Default::default() }).unwrap();
+    assert_eq!(later.len(), 1);
+    assert_eq!(later[0].text, "Revise methods");
+
+    let in_project = db.query_tasks(&TaskQuery { project_id: Some(project.id.clone()), ..Default::default() }).unwrap();
+    assert_eq!(in_project.len(), 3);
+    assert!(in_project.iter().all(|t| t.note_id == chapter.id));
+
+    // Parent tags match child tags; tags elsewhere in the note don't count
+    let travel = db.query_tasks(&TaskQuery { tag: Some("#travel".into()), ..Default::default() }).unwrap();
+    assert_eq!(travel.len(), 1);
+    assert_eq!(travel[0].text, "Book trip #travel/europe");
+    assert!(db.query_tasks(&TaskQuery { tag: Some("Thesis".into()), ..Default::default() }).unwrap().is_empty());
+    assert!(db.query_tasks(&TaskQuery { tag: Some("trav".into()), ..Default::default() }).unwrap().is_empty());
+
+    db.create_note("Café", "- [ ] Order beans #CAFÉ/supplies", "inbox", None, None).unwrap();
+    let cafe = db.query_tasks(&TaskQuery { tag: Some("café".into()), ..Default::default() }).unwrap();
+    assert_eq!(cafe.len(), 1);
+}
+
+#[test]
+fn test_toggle_task_rewrites_the_line() {
+    let (db, _temp_dir) = setup_test_db();
+    let note = db.create_note("Todo", "Intro\n- [ ] First\n  - [ ] Nested 📅 2026-10-20", "inbox", None, None).unwrap();
+
+    let task = db.toggle_task(&note.id, 2).unwrap().unwrap();
+    assert!(task.checked);
+    assert_eq!(task.status, "x");
+    assert_eq!(task.text, "Nested");
+    assert_eq!(db.get_note(&note.id).unwrap().unwrap().content, "Intro\n- [ ] First\n  - [x] Nested 📅 2026-10-20");
+
+    let open = db.query_tasks(&TaskQuery::default()).unwrap();
+    assert_eq!(open.len(), 1);
+    assert_eq!(open[0].text, "First");
+
+    let task = db.toggle_task(&note.id, 2).unwrap().unwrap();
+    assert!(!task.checked);
+    assert_eq!(db.query_tasks(&TaskQuery::default()).unwrap().len(), 2);
+
+    // Lines without a task, and unknown notes, are left alone
+    assert!(db.toggle_task(&note.id, 0).unwrap().is_none());
+    assert!(db.toggle_task("missing", 1).unwrap().is_none());
+}
+
+#[test]
+fn test_tasks_in_archived_and_deleted_notes_are_hidden() {
+    let (db, _temp_dir) = setup_test_db();
+    let archived = db.create_note("Old", "- [ ] Archived task", "inbox", None, None).unwrap();
+    let deleted = db.create_note("Gone", "- [ ] Deleted task", "inbox", None, None).unwrap();
+    db.create_note("Live", "- [ ] Live task", "inbox", None, None).unwrap();
+
+    db.set_note_archived(&archived.id, true).unwrap();
+    db.delete_note(&deleted.id).unwrap();
+
+    let open = db.query_tasks(&TaskQuery::default()).unwrap();
+    assert_eq!(open.len(), 1);
+    assert_eq!(open[0].text, "Live task");
+}
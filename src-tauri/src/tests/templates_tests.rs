@@ -0,0 +1,93 @@
+// Template tests
+// Tests for template CRUD, note creation from templates and default templates
+
+use crate::database::TemplateUpdate;
+use chrono::NaiveDate;
+use super::setup_test_db;
+
+const STATUS_PROPERTY: &str = r#"{"status":{"key":"status","type":"text","value":"draft for {{project.name}}"}}"#;
+
+#[test]
+fn test_template_crud() {
+    let (db, _temp_dir) = setup_test_db();
+
+    let template = db.create_template("Meeting", "# {{title}}", None, None, &["#meeting".to_string(), "Meeting".to_string()]).unwrap();
+    assert_eq!(template.tags, vec!["meeting".to_string()]);
+    assert!(db.create_template("meeting", "", None, None, &[]).is_err());
+    assert!(db.create_template("Bad", "", None, None, &["no spaces".to_string()]).is_err());
+
+    let update = TemplateUpdate {
+        description: Some("Weekly".to_string()),
+        tags: Some(vec![]),
+        ..Default::default()
+    };
+    let updated = db.update_template(&template.id, &update).unwrap().unwrap();
+    assert_eq!(updated.description.as_deref(), Some("Weekly"));
+    assert!(updated.tags.is_empty());
+    assert_eq!(updated.content, "# {{title}}");
+
+    assert_eq!(db.list_templates().unwrap().len(), 1);
+    assert!(db.delete_template(&template.id).unwrap());
+    assert!(db.get_template(&template.id).unwrap().is_none());
+}
+
+#[test]
+fn test_create_note_from_template() {
+    let (db, _temp_dir) = setup_test_db();
+    let project = db.create_project("Thesis", None, "research", None, None, None).unwrap();
+    let template = db.create_template(
+        "Chapter",
+        "# {{title}}\n\n{{project.name}} · {{date:D MMMM YYYY}}\n\n{{cursor}}",
+        None,
+        Some(STATUS_PROPERTY),
+        &["chapter".to_string()],
+    ).unwrap();
+
+    let date = NaiveDate::from_ymd_opt(2025, 1, 9);
+    let created = db.create_note_from_template(Some(&template.id), "Methods", "drafts", Some(&project.id), date).unwrap();
+    assert_eq!(created.note.content, "# Methods\n\nThesis · 9 January 2025\n\n#chapter");
+    assert_eq!(created.cursor, Some("# Methods\n\nThesis · 9 January 2025\n\n".chars().count()));
+    assert!(created.note.properties.unwrap().contains("draft for Thesis"));
+
+    let tags: Vec<String> = db.get_note_tags(&created.note.id).unwrap().into_iter().map(|t| t.name).collect();
+    assert_eq!(tags, vec!["chapter".to_string()]);
+
+    assert!(db.create_note_from_template(Some("missing"), "X", "inbox", None, None).is_err());
+}
+
+#[test]
+fn test_project_default_template() {
+    let (db, _temp_dir) = setup_test_db();
+    let project = db.create_project("Thesis", None, "research", None, None, Some(r#"{"wordGoal":500}"#)).unwrap();
+    let template = db.create_template("Chapter", "Chapter of {{project.name}}", None, None, &[]).unwrap();
+
+    let plain = db.create_note_from_template(None, "Before", "inbox", Some(&project.id), None).unwrap();
+    assert_eq!(plain.note.content, "");
+
+    let project = db.set_project_default_template(&project.id, Some(&template.id)).unwrap().unwrap();
+    assert!(project.settings.as_deref().unwrap().contains("wordGoal"));
+
+    let created = db.create_note_from_template(None, "After", "inbox", Some(&project.id), None).unwrap();
+    assert_eq!(created.note.content, "Chapter of Thesis");
+    assert!(db.set_project_default_template(&project.id, Some("missing")).is_err());
+}
+
+#[test]
+fn test_daily_note_template() {
+    let (db, _temp_dir) = setup_test_db();
+
+    let plain = db.get_or_create_daily_note("2025-02-01").unwrap();
+    assert_eq!(plain.content, "## 2025-02-01\n\n");
+
+    let template = db.create_template("Daily", "# {{date:dddd, MMMM D}}\n\n## Focus\n", None, None, &[]).unwrap();
+    db.set_daily_note_template(Some(&template.id)).unwrap();
+    assert_eq!(db.get_daily_note_template().unwrap().unwrap().id, template.id);
+
+    let daily = db.get_or_create_daily_note("2025-02-03").unwrap();
+    assert_eq!(daily.content, "# Monday, February 3\n\n## Focus\n");
+    assert_eq!(daily.folder, "daily");
+    assert_eq!(db.get_or_create_daily_note("2025-02-03").unwrap().id, daily.id);
+
+    db.delete_template(&template.id).unwrap();
+    assert!(db.get_daily_note_template().unwrap().is_none());
+}
@@ -0,0 +1,52 @@
+// Vault health tests
+// Tests for the orphan / dead-end / hub maintenance report
+
+use super::setup_test_db;
+
+fn ids(notes: &[crate::database::HealthNote]) -> Vec<&str> {
+    notes.iter().map(|n| n.id.as_str()).collect()
+}
+
+#[test]
+fn test_vault_health_report() {
+    let (db, _temp_dir) = setup_test_db();
+
+    let sink = db.create_note("Sink", "no links here", "notes", None, None).unwrap();
+    let hub = db.create_note("Health Hub", "[[Spoke 1]] [[Sink]]", "notes", None, None).unwrap();
+    let spokes: Vec<_> = (1..=3)
+        .map(|i| db.create_note(&format!("Spoke {}", i), "[[Health Hub]]", "notes", None, None).unwrap())
+        .collect();
+    let orphan = db.create_note("Lonely", "   \n\t", "inbox", None, None).unwrap();
+    let duplicate = db.create_note("lonely", "text", "notes", None, None).unwrap();
+    db.add_tag_to_note(&duplicate.id, "filed").unwrap();
+
+    db.conn.execute(
+        "UPDATE notes SET created_at = created_at - 30 * 86400 WHERE id = ?",
+        [&orphan.id],
+    ).unwrap();
+
+    let report = db.get_vault_health(Some(14), Some(3)).unwrap();
+
+    assert!(ids(&report.orphans).contains(&orphan.id.as_str()));
+    assert!(!ids(&report.orphans).contains(&hub.id.as_str()));
+    assert!(ids(&report.dead_ends).contains(&sink.id.as_str()));
+    assert!(!ids(&report.dead_ends).contains(&spokes[1].id.as_str()));
+    assert_eq!(report.hubs[0].id, hub.id);
+    assert_eq!(report.hubs[0].inbound_links, 3);
+    assert_eq!(report.hubs[0].outbound_links, 2);
+
+    assert!(ids(&report.unfiled).contains(&orphan.id.as_str()));
+    assert!(!ids(&report.unfiled).contains(&duplicate.id.as_str()));
+    assert_eq!(ids(&report.stale_inbox), vec![orphan.id.as_str()]);
+    assert_eq!(ids(&report.empty), vec![orphan.id.as_str()]);
+
+    let dup = report.duplicate_titles.iter().find(|d| d.title == "Lonely").unwrap();
+    assert_eq!(ids(&dup.notes), vec![orphan.id.as_str(), duplicate.id.as_str()]);
+
+    // Trashing a linker removes its inbound link from the counts
+    db.conn.execute("UPDATE notes SET deleted_at = strftime('%s', 'now') WHERE id = ?", [&spokes[0].id]).unwrap();
+    let report = db.get_vault_health(None, Some(3)).unwrap();
+    assert_eq!(report.hubs[0].id, hub.id);
+    assert_eq!(report.hubs[0].inbound_links, 2);
+    assert_eq!(report.hubs[0].outbound_links, 1);
+}
@@ -0,0 +1,134 @@
+// Writing progress tests
+// Tests for the daily writing log, streaks and word count goals
+
+use crate::database::{Database, DailyRollupConfig};
+use crate::periodic::Period;
+use chrono::{Days, Local, NaiveDate};
+use super::setup_test_db;
+
+fn log_day(db: &Database, date: NaiveDate, note_id: &str, words: i64) {
+    db.conn.execute(
+        "INSERT INTO writing_log (date, note_id, words_added) VALUES (?, ?, ?)",
+        rusqlite::params![date.to_string(), note_id, words],
+    ).unwrap();
+}
+
+#[test]
+fn test_update_note_logs_word_deltas() {
+    let (db, _temp_dir) = setup_test_db();
+    let today = Local::now().date_naive();
+    let note = db.create_note("Draft", "one two", "inbox", None, None).unwrap();
+
+    db.update_note(&note.id, None, Some("one two three four five"), None).unwrap();
+    db.update_note(&note.id, None, Some("one two three four"), None).unwrap();
+    // Title-only edits don't touch the log
+    db.update_note(&note.id, Some("Draft 2"), None, None).unwrap();
+
+    let days = db.get_writing_heatmap(today, today, None).unwrap();
+    assert_eq!(days.len(), 1);
+    assert_eq!(days[0].date, today.to_string());
+    assert_eq!(days[0].words_added, 5);
+    assert_eq!(days[0].words_removed, 1);
+
+    let streak = db.get_writing_streak(today).unwrap();
+    assert_eq!(streak.current, 1);
+    assert_eq!(streak.words_today, 5);
+}
+
+#[test]
+fn test_generated_edits_are_not_logged() {
+    let (db, _temp_dir) = setup_test_db();
+    db.set_daily_rollup_config(&DailyRollupConfig { enabled: false, ..Default::default() }).unwrap();
+    let today = Local::now().date_naive();
+    let words_today = |db: &Database| db.get_writing_streak(today).unwrap().words_today;
+
+    let day = NaiveDate::from_ymd_opt(2026, 3, 2).unwrap();
+    let daily = db.get_or_create_periodic_note(Period::Daily, day).unwrap();
+    assert_eq!(words_today(&db), 0);
+
+    db.update_note(&daily.id, None, Some("- [ ] Call bank\n- [ ] Email Sam"), None).unwrap();
+    let written = words_today(&db);
+    assert!(written > 0);
+
+    db.toggle_task(&daily.id, 1).unwrap().unwrap();
+    db.rollup_daily_note(day + Days::new(1)).unwrap();
+    assert_eq!(words_today(&db), written);
+}
+
+#[test]
+fn test_heatmap_filters_by_project() {
+    let (db, _temp_dir) = setup_test_db();
+    let project = db.create_project("Thesis", None, "research", None, None, None).unwrap();
+    let chapter = db.create_note("Chapter", "", "notes", Some(&project.id), None).unwrap();
+    let other = db.create_note("Other", "", "inbox", None, None).unwrap();
+    let day = NaiveDate::from_ymd_opt(2026, 3, 2).unwrap();
+    log_day(&db, day, &chapter.id, 400);
+    log_day(&db, day, &other.id, 100);
+    log_day(&db, day + Days::new(1), &other.id, 50);
+
+    let all = db.get_writing_heatmap(day, day + Days::new(6), None).unwrap();
+    assert_eq!(all.iter().map(|d| d.words_added).collect::<Vec<_>>(), vec![500, 50]);
+
+    let thesis = db.get_writing_heatmap(day, day + Days::new(6), Some(&project.id)).unwrap();
+    assert_eq!(thesis.len(), 1);
+    assert_eq!(thesis[0].words_added, 400);
+}
+
+#[test]
+fn test_writing_streaks() {
+    let (db, _temp_dir) = setup_test_db();
+    let note = db.create_note("Journal", "", "inbox", None, None).unwrap();
+    let today = NaiveDate::from_ymd_opt(2026, 5, 10).unwrap();
+    for days_ago in [1, 2, 3, 6, 7, 8, 9] {
+        log_day(&db, today - Days::new(days_ago), &note.id, 200);
+    }
+
+    // Nothing yet today, but yesterday keeps the streak alive
+    let streak = db.get_writing_streak(today).unwrap();
+    assert_eq!(streak.current, 3);
+    assert_eq!(streak.longest, 4);
+    assert_eq!(streak.words_today, 0);
+    assert_eq!(streak.last_written.as_deref(), Some("2026-05-09"));
+
+    assert_eq!(db.get_writing_streak(today + Days::new(1)).unwrap().current, 0);
+}
+
+#[test]
+fn test_goal_progress_for_notes_and_projects() {
+    let (db, _temp_dir) = setup_test_db();
+    let today = NaiveDate::from_ymd_opt(2026, 10, 18).unwrap();
+    let note = db.create_note(
+        "Essay",
+        "word ".repeat(250).trim(),
+        "inbox",
+        None,
+        Some(r#"{"word_goal":{"key":"word_goal","type":"number","value":1000},"due":{"key":"due","type":"date","value":"2026-10-20"}}"#),
+    ).unwrap();
+
+    let progress = db.get_note_goal_progress(&note.id, today).unwrap().unwrap();
+    assert_eq!(progress.word_count, 250);
+    assert_eq!(progress.word_goal, Some(1000));
+    assert_eq!(progress.words_remaining, Some(750));
+    assert_eq!(progress.percent, Some(25.0));
+    assert_eq!(progress.days_left, Some(3));
+    assert_eq!(progress.daily_target, Some(250));
+
+    let project = db.create_project("Book", None, "generic", None, None, None).unwrap();
+    db.create_note("Ch 1", "a b c d", "notes", Some(&project.id), None).unwrap();
+    db.create_note("Ch 2", "e f g h i j", "notes", Some(&project.id), None).unwrap();
+    db.update_project_settings(&project.id, r#"{"wordGoal": 5, "deadline": "2026-10-01"}"#).unwrap();
+
+    let progress = db.get_project_goal_progress(&project.id, today).unwrap().unwrap();
+    assert_eq!(progress.word_count, 10);
+    assert_eq!(progress.words_remaining, Some(0));
+    assert_eq!(progress.percent, Some(100.0));
+    assert_eq!(progress.days_left, Some(-16));
+    assert_eq!(progress.daily_target, None);
+
+    // No goal set
+    let plain = db.create_note("Plain", "text", "inbox", None, None).unwrap();
+    let progress = db.get_note_goal_progress(&plain.id, today).unwrap().unwrap();
+    assert_eq!(progress.word_goal, None);
+    assert_eq!(progress.percent, None);
+    assert!(db.get_project_goal_progress("missing", today).unwrap().is_none());
+}